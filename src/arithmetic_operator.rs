@@ -0,0 +1,77 @@
+use crate::{errors::SqlError, value::Value};
+
+/// Arithmetic operators used in the expressions of the `RETURNING` clause.
+/// - `Add`: Addition operator, `+`
+/// - `Subtract`: Subtraction operator, `-`
+/// - `Multiply`: Multiplication operator, `*`
+/// - `Divide`: Integer division operator, `/`
+/// - `Concat`: Text concatenation operator, `||`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithmeticOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Concat,
+}
+
+impl ArithmeticOperator {
+    /// Returns the operator written as the given token, if it is one.
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "+" => Some(ArithmeticOperator::Add),
+            "-" => Some(ArithmeticOperator::Subtract),
+            "*" => Some(ArithmeticOperator::Multiply),
+            "/" => Some(ArithmeticOperator::Divide),
+            "||" => Some(ArithmeticOperator::Concat),
+            _ => None,
+        }
+    }
+
+    /// Returns the token the operator is written as.
+    pub fn token(&self) -> &'static str {
+        match self {
+            ArithmeticOperator::Add => "+",
+            ArithmeticOperator::Subtract => "-",
+            ArithmeticOperator::Multiply => "*",
+            ArithmeticOperator::Divide => "/",
+            ArithmeticOperator::Concat => "||",
+        }
+    }
+
+    /// Returns how tightly the operator binds its operands: `*` and `/` before `+` and `-`, and those before `||`.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            ArithmeticOperator::Concat => 1,
+            ArithmeticOperator::Add | ArithmeticOperator::Subtract => 2,
+            ArithmeticOperator::Multiply | ArithmeticOperator::Divide => 3,
+        }
+    }
+
+    /// Applies the operator to two values.
+    ///
+    /// The result is empty if any of the values is empty. `||` joins the values as they are stored in the tables,
+    /// while the rest of the operators only accept integers and return an `InvalidSyntax` error for text.
+    /// Dividing by zero or overflowing an integer returns an `Error`.
+    pub fn apply(&self, left: &Value, right: &Value) -> Result<Value, SqlError> {
+        if left.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
+        if *self == ArithmeticOperator::Concat {
+            return Ok(Value::from_csv(&(left.to_csv() + right.to_csv())));
+        }
+
+        let (Some(x), Some(y)) = (left.as_integer(), right.as_integer()) else {
+            return Err(SqlError::InvalidSyntax);
+        };
+        let result = match self {
+            ArithmeticOperator::Add => x.checked_add(y),
+            ArithmeticOperator::Subtract => x.checked_sub(y),
+            ArithmeticOperator::Multiply => x.checked_mul(y),
+            ArithmeticOperator::Divide => x.checked_div(y),
+            ArithmeticOperator::Concat => None,
+        };
+        result.map(Value::Integer).ok_or(SqlError::Error)
+    }
+}
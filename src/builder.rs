@@ -0,0 +1,434 @@
+use crate::{
+    clauses::{
+        condition::Condition,
+        delete_sql::Delete,
+        insert_sql::Insert,
+        into_sql::Into,
+        orderby_sql::{Order, OrderBy},
+        returning_sql::Returning,
+        select_sql::Select,
+        set_sql::Set,
+        update_sql::Update,
+        where_sql::Where,
+    },
+    errors::SqlError,
+    logical_operator::LogicalOperator,
+    operator::Operator,
+    statement::{check_value, Statement},
+    value::Value,
+};
+use std::ops::Not;
+
+/// Struct that represents a column compared in the condition of a query built with `Query`, see `col`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Col(String);
+
+/// Returns the column with the given name, to compare it with a value.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_sql::col;
+///
+/// let filter = col("edad").gt(18).and(col("nombre").eq("Ana"));
+/// ```
+///
+pub fn col(name: &str) -> Col {
+    Col(name.to_string())
+}
+
+impl Col {
+    fn compare(self, operator: Operator, value: impl std::convert::Into<Value>) -> Filter {
+        Filter(Condition::Simple {
+            field: self.0,
+            operator,
+            value: value.into().to_string(),
+        })
+    }
+
+    /// Returns the condition met by the registers whose value of the column is equal to the given one.
+    pub fn eq(self, value: impl std::convert::Into<Value>) -> Filter {
+        self.compare(Operator::Equal, value)
+    }
+
+    /// Returns the condition met by the registers whose value of the column is greater than the given one.
+    pub fn gt(self, value: impl std::convert::Into<Value>) -> Filter {
+        self.compare(Operator::Greater, value)
+    }
+
+    /// Returns the condition met by the registers whose value of the column is lesser than the given one.
+    pub fn lt(self, value: impl std::convert::Into<Value>) -> Filter {
+        self.compare(Operator::Lesser, value)
+    }
+}
+
+/// Struct that represents the condition of a query built with `Query`, like the one of a `WHERE` clause.
+///
+/// Conditions are combined with `and` and `or`, and negated with `!`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter(Condition);
+
+impl Filter {
+    /// Returns the condition met by the registers that meet both conditions.
+    pub fn and(self, other: Filter) -> Filter {
+        Filter(Condition::new_complex(
+            Some(self.0),
+            LogicalOperator::And,
+            other.0,
+        ))
+    }
+
+    /// Returns the condition met by the registers that meet any of the conditions.
+    pub fn or(self, other: Filter) -> Filter {
+        Filter(Condition::new_complex(
+            Some(self.0),
+            LogicalOperator::Or,
+            other.0,
+        ))
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter(Condition::new_complex(None, LogicalOperator::Not, self.0))
+    }
+}
+
+/// Adds a condition to the `WHERE` clause of a query, which must also meet the previous one if there is any.
+fn add_filter(where_clause: &mut Option<Where>, filter: Filter) {
+    let condition = match where_clause.take() {
+        Some(previous) => Filter(previous.condition).and(filter).0,
+        None => filter.0,
+    };
+    *where_clause = Some(Where { condition });
+}
+
+fn returning<I, S>(columns: I) -> Option<Returning>
+where
+    I: IntoIterator<Item = S>,
+    S: std::convert::Into<String>,
+{
+    let columns: Vec<String> = columns.into_iter().map(|column| column.into()).collect();
+    Some(Returning::columns(columns))
+}
+
+/// Struct that represents a statement built in Rust code instead of parsed from a query.
+///
+/// The statement is built with the same clauses the parser produces, so it is executed like a query, without being
+/// tokenized. The values given to the builders are never parsed as part of a query.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_sql::{col, Database, Order, Query, SqlError};
+///
+/// # fn main() -> Result<(), SqlError> {
+/// let mut database = Database::open("tablas")?;
+/// let query = Query::select(["nombre", "email"])
+///     .from("clientes")
+///     .filter(col("id").gt(1))
+///     .order_by("nombre", Order::Desc)
+///     .limit(10);
+///
+/// let rows = database.query_built(query)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query(pub(crate) Statement);
+
+impl Query {
+    /// Starts a `SELECT` of the given columns, or of every column if there are none.
+    pub fn select<I, S>(columns: I) -> SelectQuery
+    where
+        I: IntoIterator<Item = S>,
+        S: std::convert::Into<String>,
+    {
+        let mut columns: Vec<String> = columns.into_iter().map(|column| column.into()).collect();
+        if columns.is_empty() {
+            columns.push(String::from("*"));
+        }
+
+        SelectQuery(Select {
+            table_name: String::new(),
+            columns,
+            where_clause: None,
+            orderby_clause: None,
+            limit: None,
+        })
+    }
+
+    /// Starts an `INSERT` of a register into the table.
+    pub fn insert_into(table_name: &str) -> InsertQuery {
+        InsertQuery(Insert {
+            values: Vec::new(),
+            into_clause: Into {
+                table_name: table_name.to_string(),
+                columns: Vec::new(),
+            },
+            returning_clause: None,
+        })
+    }
+
+    /// Starts an `UPDATE` of the registers of the table.
+    pub fn update(table_name: &str) -> UpdateQuery {
+        UpdateQuery(Update {
+            table_name: table_name.to_string(),
+            set_clause: Set(Vec::new()),
+            where_clause: None,
+            returning_clause: None,
+        })
+    }
+
+    /// Starts a `DELETE` of the registers of the table.
+    pub fn delete_from(table_name: &str) -> DeleteQuery {
+        DeleteQuery(Delete {
+            table_name: table_name.to_string(),
+            where_clause: None,
+            returning_clause: None,
+        })
+    }
+
+    /// Returns the statement to execute.
+    ///
+    /// Returns an `InvalidSyntax` error if the statement is incomplete, like an `UPDATE` without values to set, or
+    /// if a value holds a comma or a line break, since the tables don't quote their values.
+    pub(crate) fn statement(self) -> Result<Statement, SqlError> {
+        let mut statement = self.0;
+        let complete = match &statement {
+            Statement::Select(select) => !select.table_name.is_empty(),
+            Statement::Insert(insert) => !insert.values.is_empty(),
+            Statement::Update(update) => !update.set_clause.0.is_empty(),
+            Statement::Delete(_) => true,
+        };
+        if !complete {
+            return Err(SqlError::InvalidSyntax);
+        }
+
+        for value in statement.values_mut() {
+            check_value(value)?;
+        }
+        Ok(statement)
+    }
+}
+
+/// Struct that represents a `SELECT` statement being built, see `Query::select`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectQuery(Select);
+
+impl SelectQuery {
+    /// Sets the table to select the registers from.
+    pub fn from(mut self, table_name: &str) -> Self {
+        self.0.table_name = table_name.to_string();
+        self
+    }
+
+    /// Keeps only the registers that meet the condition, and the previous ones if there are any.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        add_filter(&mut self.0.where_clause, filter);
+        self
+    }
+
+    /// Sorts the registers by the column in the given order, after the columns of the previous calls.
+    pub fn order_by(mut self, column: &str, order: Order) -> Self {
+        self.0
+            .orderby_clause
+            .get_or_insert_with(|| OrderBy {
+                columns: Vec::new(),
+            })
+            .columns
+            .push((column.to_string(), order));
+        self
+    }
+
+    /// Returns at most the given number of registers.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.0.limit = Some(limit);
+        self
+    }
+}
+
+impl From<SelectQuery> for Query {
+    fn from(select: SelectQuery) -> Self {
+        Query(Statement::Select(select.0))
+    }
+}
+
+/// Struct that represents an `INSERT` statement being built, see `Query::insert_into`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertQuery(Insert);
+
+impl InsertQuery {
+    /// Sets the value of a column of the inserted register.
+    pub fn value(mut self, column: &str, value: impl std::convert::Into<Value>) -> Self {
+        self.0.into_clause.columns.push(column.to_string());
+        self.0.values.push(value.into().to_string());
+        self
+    }
+
+    /// Returns the given columns of the inserted register, or every column if there are none.
+    pub fn returning<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: std::convert::Into<String>,
+    {
+        self.0.returning_clause = returning(columns);
+        self
+    }
+}
+
+impl From<InsertQuery> for Query {
+    fn from(insert: InsertQuery) -> Self {
+        Query(Statement::Insert(insert.0))
+    }
+}
+
+/// Struct that represents an `UPDATE` statement being built, see `Query::update`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateQuery(Update);
+
+impl UpdateQuery {
+    /// Sets the value of a column of the updated registers.
+    pub fn set(mut self, column: &str, value: impl std::convert::Into<Value>) -> Self {
+        self.0
+            .set_clause
+            .0
+            .push((column.to_string(), value.into().to_string()));
+        self
+    }
+
+    /// Updates only the registers that meet the condition, and the previous ones if there are any.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        add_filter(&mut self.0.where_clause, filter);
+        self
+    }
+
+    /// Returns the given columns of the updated registers, or every column if there are none.
+    pub fn returning<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: std::convert::Into<String>,
+    {
+        self.0.returning_clause = returning(columns);
+        self
+    }
+}
+
+impl From<UpdateQuery> for Query {
+    fn from(update: UpdateQuery) -> Self {
+        Query(Statement::Update(update.0))
+    }
+}
+
+/// Struct that represents a `DELETE` statement being built, see `Query::delete_from`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteQuery(Delete);
+
+impl DeleteQuery {
+    /// Deletes only the registers that meet the condition, and the previous ones if there are any.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        add_filter(&mut self.0.where_clause, filter);
+        self
+    }
+
+    /// Returns the given columns of the deleted registers, or every column if there are none.
+    pub fn returning<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: std::convert::Into<String>,
+    {
+        self.0.returning_clause = returning(columns);
+        self
+    }
+}
+
+impl From<DeleteQuery> for Query {
+    fn from(delete: DeleteQuery) -> Self {
+        Query(Statement::Delete(delete.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{col, Order, Query};
+    use crate::{errors::SqlError, statement::Statement, tokens::tokens_from_query};
+
+    fn parse(query: &str) -> Statement {
+        Statement::new_from_tokens(tokens_from_query(query)).unwrap()
+    }
+
+    #[test]
+    fn same_clauses_as_the_parser() {
+        let query = Query::select(["nombre", "email"])
+            .from("clientes")
+            .filter(col("id").gt(1).and(!col("nombre").eq("Ana")))
+            .order_by("nombre", Order::Desc)
+            .order_by("email", Order::Asc)
+            .limit(10);
+        assert_eq!(
+            Query::from(query).statement(),
+            Ok(parse(
+                "SELECT nombre, email FROM clientes WHERE id > 1 AND NOT nombre = 'Ana' ORDER BY nombre DESC, email LIMIT 10"
+            ))
+        );
+
+        let query = Query::insert_into("clientes")
+            .value("id", 7)
+            .value("nombre", "Sean")
+            .returning(["id"]);
+        assert_eq!(
+            Query::from(query).statement(),
+            Ok(parse(
+                "INSERT INTO clientes (id, nombre) VALUES (7, 'Sean') RETURNING id"
+            ))
+        );
+
+        let query = Query::update("clientes")
+            .set("email", "sean@email.com")
+            .filter(col("id").eq(7))
+            .filter(col("nombre").lt("T"));
+        assert_eq!(
+            Query::from(query).statement(),
+            Ok(parse(
+                "UPDATE clientes SET email = 'sean@email.com' WHERE id = 7 AND nombre < 'T'"
+            ))
+        );
+
+        let query = Query::delete_from("ordenes")
+            .filter(col("cantidad").eq(0).or(col("cantidad").lt(0)))
+            .returning(Vec::<String>::new());
+        assert_eq!(
+            Query::from(query).statement(),
+            Ok(parse(
+                "DELETE FROM ordenes WHERE cantidad = 0 OR cantidad < 0 RETURNING *"
+            ))
+        );
+    }
+
+    #[test]
+    fn incomplete_or_unstorable() {
+        let select = Query::select(Vec::<String>::new());
+        assert_eq!(
+            Query::from(select).statement(),
+            Err(SqlError::InvalidSyntax)
+        );
+
+        let update = Query::update("clientes").filter(col("id").eq(1));
+        assert_eq!(
+            Query::from(update).statement(),
+            Err(SqlError::InvalidSyntax)
+        );
+
+        let insert = Query::insert_into("clientes").value("nombre", "a,b");
+        assert_eq!(
+            Query::from(insert).statement(),
+            Err(SqlError::InvalidSyntax)
+        );
+
+        let insert = Query::insert_into("clientes").value("nombre", "O'Brien");
+        assert!(Query::from(insert).statement().is_ok());
+    }
+}
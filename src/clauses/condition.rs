@@ -1,14 +1,14 @@
 use crate::{
-    errors::SqlError, logical_operator::LogicalOperator, operator::Operator, utils::is_number,
+    errors::SqlError, logical_operator::LogicalOperator, operator::Operator, register::Register,
+    utils::is_number,
 };
-use std::collections::HashMap;
 
 /// Enum for the conditions used in the `WHERE` clause.
 ///
 /// - `Simple`: Simple condition with a field, operator and value.
 /// - `Complex`: Complex condition with a left condition, logical operator and right condition.
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Condition {
     Simple {
         field: String,
@@ -34,7 +34,7 @@ impl Condition {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let tokens = vec!["age", ">", "18"];
     /// let pos = 0;
     /// let condition = Condition::new_simple_from_tokens(&tokens, &mut pos).unwrap();
@@ -93,7 +93,7 @@ impl Condition {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let left = Condition::Simple {
     ///     field: String::from("age"),
     ///     operator: Operator::Greater,
@@ -134,22 +134,63 @@ impl Condition {
         }
     }
 
+    /// Returns the fields referenced by the condition, in the order they appear.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let tokens = vec!["age", ">", "18", "AND", "city", "=", "Gaiman"];
+    /// let condition = parse_condition(&tokens, &mut 0).unwrap();
+    /// assert_eq!(condition.fields(), vec!["age", "city"]);
+    /// ```
+    ///
+    pub fn fields(&self) -> Vec<&String> {
+        match self {
+            Condition::Simple { field, .. } => vec![field],
+            Condition::Complex { left, right, .. } => {
+                let mut fields = Vec::new();
+                if let Some(left) = left {
+                    fields.extend(left.fields());
+                }
+                fields.extend(right.fields());
+                fields
+            }
+        }
+    }
+
+    /// Returns mutable references to the values compared by the condition, in the order they appear.
+    pub fn values_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Condition::Simple { value, .. } => vec![value],
+            Condition::Complex { left, right, .. } => {
+                let mut values = Vec::new();
+                if let Some(left) = left {
+                    values.extend(left.values_mut());
+                }
+                values.extend(right.values_mut());
+                values
+            }
+        }
+    }
+
     /// Executes the condition on the given register.
     /// Returns a bool with the result of the condition.
     ///
     /// # Arguments
     ///
-    /// * `register` - A reference to a `HashMap<String, String>` with the register to evaluate.
+    /// * `register` - A reference to the `Register` to evaluate.
     ///
-    pub fn execute(&self, register: &HashMap<String, String>) -> Result<bool, SqlError> {
+    pub fn execute(&self, register: &Register) -> Result<bool, SqlError> {
         let op_result: Result<bool, SqlError> = match &self {
             Condition::Simple {
                 field,
                 operator,
                 value,
             } => {
-                let y = value;
+                let y = value.as_str();
                 if let Some(x) = register.get(field) {
+                    let text = x.to_csv();
+                    let x = text.as_ref();
                     if is_number(y) && !is_number(x) || !is_number(y) && is_number(x) {
                         return Err(SqlError::InvalidSyntax);
                     }
@@ -198,8 +239,11 @@ impl Condition {
 #[cfg(test)]
 mod tests {
     use super::Condition;
-    use crate::clauses::condition::{LogicalOperator, Operator};
-    use std::collections::HashMap;
+    use crate::{
+        clauses::condition::{LogicalOperator, Operator},
+        register::{Columns, Register},
+    };
+    use std::sync::Arc;
 
     #[test]
     fn create_simple() {
@@ -290,10 +334,12 @@ mod tests {
 
     #[test]
     fn execute_simple() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24", &columns);
 
         let condition_true = Condition::Simple {
             field: String::from("age"),
@@ -310,17 +356,19 @@ mod tests {
         let result_true = condition_true.execute(&register).unwrap();
         let result_false = condition_false.execute(&register).unwrap();
 
-        assert_eq!(result_true, true);
+        assert!(result_true);
 
-        assert_eq!(result_false, false);
+        assert!(!result_false);
     }
 
     #[test]
     fn execute_and() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24", &columns);
 
         let left = Condition::Simple {
             field: String::from("age"),
@@ -341,15 +389,17 @@ mod tests {
 
         let result = condition.execute(&register).unwrap();
 
-        assert_eq!(result, true)
+        assert!(result)
     }
 
     #[test]
     fn execute_or() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24", &columns);
 
         let left = Condition::Simple {
             field: String::from("age"),
@@ -370,15 +420,17 @@ mod tests {
 
         let result = condition.execute(&register).unwrap();
 
-        assert_eq!(result, false)
+        assert!(!result)
     }
 
     #[test]
     fn execute_not() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24", &columns);
 
         let right = Condition::Simple {
             field: String::from("name"),
@@ -394,16 +446,18 @@ mod tests {
 
         let result = condition.execute(&register).unwrap();
 
-        assert_eq!(result, true)
+        assert!(result)
     }
 
     #[test]
     fn execute_and_or() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
-        register.insert(String::from("city"), String::from("Gaiman"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+            String::from("city"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24,Gaiman", &columns);
 
         let left = Condition::Simple {
             field: String::from("age"),
@@ -436,16 +490,18 @@ mod tests {
 
         let result = and.execute(&register).unwrap();
 
-        assert_eq!(result, false)
+        assert!(!result)
     }
 
     #[test]
     fn execute_not_and() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
-        register.insert(String::from("city"), String::from("Gaiman"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+            String::from("city"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24,Gaiman", &columns);
 
         let right1 = Condition::Simple {
             field: String::from("age"),
@@ -473,16 +529,18 @@ mod tests {
 
         let result = and.execute(&register).unwrap();
 
-        assert_eq!(result, true)
+        assert!(result)
     }
 
     #[test]
     fn execute_not_and_or_with_paren() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
-        register.insert(String::from("city"), String::from("Gaiman"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+            String::from("city"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24,Gaiman", &columns);
 
         // NOT (city = Gaiman AND (age > 18 OR lastname = Davies))
 
@@ -514,17 +572,19 @@ mod tests {
 
         let result = condition.execute(&register).unwrap();
 
-        assert_eq!(result, false)
+        assert!(!result)
     }
 
     #[test]
 
     fn execute_and_or_with_paren2() {
-        let mut register = HashMap::new();
-        register.insert(String::from("name"), String::from("Alen"));
-        register.insert(String::from("lastname"), String::from("Davies"));
-        register.insert(String::from("age"), String::from("24"));
-        register.insert(String::from("city"), String::from("Gaiman"));
+        let columns = Arc::new(Columns::new(vec![
+            String::from("name"),
+            String::from("lastname"),
+            String::from("age"),
+            String::from("city"),
+        ]));
+        let register = Register::from_csv("Alen,Davies,24,Gaiman", &columns);
 
         // city = Gaiman AND (age > 30 OR lastname = Davies)
 
@@ -552,6 +612,6 @@ mod tests {
 
         let result = condition.execute(&register).unwrap();
 
-        assert_eq!(result, true);
+        assert!(result);
     }
 }
@@ -1,11 +1,15 @@
-use super::where_sql::Where;
-use crate::utils::{is_delete, is_from, is_where};
-use crate::{errors::SqlError, register::Register, table::Table, utils::find_file_in_folder};
-use std::io::Write;
+use super::{returning_sql::Returning, where_sql::Where};
+use crate::utils::{is_delete, is_from, is_where, lines_with_offsets};
+use crate::{
+    errors::SqlError,
+    register::{Columns, Register},
+    utils::find_file_in_folder,
+};
 use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::{BufRead, BufReader},
+    collections::BTreeSet,
+    fs::File,
+    io::{BufReader, Write},
+    sync::Arc,
 };
 
 /// Struct that represents the `DELETE` SQL clause.
@@ -15,11 +19,13 @@ use std::{
 ///
 /// - `table_name`: a `String` that holds the name of the table from which the records will be deleted.
 /// - `where_clause`: an `Option<Where>` that holds the condition that the records must meet to be deleted. If it is `None`, all records will be deleted.
+/// - `returning_clause`: an `Option<Returning>` that holds the columns of the deleted records to return, if any.
 ///
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Delete {
     pub table_name: String,
     pub where_clause: Option<Where>,
+    pub returning_clause: Option<Returning>,
 }
 
 impl Delete {
@@ -29,13 +35,14 @@ impl Delete {
     ///
     /// - `tokens`: a `Vec<String>` that holds the tokens that form the `DELETE` clause.
     ///
-    /// The tokens must be in the following order: `DELETE`, `FROM`, `table_name`, `WHERE`, `condition`.
+    /// The tokens must be in the following order: `DELETE`, `FROM`, `table_name`, `WHERE`, `condition`, `RETURNING`, `columns`.
     ///
-    /// If the `WHERE` clause is not present, the `where_clause` field will be `None`.
+    /// If the `WHERE` clause is not present, the `where_clause` field will be `None`, and the same goes for
+    /// the `RETURNING` clause and the `returning_clause` field.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let tokens = vec![
     ///     String::from("DELETE"),
     ///     String::from("FROM"),
@@ -47,12 +54,14 @@ impl Delete {
     ///    delete,
     ///     Delete {
     ///         table_name: String::from("table"),
-    ///         where_clause: None
+    ///         where_clause: None,
+    ///         returning_clause: None,
     ///     }
     /// );
     /// ```
     ///
     pub fn new_from_tokens(tokens: Vec<String>) -> Result<Self, SqlError> {
+        let (tokens, returning_clause) = Returning::split_from_tokens(tokens)?;
         if tokens.len() < 3 {
             return Err(SqlError::InvalidSyntax);
         }
@@ -91,90 +100,57 @@ impl Delete {
         Ok(Self {
             table_name,
             where_clause,
+            returning_clause,
         })
     }
 
     /// Applies the `DELETE` clause to the given table.
     ///
-    /// Returns a new table with the records that do not meet the condition.
-    /// The ones that meet the condition will be deleted.
+    /// Reads the table one register at a time and writes the registers that do not meet the condition to the output,
+    /// as they are, so the output holds the new content of the table. Returns the columns of the table, along with the deleted registers.
     ///
     /// If the `WHERE` clause is not present, all records will be deleted.
     ///
     /// # Arguments
     ///
     /// - `table`: a `BufReader<File>` that holds the table to which the `DELETE` clause will be applied.
+    /// - `candidates`: the byte offsets of the only records that may meet the condition, found with an index. If it is `None`, every record is checked.
+    /// - `output`: where the new content of the table is written, usually a `TempFile` that replaces the table.
     ///
-    pub fn apply_to_table(&self, table: BufReader<File>) -> Result<Table, SqlError> {
-        let mut result = Table::new();
-
-        for (idx, line) in table.lines().enumerate() {
-            let line = line.map_err(|_| SqlError::Error)?;
+    pub fn apply_to_table<W: Write>(
+        &self,
+        table: BufReader<File>,
+        candidates: Option<&BTreeSet<u64>>,
+        output: &mut W,
+    ) -> Result<(Vec<String>, Vec<Register>), SqlError> {
+        let mut names = Vec::new();
+        let mut columns = Arc::new(Columns::new(Vec::new()));
+        let mut deleted = Vec::new();
+
+        for (idx, line) in lines_with_offsets(table).enumerate() {
+            let (offset, line) = line?;
 
             if idx == 0 {
-                result.columns = line.split(',').map(|s| s.to_string()).collect();
-                if self.where_clause.is_none() {
-                    return Ok(result);
+                names = line.split(',').map(|s| s.to_string()).collect();
+                columns = Arc::new(Columns::new(names.to_vec()));
+            } else if candidates.is_none_or(|candidates| candidates.contains(&offset)) {
+                let register = Register::from_csv(&line, &columns);
+                if self.is_deleted(&register)? {
+                    deleted.push(register);
+                    continue;
                 }
-                continue;
             }
-            let register = self.execute(line, &result.columns)?;
 
-            if !register.0.is_empty() {
-                result.registers.push(register);
-            }
+            writeln!(output, "{}", line).map_err(|_| SqlError::Error)?;
         }
-        Ok(result)
+        Ok((names, deleted))
     }
 
-    fn execute(&self, line: String, columns: &Vec<String>) -> Result<Register, SqlError> {
-        let atributes: Vec<String> = line.split(',').map(|s| s.to_string()).collect();
-
-        let mut register = Register(HashMap::new());
-
-        for (idx, col) in columns.iter().enumerate() {
-            register
-                .0
-                .insert(col.to_string(), atributes[idx].to_string());
+    fn is_deleted(&self, register: &Register) -> Result<bool, SqlError> {
+        match &self.where_clause {
+            Some(where_clause) => where_clause.execute(register),
+            None => Ok(true),
         }
-
-        let mut result = Register(HashMap::new());
-
-        if let Some(where_clause) = &self.where_clause {
-            let op_result = where_clause.execute(&register)?;
-
-            if !op_result {
-                for col in columns {
-                    result.0.insert(
-                        col.to_string(),
-                        register.0.get(col).unwrap_or(&String::new()).to_string(),
-                    );
-                }
-            }
-        }
-        Ok(result)
-    }
-
-    /// Updates the table file with the new data after the `DELETE` clause is applied.
-    ///
-    /// # Arguments
-    ///
-    /// - `csv`: a `Vec<String>` that holds the new data to be written to the table file.
-    /// - `folder_path`: a `&str` that holds the path to the folder where the table file is located.
-    ///
-    pub fn write_table(&self, csv: Vec<String>, folder_path: &str) -> Result<(), SqlError> {
-        let temp_file_path = folder_path.to_string() + "/" + "temp.csv";
-        let mut temp_file = File::create(&temp_file_path).map_err(|_| SqlError::Error)?;
-
-        for line in csv {
-            writeln!(temp_file, "{}", line).map_err(|_| SqlError::Error)?;
-        }
-
-        let path = folder_path.to_string() + "/" + &self.table_name + ".csv";
-
-        fs::rename(&temp_file_path, path).map_err(|_| SqlError::Error)?;
-
-        Ok(())
     }
 
     /// Opens the table file to which the `DELETE` clause will be applied.
@@ -200,15 +176,11 @@ impl Delete {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use super::Delete;
     use crate::{
         clauses::{condition::Condition, where_sql::Where},
         errors::SqlError,
         operator::Operator,
-        register::Register,
-        table::Table,
     };
 
     #[test]
@@ -237,7 +209,8 @@ mod tests {
             delete,
             Delete {
                 table_name: String::from("table"),
-                where_clause: None
+                where_clause: None,
+                returning_clause: None,
             }
         );
     }
@@ -277,6 +250,7 @@ mod tests {
                         value: String::from("1")
                     }
                 }),
+                returning_clause: None,
             }
         );
     }
@@ -286,22 +260,16 @@ mod tests {
         let delete = Delete {
             table_name: String::from("testing"),
             where_clause: None,
+            returning_clause: None,
         };
         let folder_path = String::from("tablas");
         let reader = delete.open_table(&folder_path).unwrap();
 
-        let table = delete.apply_to_table(reader).unwrap();
-        let expected = Table {
-            columns: vec![
-                String::from("nombre"),
-                String::from("apellido"),
-                String::from("edad"),
-            ],
-            registers: vec![],
-        };
+        let mut output = Vec::new();
+        let (columns, _) = delete.apply_to_table(reader, None, &mut output).unwrap();
 
-        assert_eq!(table.registers, expected.registers);
-        assert_eq!(table.columns, expected.columns);
+        assert_eq!(String::from_utf8(output).unwrap(), "nombre,apellido,edad\n");
+        assert_eq!(columns, vec!["nombre", "apellido", "edad"]);
     }
 
     #[test]
@@ -315,25 +283,18 @@ mod tests {
                     value: String::from("18"),
                 },
             }),
+            returning_clause: None,
         };
         let folder_path = String::from("tablas");
         let reader = delete.open_table(&folder_path).unwrap();
 
-        let table = delete.apply_to_table(reader).unwrap();
-        let expected = Table {
-            columns: vec![
-                String::from("nombre"),
-                String::from("apellido"),
-                String::from("edad"),
-            ],
-            registers: vec![Register(HashMap::from([
-                (String::from("nombre"), String::from("Ana")),
-                (String::from("apellido"), String::from("López")),
-                (String::from("edad"), String::from("18")),
-            ]))],
-        };
+        let mut output = Vec::new();
+        let (_, deleted) = delete.apply_to_table(reader, None, &mut output).unwrap();
 
-        assert_eq!(table.registers, expected.registers);
-        assert_eq!(table.columns, expected.columns);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "nombre,apellido,edad\nAna,López,18\n"
+        );
+        assert_eq!(deleted.len(), 2);
     }
 }
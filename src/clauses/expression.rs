@@ -0,0 +1,231 @@
+use crate::{
+    arithmetic_operator::ArithmeticOperator, errors::SqlError, register::Register,
+    tokens::tokens_from_query, value::Value,
+};
+use std::fmt;
+
+/// Enum for the expressions returned by the `RETURNING` clause.
+///
+/// - `Column`: The value of a column of the register.
+/// - `Integer`: A constant integer.
+/// - `Binary`: An arithmetic operator applied to two expressions.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Column(String),
+    Integer(i64),
+    Binary {
+        left: Box<Expression>,
+        operator: ArithmeticOperator,
+        right: Box<Expression>,
+    },
+}
+
+impl Expression {
+    /// Creates a new `Expression` from the tokens that start at the given position, leaving the position after
+    /// its last token.
+    ///
+    /// An expression is a column, an integer or an expression between parentheses, followed by any number of
+    /// operators and operands. `*` and `/` are applied before `+` and `-`, and those before `||`, from left to right.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - A slice of `&str` with the tokens of the expression.
+    /// * `pos` - A mutable reference to `usize` with the position of the tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let tokens = vec!["cantidad", "*", "2", "AS", "doble"];
+    /// let mut pos = 0;
+    /// let expression = Expression::new_from_tokens(&tokens, &mut pos).unwrap();
+    ///
+    /// assert_eq!(expression.to_string(), "cantidad * 2");
+    /// assert_eq!(pos, 3);
+    /// ```
+    ///
+    pub fn new_from_tokens(tokens: &[&str], pos: &mut usize) -> Result<Self, SqlError> {
+        Self::parse(tokens, pos, 1)
+    }
+
+    fn parse(tokens: &[&str], pos: &mut usize, precedence: u8) -> Result<Self, SqlError> {
+        let mut left = Self::parse_operand(tokens, pos)?;
+
+        while let Some(operator) = tokens
+            .get(*pos)
+            .and_then(|token| ArithmeticOperator::from_token(token))
+        {
+            if operator.precedence() < precedence {
+                break;
+            }
+            *pos += 1;
+            let right = Self::parse(tokens, pos, operator.precedence() + 1)?;
+            left = Expression::Binary {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn parse_operand(tokens: &[&str], pos: &mut usize) -> Result<Self, SqlError> {
+        let token = tokens.get(*pos).ok_or(SqlError::InvalidSyntax)?;
+        *pos += 1;
+
+        if let Ok(integer) = token.parse::<i64>() {
+            return Ok(Expression::Integer(integer));
+        }
+        if token.chars().all(|c| c.is_alphabetic() || c == '_') {
+            return Ok(Expression::Column(token.to_string()));
+        }
+
+        // The tokenizer keeps the content of a pair of parentheses as a single token.
+        let inner = tokens_from_query(token);
+        let inner: Vec<&str> = inner.iter().map(|token| token.as_str()).collect();
+        if inner.as_slice() == [*token] {
+            return Err(SqlError::InvalidSyntax);
+        }
+        let mut inner_pos = 0;
+        let expression = Self::new_from_tokens(&inner, &mut inner_pos)?;
+        if inner_pos < inner.len() {
+            return Err(SqlError::InvalidSyntax);
+        }
+        Ok(expression)
+    }
+
+    /// Returns the columns used by the expression.
+    pub fn columns(&self) -> Vec<&String> {
+        match self {
+            Expression::Column(column) => vec![column],
+            Expression::Integer(_) => Vec::new(),
+            Expression::Binary { left, right, .. } => {
+                let mut columns = left.columns();
+                columns.extend(right.columns());
+                columns
+            }
+        }
+    }
+
+    /// Evaluates the expression on a register, see `ArithmeticOperator::apply`.
+    ///
+    /// Returns an `InvalidColumn` error if the register doesn't have one of the columns of the expression.
+    pub fn evaluate(&self, register: &Register) -> Result<Value, SqlError> {
+        match self {
+            Expression::Column(column) => {
+                register.get(column).cloned().ok_or(SqlError::InvalidColumn)
+            }
+            Expression::Integer(integer) => Ok(Value::Integer(*integer)),
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => operator.apply(&left.evaluate(register)?, &right.evaluate(register)?),
+        }
+    }
+}
+
+fn write_operand(f: &mut fmt::Formatter, expression: &Expression, precedence: u8) -> fmt::Result {
+    match expression {
+        Expression::Binary { operator, .. } if operator.precedence() < precedence => {
+            write!(f, "({})", expression)
+        }
+        _ => write!(f, "{}", expression),
+    }
+}
+
+/// Shows the expression as it would be written in a query, with parentheses only where they are needed.
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Column(column) => write!(f, "{}", column),
+            Expression::Integer(integer) => write!(f, "{}", integer),
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                write_operand(f, left, operator.precedence())?;
+                write!(f, " {} ", operator.token())?;
+                write_operand(f, right, operator.precedence() + 1)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Expression;
+    use crate::{
+        errors::SqlError,
+        register::{Columns, Register},
+        tokens::tokens_from_query,
+        value::Value,
+    };
+    use std::sync::Arc;
+
+    fn parse(query: &str) -> Result<Expression, SqlError> {
+        let tokens = tokens_from_query(query);
+        let tokens: Vec<&str> = tokens.iter().map(|token| token.as_str()).collect();
+        let mut pos = 0;
+        let expression = Expression::new_from_tokens(&tokens, &mut pos)?;
+        assert_eq!(pos, tokens.len());
+        Ok(expression)
+    }
+
+    #[test]
+    fn parse_with_precedence() {
+        assert_eq!(
+            parse("cantidad + precio * 2").unwrap().to_string(),
+            "cantidad + precio * 2"
+        );
+        assert_eq!(
+            parse("(cantidad + precio) * 2").unwrap().to_string(),
+            "(cantidad + precio) * 2"
+        );
+        assert_eq!(parse("10 - (4 - 3)").unwrap().to_string(), "10 - (4 - 3)");
+        assert_eq!(
+            parse("((cantidad))").unwrap(),
+            Expression::Column(String::from("cantidad"))
+        );
+        assert_eq!(parse("cantidad +"), Err(SqlError::InvalidSyntax));
+        assert_eq!(parse("* 2"), Err(SqlError::InvalidSyntax));
+        assert_eq!(parse("()"), Err(SqlError::InvalidSyntax));
+    }
+
+    #[test]
+    fn evaluate() {
+        let columns = Arc::new(Columns::new(vec![
+            String::from("producto"),
+            String::from("cantidad"),
+            String::from("precio"),
+        ]));
+        let register = Register::from_csv("Teclado,3,", &columns);
+
+        assert_eq!(
+            parse("10 - cantidad * 2 - 1").unwrap().evaluate(&register),
+            Ok(Value::Integer(3))
+        );
+        assert_eq!(
+            parse("producto || cantidad").unwrap().evaluate(&register),
+            Ok(Value::from("Teclado3"))
+        );
+        assert_eq!(
+            parse("cantidad + precio").unwrap().evaluate(&register),
+            Ok(Value::Null)
+        );
+        assert_eq!(
+            parse("producto + 1").unwrap().evaluate(&register),
+            Err(SqlError::InvalidSyntax)
+        );
+        assert_eq!(
+            parse("cantidad / 0").unwrap().evaluate(&register),
+            Err(SqlError::Error)
+        );
+        assert_eq!(
+            parse("email").unwrap().evaluate(&register),
+            Err(SqlError::InvalidColumn)
+        );
+    }
+}
@@ -1,8 +1,14 @@
 use super::into_sql::Into;
+use super::returning_sql::Returning;
 use crate::errors::SqlError;
+use crate::register::{Columns, Register};
+use crate::schema::{Identity, Schema};
+use crate::sequence::{parse_nextval, Sequence};
 use crate::utils::{find_file_in_folder, is_insert, is_values};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Struct that represents the `INSERT` SQL clause.
 /// The `INSERT` clause is used to insert new records into a table.
@@ -11,11 +17,13 @@ use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
 ///
 /// * `values` - A vector of strings that contains the values to be inserted.
 /// * `into_clause` - An `Into` struct that contains the table name and columns.
+/// * `returning_clause` - The columns of the inserted record to return, if any.
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Insert {
     pub values: Vec<String>,
     pub into_clause: Into,
+    pub returning_clause: Option<Returning>,
 }
 
 impl Insert {
@@ -27,13 +35,17 @@ impl Insert {
     ///
     /// The tokens should be in the following order: `INSERT`, `INTO`, `table_name`, `column_names`, `VALUES`, `values`.
     ///
-    /// The `column_names` and `values` should be comma-separated and between parentheses.
+    /// The `column_names` and `values` should be comma-separated and between parentheses. They can be followed by
+    /// `RETURNING` and the columns of the inserted record to return.
     ///
-    /// If a pair of col, value is missing for a column in the table, the value will be an empty string for that column.
+    /// If a pair of col, value is missing for a column in the table, the value will be generated by the sequence of an identity column,
+    /// or the default declared in the table schema for that column, or an empty string if there is none.
+    ///
+    /// A value can be `nextval('sequence')`, which is replaced by the next value of the sequence when the clause is applied.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let tokens = vec![
     ///     String::from("INSERT"),
     ///     String::from("INTO"),
@@ -52,12 +64,14 @@ impl Insert {
     ///         into_clause: Into {
     ///             table_name: String::from("table"),
     ///             columns: vec![String::from("name"), String::from("age")]
-    ///         }
+    ///         },
+    ///         returning_clause: None,
     ///     }
     /// );
     /// ```
     ///
     pub fn new_from_tokens(tokens: Vec<String>) -> Result<Self, SqlError> {
+        let (tokens, returning_clause) = Returning::split_from_tokens(tokens)?;
         if tokens.len() < 6 {
             return Err(SqlError::InvalidSyntax);
         }
@@ -96,16 +110,27 @@ impl Insert {
         Ok(Self {
             values,
             into_clause,
+            returning_clause,
         })
     }
 
-    /// Applies the `INSERT` clause to a table.
+    /// Applies the `INSERT` clause to a table, and returns the number of registers inserted.
+    ///
+    /// The new register is checked against the constraints of the table schema before anything is written,
+    /// so a statement that violates a constraint leaves the table untouched.
     ///
     /// # Arguments
     ///
     /// * `file` - A mutable reference to a `File` instance that represents the table file.
+    /// * `schema` - A reference to the `Schema` of the table.
+    /// * `timeout` - How long to wait for the lock of each sequence that generates a value.
     ///
-    pub fn apply_to_table(&mut self, file: &mut File) -> Result<(), SqlError> {
+    pub fn apply_to_table(
+        &mut self,
+        file: &mut File,
+        schema: &Schema,
+        timeout: Duration,
+    ) -> Result<usize, SqlError> {
         let mut reader = BufReader::new(file.by_ref());
 
         let mut first_line = String::new();
@@ -120,36 +145,69 @@ impl Insert {
             .map(|col| col.to_string())
             .collect();
 
-        self.reorder_values(columns);
+        self.reorder_values(columns, schema, timeout)?;
 
         let line = self.values.join(",");
+        let columns = Arc::new(Columns::new(self.into_clause.columns.to_vec()));
+        let register = Register::from_csv(&line, &columns);
+
+        schema.check_register(&register)?;
+
+        if !schema.keys.is_empty() || !schema.foreign_keys.is_empty() {
+            let mut registers = Vec::new();
+            for existing in reader.lines() {
+                let existing = existing.map_err(|_| SqlError::InvalidTable)?;
+                registers.push(Register::from_csv(&existing, &columns));
+            }
+            registers.push(register);
+            let inserted = [registers.len() - 1];
+
+            schema.check_keys(&registers, &inserted)?;
+            schema.check_foreign_keys(&registers, &inserted)?;
+        }
 
         file.seek(SeekFrom::End(0)).map_err(|_| SqlError::Error)?;
 
         writeln!(file, "{}", line).map_err(|_| SqlError::Error)?;
 
-        Ok(())
+        Ok(1)
     }
 
-    fn reorder_values(&mut self, columns: Vec<String>) {
-        let mut reordered_values: Vec<&str> = Vec::new();
-        let mut reordered_cols: Vec<&str> = Vec::new();
+    fn reorder_values(
+        &mut self,
+        columns: Vec<String>,
+        schema: &Schema,
+        timeout: Duration,
+    ) -> Result<(), SqlError> {
+        let mut reordered_values: Vec<String> = Vec::new();
 
         for col in &columns {
-            if self.into_clause.columns.contains(col) {
-                if let Some(index) = self.into_clause.columns.iter().position(|x| x == col) {
-                    reordered_values.push(self.values[index].as_str());
+            if let Some(index) = self.into_clause.columns.iter().position(|x| x == col) {
+                let value = self.values.get(index).ok_or(SqlError::InvalidSyntax)?;
+
+                if let Some(Identity::Always) = schema.column(col).and_then(|c| c.identity) {
+                    return Err(SqlError::ConstraintViolation {
+                        constraint: format!("GENERATED ALWAYS AS IDENTITY ({})", col),
+                        value: format!("'{}'", value),
+                    });
                 }
 
-                reordered_cols.push(col);
+                match parse_nextval(value) {
+                    Some(sequence) => {
+                        let next = Sequence::next_value(&schema.folder_path, sequence, timeout)?;
+                        reordered_values.push(next.to_string());
+                    }
+                    None => reordered_values.push(value.to_string()),
+                }
             } else {
-                reordered_values.push("");
-                reordered_cols.push(col);
+                reordered_values.push(schema.missing_value(col, timeout)?);
             }
         }
 
-        self.into_clause.columns = reordered_cols.iter().map(|c| c.to_string()).collect();
-        self.values = reordered_values.iter().map(|c| c.to_string()).collect();
+        self.into_clause.columns = columns;
+        self.values = reordered_values;
+
+        Ok(())
     }
 
     /// Opens the table file and returns a `File` instance.
@@ -177,8 +235,10 @@ impl Insert {
 
 #[cfg(test)]
 mod test {
-    use crate::errors::SqlError;
-    use std::io::BufRead;
+    use crate::{errors::SqlError, schema::Schema};
+    use std::{io::BufRead, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
 
     #[test]
     fn new_1_token() {
@@ -217,7 +277,8 @@ mod test {
                 into_clause: super::Into {
                     table_name: String::from("table"),
                     columns: vec![String::from("name")]
-                }
+                },
+                returning_clause: None,
             }
         );
     }
@@ -240,7 +301,8 @@ mod test {
                 into_clause: super::Into {
                     table_name: String::from("table"),
                     columns: vec![String::from("name"), String::from("age")]
-                }
+                },
+                returning_clause: None,
             }
         );
     }
@@ -253,11 +315,15 @@ mod test {
                 table_name: String::from("testing_values"),
                 columns: vec![String::from("nombre")],
             },
+            returning_clause: None,
         };
 
         let mut file = insert.open_table("tablas").unwrap();
 
-        assert_eq!(insert.apply_to_table(&mut file), Ok(()));
+        assert_eq!(
+            insert.apply_to_table(&mut file, &Schema::default(), TIMEOUT),
+            Ok(1)
+        );
 
         let expected = vec![
             "nombre,apellido,edad",
@@ -294,11 +360,15 @@ mod test {
                     String::from("edad"),
                 ],
             },
+            returning_clause: None,
         };
 
         let mut file = insert.open_table("tablas").unwrap();
 
-        assert_eq!(insert.apply_to_table(&mut file), Ok(()));
+        assert_eq!(
+            insert.apply_to_table(&mut file, &Schema::default(), TIMEOUT),
+            Ok(1)
+        );
 
         let expected = vec![
             "nombre,apellido,edad",
@@ -335,11 +405,15 @@ mod test {
                     String::from("nombre"),
                 ],
             },
+            returning_clause: None,
         };
 
         let mut file = insert.open_table("tablas").unwrap();
 
-        assert_eq!(insert.apply_to_table(&mut file), Ok(()));
+        assert_eq!(
+            insert.apply_to_table(&mut file, &Schema::default(), TIMEOUT),
+            Ok(1)
+        );
 
         let expected = vec![
             "nombre,apellido,edad",
@@ -359,4 +433,133 @@ mod test {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn insert_duplicated_primary_key() {
+        let folder = std::env::temp_dir().join("rustic_sql_insert_duplicated_primary_key");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("clientes.csv"), "id,nombre\n1,Juan\n2,Ana\n").unwrap();
+        std::fs::write(folder.join("clientes.schema"), "id PRIMARY KEY\nnombre\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let mut insert = super::Insert {
+            values: vec![String::from("Alen"), String::from("2")],
+            into_clause: super::Into {
+                table_name: String::from("clientes"),
+                columns: vec![String::from("nombre"), String::from("id")],
+            },
+            returning_clause: None,
+        };
+        let schema = Schema::open(folder_path, "clientes").unwrap();
+        let mut file = insert.open_table(folder_path).unwrap();
+
+        assert_eq!(
+            insert.apply_to_table(&mut file, &schema, TIMEOUT),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("PRIMARY KEY (id)"),
+                value: String::from("'2'"),
+            })
+        );
+
+        let content = std::fs::read_to_string(folder.join("clientes.csv")).unwrap();
+        assert_eq!(content, "id,nombre\n1,Juan\n2,Ana\n");
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn insert_with_default_and_not_null() {
+        let folder = std::env::temp_dir().join("rustic_sql_insert_with_default_and_not_null");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("ordenes.csv"), "id,producto,cantidad\n").unwrap();
+        std::fs::write(
+            folder.join("ordenes.schema"),
+            "id\nproducto NOT NULL\ncantidad DEFAULT 1 CHECK (cantidad > 0)\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+        let schema = Schema::open(folder_path, "ordenes").unwrap();
+
+        let mut insert = super::Insert {
+            values: vec![String::from("101"), String::from("Laptop")],
+            into_clause: super::Into {
+                table_name: String::from("ordenes"),
+                columns: vec![String::from("id"), String::from("producto")],
+            },
+            returning_clause: None,
+        };
+        let mut file = insert.open_table(folder_path).unwrap();
+        assert_eq!(insert.apply_to_table(&mut file, &schema, TIMEOUT), Ok(1));
+
+        let mut insert = super::Insert {
+            values: vec![String::from("102")],
+            into_clause: super::Into {
+                table_name: String::from("ordenes"),
+                columns: vec![String::from("id")],
+            },
+            returning_clause: None,
+        };
+        let mut file = insert.open_table(folder_path).unwrap();
+        assert_eq!(
+            insert.apply_to_table(&mut file, &schema, TIMEOUT),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("NOT NULL (producto)"),
+                value: String::from("''"),
+            })
+        );
+
+        let content = std::fs::read_to_string(folder.join("ordenes.csv")).unwrap();
+        assert_eq!(content, "id,producto,cantidad\n101,Laptop,1\n");
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn insert_with_identity() {
+        let folder = std::env::temp_dir().join("rustic_sql_insert_with_identity");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("ordenes.csv"), "id,producto\n4,Laptop\n").unwrap();
+        std::fs::write(
+            folder.join("ordenes.schema"),
+            "id INTEGER GENERATED ALWAYS AS IDENTITY\nproducto\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+        let schema = Schema::open(folder_path, "ordenes").unwrap();
+
+        for producto in ["Mouse", "Monitor"] {
+            let mut insert = super::Insert {
+                values: vec![String::from(producto)],
+                into_clause: super::Into {
+                    table_name: String::from("ordenes"),
+                    columns: vec![String::from("producto")],
+                },
+                returning_clause: None,
+            };
+            let mut file = insert.open_table(folder_path).unwrap();
+            assert_eq!(insert.apply_to_table(&mut file, &schema, TIMEOUT), Ok(1));
+        }
+
+        let mut insert = super::Insert {
+            values: vec![String::from("9"), String::from("Teclado")],
+            into_clause: super::Into {
+                table_name: String::from("ordenes"),
+                columns: vec![String::from("id"), String::from("producto")],
+            },
+            returning_clause: None,
+        };
+        let mut file = insert.open_table(folder_path).unwrap();
+        assert_eq!(
+            insert.apply_to_table(&mut file, &schema, TIMEOUT),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("GENERATED ALWAYS AS IDENTITY (id)"),
+                value: String::from("'9'"),
+            })
+        );
+
+        let content = std::fs::read_to_string(folder.join("ordenes.csv")).unwrap();
+        assert_eq!(content, "id,producto\n4,Laptop\n5,Mouse\n6,Monitor\n");
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
 }
@@ -1,9 +1,11 @@
 pub mod condition;
 pub mod delete_sql;
+pub mod expression;
 pub mod insert_sql;
 pub mod into_sql;
 pub mod orderby_sql;
 pub mod recursive_parser;
+pub mod returning_sql;
 pub mod select_sql;
 pub mod set_sql;
 pub mod update_sql;
@@ -5,18 +5,27 @@ use crate::{
 };
 use std::cmp::Ordering;
 
+/// Enum for the order of the registers sorted by a column of the `ORDER BY` clause.
+///
+/// - `Asc`: from the lowest to the highest value, written as `ASC` or left out.
+/// - `Desc`: from the highest to the lowest value, written as `DESC`.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
 /// Struct that epresents the `ORDER BY` SQL clause.
 /// The `ORDER BY` clause is used to sort the result set in ascending or descending order in a `SELECT` clause.
 ///
 /// # Fields
 ///
-/// * `columns` - The columns to sort the result set by.
-/// * `order` - The order to sort the result set by. It can be either `ASC` or `DESC`.
+/// * `columns` - The columns to sort the result set by, each one with the order to sort it in.
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OrderBy {
-    pub columns: Vec<String>,
-    pub order: String,
+    pub columns: Vec<(String, Order)>,
 }
 
 impl OrderBy {
@@ -26,19 +35,19 @@ impl OrderBy {
     ///
     /// * `tokens` - A vector of `&str` tokens that represent the `ORDER BY` clause.
     ///
-    /// The tokens should be in the following order: `ORDER`, `BY`, `columns`, `order`.
+    /// The tokens should be in the following order: `ORDER`, `BY`, `columns`, each one optionally followed by its order.
     ///
     /// The `columns` should be comma-separated.
     ///
     /// The `order` can be `ASC` or `DESC`.
-    /// If the `order` is not specified, the result set will be sorted in ascending order.
+    /// If the `order` of a column is not specified, the result set will be sorted by it in ascending order.
     ///
     /// # Examples
     ///
-    /// ```
-    /// let tokens = vec!["ORDER", "BY", "name", "DESC"];
+    /// ```ignore
+    /// let tokens = vec!["ORDER", "BY", "name", "DESC", "id"];
     /// let order_by = OrderBy::new_from_tokens(tokens).unwrap();
-    /// assert_eq!(order_by., OrderBy { columns: vec!["name".to_string()], order: "DESC".to_string() });
+    /// assert_eq!(order_by.columns, vec![("name".to_string(), Order::Desc), ("id".to_string(), Order::Asc)]);
     /// ```
     ///
     pub fn new_from_tokens(tokens: Vec<&str>) -> Result<Self, SqlError> {
@@ -47,7 +56,6 @@ impl OrderBy {
         }
 
         let mut columns = Vec::new();
-        let mut order = String::new();
         let mut i = 0;
 
         if !is_order(tokens[i]) && !is_by(tokens[i + 1]) {
@@ -56,43 +64,101 @@ impl OrderBy {
 
         i += 2;
 
-        while i < tokens.len() && tokens[i] != "DESC" && tokens[i] != "ASC" {
-            columns.push(tokens[i].to_string());
+        while i < tokens.len() {
+            let column = tokens[i];
+            if column == "ASC" || column == "DESC" {
+                return Err(SqlError::InvalidSyntax);
+            }
             i += 1;
-        }
 
-        if i < tokens.len() {
-            order = tokens[i].to_string();
+            let order = match tokens.get(i) {
+                Some(&"DESC") => Order::Desc,
+                _ => Order::Asc,
+            };
+            if matches!(tokens.get(i), Some(&"ASC" | &"DESC")) {
+                i += 1;
+            }
+            columns.push((column.to_string(), order));
         }
 
-        Ok(Self { columns, order })
+        Ok(Self { columns })
     }
 
-    /// Sorts the registers by the columns and order specified in the `ORDER BY` clause.
+    /// Compares two registers by the columns and orders specified in the `ORDER BY` clause.
+    ///
+    /// The columns missing in any of the registers are skipped.
     ///
     /// # Arguments
     ///
-    /// * `registers` - A mutable reference to a vector of `Register`.
+    /// * `val_a` - The first register to compare.
+    /// * `val_b` - The second register to compare.
     ///
-    pub fn execute<'a>(&self, registers: &'a mut Vec<Register>) -> &'a Vec<Register> {
-        registers.sort_by(|val_a, val_b| {
-            let mut result = Ordering::Equal;
-            for column in &self.columns {
-                if let Some(val_a) = val_a.0.get(column) {
-                    if let Some(val_b) = val_b.0.get(column) {
-                        result = if self.order == "DESC" {
-                            val_b.cmp(val_a)
-                        } else {
-                            val_a.cmp(val_b)
-                        };
-                        if result != Ordering::Equal {
-                            break;
-                        }
+    pub fn compare(&self, val_a: &Register, val_b: &Register) -> Ordering {
+        let mut result = Ordering::Equal;
+        for (column, order) in &self.columns {
+            if let Some(val_a) = val_a.get(column).map(|value| value.to_csv()) {
+                if let Some(val_b) = val_b.get(column).map(|value| value.to_csv()) {
+                    result = match order {
+                        Order::Desc => val_b.cmp(&val_a),
+                        Order::Asc => val_a.cmp(&val_b),
+                    };
+                    if result != Ordering::Equal {
+                        break;
                     }
                 }
             }
-            result
-        });
+        }
+        result
+    }
+
+    /// Sorts the registers by the columns and orders specified in the `ORDER BY` clause.
+    ///
+    /// # Arguments
+    ///
+    /// * `registers` - A mutable reference to a vector of `Register`.
+    ///
+    pub fn execute<'a>(&self, registers: &'a mut Vec<Register>) -> &'a Vec<Register> {
+        registers.sort_by(|val_a, val_b| self.compare(val_a, val_b));
         registers
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Order, OrderBy};
+    use crate::{
+        errors::SqlError,
+        register::{Columns, Register},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn order_per_column() {
+        let orderby =
+            OrderBy::new_from_tokens(vec!["ORDER", "BY", "apellido", "DESC", "nombre"]).unwrap();
+        assert_eq!(
+            orderby.columns,
+            vec![
+                (String::from("apellido"), Order::Desc),
+                (String::from("nombre"), Order::Asc)
+            ]
+        );
+        assert_eq!(
+            OrderBy::new_from_tokens(vec!["ORDER", "BY", "apellido", "DESC", "ASC"]),
+            Err(SqlError::InvalidSyntax)
+        );
+
+        let columns = Arc::new(Columns::new(vec![
+            String::from("nombre"),
+            String::from("apellido"),
+        ]));
+        let mut registers: Vec<Register> = ["Ana,Diaz", "Juan,Perez", "Ana,Perez"]
+            .iter()
+            .map(|line| Register::from_csv(line, &columns))
+            .collect();
+        orderby.execute(&mut registers);
+
+        let lines: Vec<String> = registers.iter().map(|r| r.to_csv()).collect();
+        assert_eq!(lines, vec!["Ana,Perez", "Juan,Perez", "Ana,Diaz"]);
+    }
+}
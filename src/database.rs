@@ -0,0 +1,481 @@
+#[cfg(feature = "serde")]
+use crate::serialization::{from_row, to_register};
+use crate::{
+    builder::Query, errors::SqlError, open_folder, pipeline::RegisterStream,
+    query_result::QueryResult, row::Rows, session::Session, statement::PreparedStatement,
+};
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+use std::{path::Path, time::Duration};
+
+/// Struct that represents the tables stored in a folder, opened to execute statements on them.
+///
+/// The statements are executed in the same session, so a transaction opened with BEGIN holds the following
+/// statements until COMMIT or ROLLBACK.
+///
+/// # Fields
+///
+/// * `session` - The session where the statements are executed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rustic_sql::{Database, SqlError};
+///
+/// # fn main() -> Result<(), SqlError> {
+/// let mut database = Database::open("tablas")?;
+///
+/// assert_eq!(database.execute("DELETE FROM ordenes WHERE cantidad = 0")?, 2);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug)]
+pub struct Database {
+    session: Session,
+}
+
+impl Database {
+    /// Opens the tables of a folder.
+    ///
+    /// The changes of a statement interrupted by a crash are recovered before returning, like the program does
+    /// before executing any statement.
+    ///
+    /// Returns an error if the folder doesn't exist.
+    pub fn open(folder_path: &str) -> Result<Self, SqlError> {
+        if !Path::new(folder_path).is_dir() {
+            return Err(SqlError::Error);
+        }
+        open_folder(folder_path)?;
+
+        Ok(Self {
+            session: Session::new(folder_path, false),
+        })
+    }
+
+    /// Sets how long the statements of the database wait for the lock of a table before failing with a `Locked` error.
+    /// 5 seconds by default.
+    pub fn set_lock_timeout(&mut self, timeout: Duration) {
+        self.session.settings.lock_timeout = timeout;
+    }
+
+    /// Sets how many threads evaluate the `WHERE` clause of a query over a large table. 1 by default.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.session.settings.threads = threads.max(1);
+    }
+
+    /// Sets how many bytes of registers an `ORDER BY` sorts in memory before spilling them to temporary files.
+    /// 64 MiB by default.
+    pub fn set_sort_memory(&mut self, bytes: usize) {
+        self.session.settings.sort_memory = bytes;
+    }
+
+    /// Executes a statement and returns the number of registers it inserted, updated or deleted,
+    /// or the number of rows it returned if it is a query.
+    pub fn execute(&mut self, sql: &str) -> Result<usize, SqlError> {
+        Ok(self.session.exec(sql)?.materialize()?.rows())
+    }
+
+    /// Executes a query and returns an iterator over the rows of its result.
+    ///
+    /// The rows of a SELECT are read from the table as they are iterated, so the errors found while reading them
+    /// are returned by the iterator. Statements that don't return rows, like INSERT without RETURNING, are executed
+    /// and give an empty iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_sql::{Database, SqlError};
+    ///
+    /// # fn main() -> Result<(), SqlError> {
+    /// let mut database = Database::open("tablas")?;
+    ///
+    /// for row in database.query("SELECT nombre FROM clientes ORDER BY nombre")? {
+    ///     println!("{}", row?.values[0]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn query(&mut self, sql: &str) -> Result<Rows, SqlError> {
+        rows(self.session.exec(sql)?)
+    }
+
+    /// Parses a SELECT, INSERT, UPDATE or DELETE statement with `?` or `:name` parameters in place of its values,
+    /// to execute it many times with `execute_prepared` or `query_prepared`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rustic_sql::{Database, SqlError};
+    ///
+    /// # fn main() -> Result<(), SqlError> {
+    /// let mut database = Database::open("tablas")?;
+    /// let mut statement = database.prepare("INSERT INTO clientes (nombre, apellido) VALUES (?, :apellido)")?;
+    /// for (nombre, apellido) in [("Sean", "O'Brien"), ("Ana", "D'Amico")] {
+    ///     statement.bind(1, nombre)?;
+    ///     statement.bind_named("apellido", apellido)?;
+    ///     database.execute_prepared(&statement)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn prepare(&self, sql: &str) -> Result<PreparedStatement, SqlError> {
+        PreparedStatement::new(sql)
+    }
+
+    /// Executes a prepared statement with the values bound to its parameters, see `execute`.
+    pub fn execute_prepared(&mut self, statement: &PreparedStatement) -> Result<usize, SqlError> {
+        Ok(self
+            .session
+            .exec_statement(statement.statement()?)?
+            .materialize()?
+            .rows())
+    }
+
+    /// Executes a prepared query with the values bound to its parameters, see `query`.
+    pub fn query_prepared(&mut self, statement: &PreparedStatement) -> Result<Rows, SqlError> {
+        rows(self.session.exec_statement(statement.statement()?)?)
+    }
+
+    /// Executes a statement built with `Query`, see `execute`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rustic_sql::{col, Database, Query, SqlError};
+    ///
+    /// # fn main() -> Result<(), SqlError> {
+    /// let mut database = Database::open("tablas")?;
+    /// let updated = database.execute_built(
+    ///     Query::update("clientes")
+    ///         .set("email", "ana@email.com")
+    ///         .filter(col("id").eq(2)),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn execute_built(&mut self, query: impl Into<Query>) -> Result<usize, SqlError> {
+        Ok(self
+            .session
+            .exec_statement(query.into().statement()?)?
+            .materialize()?
+            .rows())
+    }
+
+    /// Executes a query built with `Query`, see `query`.
+    pub fn query_built(&mut self, query: impl Into<Query>) -> Result<Rows, SqlError> {
+        rows(self.session.exec_statement(query.into().statement()?)?)
+    }
+
+    /// Executes a query and deserializes each row of its result into a struct with fields named like its columns,
+    /// see `from_row`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_sql::{Database, SqlError};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Cliente {
+    ///     id: i64,
+    ///     nombre: String,
+    /// }
+    ///
+    /// # fn main() -> Result<(), SqlError> {
+    /// let mut database = Database::open("tablas")?;
+    /// let clientes = database.query_as::<Cliente>("SELECT * FROM clientes")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    #[cfg(feature = "serde")]
+    pub fn query_as<T: DeserializeOwned>(&mut self, sql: &str) -> Result<Vec<T>, SqlError> {
+        self.query(sql)?.map(|row| from_row(row?)).collect()
+    }
+
+    /// Inserts a struct as a register of a table, with a column for each field, see `to_register`.
+    ///
+    /// The values of the fields are bound to a prepared statement, so they are never parsed as part of a query.
+    ///
+    /// Returns the number of registers inserted.
+    #[cfg(feature = "serde")]
+    pub fn insert<T: Serialize>(&mut self, table_name: &str, value: &T) -> Result<usize, SqlError> {
+        let register = to_register(value)?;
+        let columns: Vec<&str> = register.iter().map(|(column, _)| column.as_str()).collect();
+        let parameters = vec!["?"; register.len()];
+
+        let mut statement = self.prepare(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table_name,
+            columns.join(", "),
+            parameters.join(", ")
+        ))?;
+        for (idx, (_, value)) in register.into_iter().enumerate() {
+            statement.bind(idx + 1, value)?;
+        }
+
+        self.execute_prepared(&statement)
+    }
+}
+
+/// Returns an iterator over the rows of the result of a statement, which reads the rows of a stream as it is iterated.
+fn rows(result: QueryResult) -> Result<Rows, SqlError> {
+    let stream = match result {
+        QueryResult::Stream(stream) => stream,
+        QueryResult::Rows(table) => {
+            RegisterStream::new(table.columns, table.registers.into_iter().map(Ok))
+        }
+        QueryResult::Affected { .. } | QueryResult::Empty => {
+            RegisterStream::new(Vec::new(), std::iter::empty())
+        }
+    };
+    Ok(Rows::new(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Database;
+    use crate::{
+        builder::{col, Query},
+        clauses::orderby_sql::Order,
+        errors::SqlError,
+        lock::TableLock,
+        value::Value,
+    };
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn execute_and_query() {
+        let folder = std::env::temp_dir().join("rustic_sql_database");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id,nombre,email\n1,Juan,\n").unwrap();
+
+        let mut database = Database::open(folder.to_str().unwrap()).unwrap();
+        assert_eq!(
+            database.execute(
+                "INSERT INTO clientes (id, nombre, email) VALUES (2, 'Ana', 'ana@email.com')"
+            ),
+            Ok(1)
+        );
+        assert_eq!(
+            database.execute("UPDATE clientes SET nombre = 'Juana'"),
+            Ok(2)
+        );
+
+        let mut rows = database
+            .query("SELECT email, id FROM clientes ORDER BY id")
+            .unwrap();
+        assert_eq!(rows.columns[0].name, "email");
+
+        let row = rows.next().unwrap().unwrap();
+        assert_eq!(row.values, vec![Value::Null, Value::Integer(1)]);
+        assert_eq!(row.get("id"), Some(&Value::Integer(1)));
+        assert_eq!(row.get("nombre"), None);
+
+        let row = rows.next().unwrap().unwrap();
+        assert_eq!(
+            row.get("email"),
+            Some(&Value::Text(String::from("ana@email.com")))
+        );
+        assert_eq!(rows.next(), None);
+
+        assert_eq!(database.query("DELETE FROM clientes").unwrap().count(), 0);
+        assert_eq!(database.query("SELECT * FROM clientes").unwrap().count(), 0);
+        assert_eq!(
+            database.execute("SELECT * FROM productos"),
+            Err(SqlError::InvalidTable)
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+        assert_eq!(
+            Database::open(folder.to_str().unwrap()).err(),
+            Some(SqlError::Error)
+        );
+    }
+
+    #[test]
+    fn stale_index_is_not_used() {
+        let folder = std::env::temp_dir().join("rustic_sql_database_stale_index");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("ordenes.csv"), "id,id_cliente\n1,1\n2,2\n").unwrap();
+
+        let mut database = Database::open(folder.to_str().unwrap()).unwrap();
+        database
+            .execute("CREATE INDEX idx ON ordenes (id_cliente)")
+            .unwrap();
+
+        // The table is replaced without rebuilding its index, like an UPDATE interrupted after its rename.
+        fs::write(folder.join("nuevo.csv"), "id,id_cliente\n10,2\n20,1\n").unwrap();
+        fs::rename(folder.join("nuevo.csv"), folder.join("ordenes.csv")).unwrap();
+
+        assert_eq!(
+            database.execute("UPDATE ordenes SET id = 21 WHERE id_cliente = 1"),
+            Ok(1)
+        );
+        assert_eq!(
+            database.execute("INSERT INTO ordenes (id, id_cliente) VALUES (30, 1)"),
+            Ok(1)
+        );
+        assert_eq!(
+            database.execute("DELETE FROM ordenes WHERE id_cliente = 1"),
+            Ok(2)
+        );
+        assert_eq!(
+            fs::read_to_string(folder.join("ordenes.csv")).unwrap(),
+            "id,id_cliente\n10,2\n"
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn settings_are_per_database() {
+        let folder = std::env::temp_dir().join("rustic_sql_database_settings");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("ordenes.csv"), "id\n1\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let mut waiting = Database::open(folder_path).unwrap();
+        waiting.set_lock_timeout(Duration::from_millis(50));
+        let other = Database::open(folder_path).unwrap();
+        assert_eq!(other.session.settings.lock_timeout, Duration::from_secs(5));
+
+        let lock = TableLock::exclusive(folder_path, "ordenes", Duration::ZERO).unwrap();
+        assert_eq!(
+            waiting.execute("DELETE FROM ordenes"),
+            Err(SqlError::Locked {
+                table: String::from("ordenes")
+            })
+        );
+        drop(lock);
+        assert_eq!(waiting.execute("DELETE FROM ordenes"), Ok(1));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn prepared_statements() {
+        let folder = std::env::temp_dir().join("rustic_sql_database_prepared");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(
+            folder.join("clientes.csv"),
+            "id,nombre,apellido
+",
+        )
+        .unwrap();
+
+        let mut database = Database::open(folder.to_str().unwrap()).unwrap();
+        let mut insert = database
+            .prepare("INSERT INTO clientes (id, nombre, apellido) VALUES (?, ?, :apellido)")
+            .unwrap();
+        for (id, nombre, apellido) in [(1, "Sean", "O'Brien"), (2, "Ana", "WHERE id = 1")] {
+            insert.bind(1, id).unwrap();
+            insert.bind(2, nombre).unwrap();
+            insert.bind_named("apellido", apellido).unwrap();
+            assert_eq!(database.execute_prepared(&insert), Ok(1));
+        }
+
+        let mut select = database
+            .prepare("SELECT id FROM clientes WHERE apellido = :apellido")
+            .unwrap();
+        select.bind_named("apellido", "O'Brien").unwrap();
+        let ids: Vec<Value> = database
+            .query_prepared(&select)
+            .unwrap()
+            .map(|row| row.unwrap().values[0].clone())
+            .collect();
+        assert_eq!(ids, vec![Value::Integer(1)]);
+
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id,nombre,apellido\n1,Sean,O'Brien\n2,Ana,WHERE id = 1\n"
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn built_statements() {
+        let folder = std::env::temp_dir().join("rustic_sql_database_built");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id,nombre\n1,Juan\n").unwrap();
+
+        let mut database = Database::open(folder.to_str().unwrap()).unwrap();
+        for (id, nombre) in [(2, "Ana"), (3, "O'Brien")] {
+            let insert = Query::insert_into("clientes")
+                .value("id", id)
+                .value("nombre", nombre);
+            assert_eq!(database.execute_built(insert), Ok(1));
+        }
+        assert_eq!(
+            database.execute_built(Query::delete_from("clientes").filter(col("id").eq(1))),
+            Ok(1)
+        );
+
+        let query = Query::select(["nombre"])
+            .from("clientes")
+            .order_by("id", Order::Desc)
+            .limit(1);
+        let nombres: Vec<Value> = database
+            .query_built(query)
+            .unwrap()
+            .map(|row| row.unwrap().values[0].clone())
+            .collect();
+        assert_eq!(nombres, vec![Value::from("O'Brien")]);
+
+        assert_eq!(
+            database.execute_built(Query::select(["email"]).from("clientes")),
+            Err(SqlError::InvalidColumn)
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn insert_and_query_structs() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Cliente {
+            id: Option<i64>,
+            nombre: String,
+            apellido: String,
+        }
+
+        let folder = std::env::temp_dir().join("rustic_sql_database_serde");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id,nombre,apellido\n").unwrap();
+        fs::write(
+            folder.join("clientes.schema"),
+            "id INTEGER PRIMARY KEY AUTOINCREMENT\n",
+        )
+        .unwrap();
+
+        let mut database = Database::open(folder.to_str().unwrap()).unwrap();
+        let cliente = Cliente {
+            id: None,
+            nombre: String::from("Sean"),
+            apellido: String::from("O'Brien"),
+        };
+        assert_eq!(database.insert("clientes", &cliente), Ok(1));
+
+        assert_eq!(
+            database.query_as::<Cliente>("SELECT * FROM clientes"),
+            Ok(vec![Cliente {
+                id: Some(1),
+                ..cliente
+            }])
+        );
+        assert_eq!(
+            database.query_as::<Cliente>("SELECT nombre FROM clientes"),
+            Err(SqlError::InvalidColumn)
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -7,6 +7,10 @@ use std::fmt::Display;
 /// - `InvalidTable`: related to problems with the processing of tables.
 /// - `InvalidColumn`: related to problems with the processing of columns.
 /// - `InvalidSyntax`: related to problems with the processing of queries.
+/// - `ConstraintViolation`: a statement would break a constraint declared in the table schema. Holds the violated constraint and the conflicting value.
+/// - `Locked`: the table is locked by another process for longer than the lock timeout. Holds the name of the table.
+/// - `ReadOnly`: a statement that changes the tables was executed in a read-only session.
+/// - `Conflict`: a transaction can't be committed because a file it changed was changed by another process since the transaction read it. Holds the name of the file.
 /// - `Error`: generic type for other possible errors detected.
 ///
 #[derive(Debug, PartialEq)]
@@ -14,15 +18,64 @@ pub enum SqlError {
     InvalidTable,
     InvalidColumn,
     InvalidSyntax,
+    ConstraintViolation { constraint: String, value: String },
+    Locked { table: String },
+    ReadOnly,
+    Conflict { file: String },
     Error,
 }
 
+impl SqlError {
+    /// Returns the exit code of the program when a statement fails with this error.
+    ///
+    /// - `1`: `Error`, for input and output errors.
+    /// - `3`: `InvalidSyntax`.
+    /// - `4`: `InvalidTable`.
+    /// - `5`: `InvalidColumn`.
+    /// - `6`: `ConstraintViolation`.
+    /// - `7`: `Locked`.
+    /// - `8`: `ReadOnly`.
+    /// - `9`: `Conflict`.
+    ///
+    /// The code `2` is used for invalid command line arguments.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            SqlError::Error => 1,
+            SqlError::InvalidSyntax => 3,
+            SqlError::InvalidTable => 4,
+            SqlError::InvalidColumn => 5,
+            SqlError::ConstraintViolation { .. } => 6,
+            SqlError::Locked { .. } => 7,
+            SqlError::ReadOnly => 8,
+            SqlError::Conflict { .. } => 9,
+        }
+    }
+}
+
+impl std::error::Error for SqlError {}
+
 impl Display for SqlError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SqlError::InvalidTable => write!(f, "[InvalidTable]: [Error to process table]"),
             SqlError::InvalidColumn => write!(f, "[InvalidColumn]: [Error to process column]"),
             SqlError::InvalidSyntax => write!(f, "[InvalidSyntax]: [Error to process query]"),
+            SqlError::ConstraintViolation { constraint, value } => write!(
+                f,
+                "[ConstraintViolation]: [{} violated by value {}]",
+                constraint, value
+            ),
+            SqlError::Locked { table } => write!(f, "[Locked]: [Table {} is locked]", table),
+            SqlError::ReadOnly => {
+                write!(f, "[ReadOnly]: [The tables were opened in read-only mode]")
+            }
+            SqlError::Conflict { file } => {
+                write!(
+                    f,
+                    "[Conflict]: [File {} was changed by another process]",
+                    file
+                )
+            }
             SqlError::Error => write!(f, "[Error]: [An error occurred]"),
         }
     }
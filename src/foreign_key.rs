@@ -0,0 +1,533 @@
+use crate::{
+    errors::SqlError,
+    register::Register,
+    schema::{format_values, Schema},
+    table::Table,
+    utils::{is_on, is_references, read_table},
+    value::Value,
+};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
+};
+
+/// Enum for the actions taken on the referencing registers when a referenced register is deleted or updated.
+/// - `Restrict`: The statement is rejected.
+/// - `Cascade`: The referencing registers are deleted, or updated with the new referenced values.
+/// - `SetNull`: The referencing columns are emptied.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReferentialAction {
+    Restrict,
+    Cascade,
+    SetNull,
+}
+
+/// Struct that represents a `FOREIGN KEY` constraint.
+///
+/// # Fields
+///
+/// * `columns` - The referencing columns of the table.
+/// * `table` - The name of the referenced table.
+/// * `references` - The referenced columns of the referenced table.
+/// * `on_delete` - The action taken when a referenced register is deleted.
+/// * `on_update` - The action taken when a referenced register is updated.
+///
+#[derive(Debug, PartialEq)]
+pub struct ForeignKey {
+    pub columns: Vec<String>,
+    pub table: String,
+    pub references: Vec<String>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+
+/// Pending content of the tables modified by a statement, including the ones modified by referential actions.
+pub type PendingTables = HashMap<String, Table>;
+
+impl ForeignKey {
+    /// Creates and returns a new `ForeignKey` from tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The referencing columns.
+    /// * `tokens` - A slice of tokens that contains the constraint.
+    /// * `i` - A mutable reference to the position of the `REFERENCES` token. It is left after the last token of the constraint.
+    ///
+    /// The tokens should be in the following order: `REFERENCES`, `table`, `columns`, followed by optional `ON DELETE action` and `ON UPDATE action`.
+    /// The action can be `RESTRICT`, `NO ACTION`, `CASCADE` or `SET NULL`. If it is not specified, it is `RESTRICT`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let tokens = tokens_from_query("REFERENCES clientes (id) ON DELETE CASCADE");
+    /// let mut i = 0;
+    /// let foreign_key = ForeignKey::new_from_tokens(vec!["id_cliente".to_string()], &tokens, &mut i).unwrap();
+    ///
+    /// assert_eq!(foreign_key.on_delete, ReferentialAction::Cascade);
+    /// assert_eq!(foreign_key.on_update, ReferentialAction::Restrict);
+    /// ```
+    ///
+    pub fn new_from_tokens(
+        columns: Vec<String>,
+        tokens: &[String],
+        i: &mut usize,
+    ) -> Result<Self, SqlError> {
+        if *i + 2 >= tokens.len() || !is_references(&tokens[*i]) {
+            return Err(SqlError::InvalidSyntax);
+        }
+        let table = tokens[*i + 1].to_string();
+        let references: Vec<String> = tokens[*i + 2]
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .collect();
+        *i += 3;
+
+        if references.len() != columns.len() {
+            return Err(SqlError::InvalidSyntax);
+        }
+
+        let mut on_delete = ReferentialAction::Restrict;
+        let mut on_update = ReferentialAction::Restrict;
+
+        while *i + 2 < tokens.len() && is_on(&tokens[*i]) {
+            let (action, len) = match (tokens[*i + 2].as_str(), tokens.get(*i + 3)) {
+                ("RESTRICT", _) => (ReferentialAction::Restrict, 3),
+                ("CASCADE", _) => (ReferentialAction::Cascade, 3),
+                ("NO", Some(next)) if next == "ACTION" => (ReferentialAction::Restrict, 4),
+                ("SET", Some(next)) if next == "NULL" => (ReferentialAction::SetNull, 4),
+                _ => return Err(SqlError::InvalidSyntax),
+            };
+            match tokens[*i + 1].as_str() {
+                "DELETE" => on_delete = action,
+                "UPDATE" => on_update = action,
+                _ => return Err(SqlError::InvalidSyntax),
+            }
+            *i += len;
+        }
+
+        Ok(Self {
+            columns,
+            table,
+            references,
+            on_delete,
+            on_update,
+        })
+    }
+
+    /// Returns the name of the constraint, for example `FOREIGN KEY (id_cliente) REFERENCES clientes (id)`.
+    pub fn name(&self) -> String {
+        format!(
+            "FOREIGN KEY ({}) REFERENCES {} ({})",
+            self.columns.join(", "),
+            self.table,
+            self.references.join(", ")
+        )
+    }
+
+    /// Returns the values of the referencing columns in the given register.
+    pub fn values(&self, register: &Register) -> Vec<String> {
+        columns_values(&self.columns, register)
+    }
+
+    /// Returns the values of the referenced columns in the given register of the referenced table.
+    pub fn referenced_values(&self, register: &Register) -> Vec<String> {
+        columns_values(&self.references, register)
+    }
+
+    /// Returns a `ConstraintViolation` error for the constraint with the given values.
+    pub fn violation(&self, values: &[String]) -> SqlError {
+        SqlError::ConstraintViolation {
+            constraint: self.name(),
+            value: format_values(values),
+        }
+    }
+}
+
+fn columns_values(columns: &[String], register: &Register) -> Vec<String> {
+    columns
+        .iter()
+        .map(|col| register.get(col).map(Value::to_string).unwrap_or_default())
+        .collect()
+}
+
+fn child_schemas(folder_path: &str, table_name: &str) -> Result<Vec<Schema>, SqlError> {
+    let mut schemas = Vec::new();
+    let entries = fs::read_dir(folder_path).map_err(|_| SqlError::Error)?;
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if let Some(child_name) = file_name.strip_suffix(".schema") {
+            let schema = Schema::open(folder_path, child_name)?;
+            if schema.foreign_keys.iter().any(|fk| fk.table == table_name) {
+                schemas.push(schema);
+            }
+        }
+    }
+
+    Ok(schemas)
+}
+
+/// Returns the given table and every table that references it through foreign keys, directly or through other tables.
+///
+/// These are the tables that a `DELETE` or `UPDATE` over the given table may change or check.
+pub fn dependent_tables(folder_path: &str, table_name: &str) -> Result<BTreeSet<String>, SqlError> {
+    let mut tables = BTreeSet::from([table_name.to_string()]);
+    let mut pending = vec![table_name.to_string()];
+
+    while let Some(table) = pending.pop() {
+        for schema in child_schemas(folder_path, &table)? {
+            if tables.insert(schema.table_name.to_string()) {
+                pending.push(schema.table_name);
+            }
+        }
+    }
+
+    Ok(tables)
+}
+
+fn take_table(
+    folder_path: &str,
+    table_name: &str,
+    tables: &mut PendingTables,
+) -> Result<Table, SqlError> {
+    match tables.remove(table_name) {
+        Some(table) => Ok(table),
+        None => read_table(folder_path, table_name),
+    }
+}
+
+fn matching_registers<F>(table: &Table, predicate: F) -> Vec<usize>
+where
+    F: Fn(&Register) -> bool,
+{
+    table
+        .registers
+        .iter()
+        .enumerate()
+        .filter(|(_, register)| predicate(register))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Applies the `ON DELETE` actions of the foreign keys that reference the registers deleted from a table.
+///
+/// The tables modified by the actions are added to the pending tables, and the actions are applied recursively
+/// to the tables that reference them. Returns a `ConstraintViolation` error if a `RESTRICT` foreign key references a deleted register.
+///
+/// # Arguments
+///
+/// * `folder_path` - A string slice that contains the path to the folder where the tables are stored.
+/// * `table_name` - A string slice that contains the name of the table the registers were deleted from.
+/// * `deleted` - A slice with the deleted registers.
+/// * `tables` - The pending content of the tables modified by the statement.
+///
+pub fn cascade_delete(
+    folder_path: &str,
+    table_name: &str,
+    deleted: &[Register],
+    tables: &mut PendingTables,
+) -> Result<(), SqlError> {
+    for schema in child_schemas(folder_path, table_name)? {
+        for fk in schema
+            .foreign_keys
+            .iter()
+            .filter(|fk| fk.table == table_name)
+        {
+            let keys: HashSet<Vec<String>> = deleted
+                .iter()
+                .map(|register| fk.referenced_values(register))
+                .filter(|values| values.iter().all(|value| !value.is_empty()))
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
+
+            let is_pending = tables.contains_key(&schema.table_name);
+            let mut child = take_table(folder_path, &schema.table_name, tables)?;
+            let matching =
+                matching_registers(&child, |register| keys.contains(&fk.values(register)));
+
+            if matching.is_empty() {
+                if is_pending {
+                    tables.insert(schema.table_name.to_string(), child);
+                }
+                continue;
+            }
+
+            match fk.on_delete {
+                ReferentialAction::Restrict => {
+                    return Err(fk.violation(&fk.values(&child.registers[matching[0]])));
+                }
+                ReferentialAction::Cascade => {
+                    let matching: HashSet<usize> = matching.into_iter().collect();
+                    let mut child_deleted = Vec::new();
+                    let mut rest = Vec::new();
+                    for (idx, register) in child.registers.into_iter().enumerate() {
+                        if matching.contains(&idx) {
+                            child_deleted.push(register);
+                        } else {
+                            rest.push(register);
+                        }
+                    }
+                    child.registers = rest;
+                    tables.insert(schema.table_name.to_string(), child);
+
+                    cascade_delete(folder_path, &schema.table_name, &child_deleted, tables)?;
+                }
+                ReferentialAction::SetNull => {
+                    let child_updated = set_values(&schema, fk, &mut child, &matching, None)?;
+                    tables.insert(schema.table_name.to_string(), child);
+                    cascade_update(folder_path, &schema.table_name, &child_updated, tables)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the `ON UPDATE` actions of the foreign keys that reference the registers updated in a table.
+///
+/// Only the registers whose referenced values changed are taken into account. The tables modified by the actions are added
+/// to the pending tables, and the actions are applied recursively to the tables that reference them.
+/// Returns a `ConstraintViolation` error if a `RESTRICT` foreign key references an updated register.
+///
+/// # Arguments
+///
+/// * `folder_path` - A string slice that contains the path to the folder where the tables are stored.
+/// * `table_name` - A string slice that contains the name of the updated table.
+/// * `updated` - A slice with the previous and the new version of every updated register.
+/// * `tables` - The pending content of the tables modified by the statement.
+///
+pub fn cascade_update(
+    folder_path: &str,
+    table_name: &str,
+    updated: &[(Register, Register)],
+    tables: &mut PendingTables,
+) -> Result<(), SqlError> {
+    for schema in child_schemas(folder_path, table_name)? {
+        for fk in schema
+            .foreign_keys
+            .iter()
+            .filter(|fk| fk.table == table_name)
+        {
+            let changes: HashMap<Vec<String>, Vec<String>> = updated
+                .iter()
+                .map(|(old, new)| (fk.referenced_values(old), fk.referenced_values(new)))
+                .filter(|(old, new)| old != new && old.iter().all(|value| !value.is_empty()))
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+
+            let is_pending = tables.contains_key(&schema.table_name);
+            let mut child = take_table(folder_path, &schema.table_name, tables)?;
+            let matching = matching_registers(&child, |register| {
+                changes.contains_key(&fk.values(register))
+            });
+
+            if matching.is_empty() {
+                if is_pending {
+                    tables.insert(schema.table_name.to_string(), child);
+                }
+                continue;
+            }
+
+            let child_updated = match fk.on_update {
+                ReferentialAction::Restrict => {
+                    return Err(fk.violation(&fk.values(&child.registers[matching[0]])));
+                }
+                ReferentialAction::Cascade => {
+                    set_values(&schema, fk, &mut child, &matching, Some(&changes))?
+                }
+                ReferentialAction::SetNull => set_values(&schema, fk, &mut child, &matching, None)?,
+            };
+            tables.insert(schema.table_name.to_string(), child);
+            cascade_update(folder_path, &schema.table_name, &child_updated, tables)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets the referencing columns of the matching registers to their new referenced values, or empties them if there are none,
+/// and checks the constraints of the child table on the modified registers.
+fn set_values(
+    schema: &Schema,
+    fk: &ForeignKey,
+    child: &mut Table,
+    matching: &[usize],
+    changes: Option<&HashMap<Vec<String>, Vec<String>>>,
+) -> Result<Vec<(Register, Register)>, SqlError> {
+    let mut updated = Vec::new();
+
+    for idx in matching {
+        let old = child.registers[*idx].clone();
+        let new = &mut child.registers[*idx];
+        let values = changes.and_then(|changes| changes.get(&fk.values(&old)));
+
+        for (pos, col) in fk.columns.iter().enumerate() {
+            let value = values.map_or("", |values| values[pos].as_str());
+            new.set(col, Value::from_csv(value))?;
+        }
+        schema.check_register(new)?;
+
+        updated.push((old, new.clone()));
+    }
+
+    schema.check_keys(&child.registers, matching)?;
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cascade_delete, cascade_update, ForeignKey, PendingTables, ReferentialAction};
+    use crate::{
+        errors::SqlError,
+        register::{Columns, Register},
+        tokens::tokens_from_query,
+        utils::read_table,
+        value::Value,
+    };
+    use std::{fs, path::PathBuf, sync::Arc};
+
+    fn create_folder(name: &str, on_delete: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(name);
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id,nombre\n1,Juan\n2,Ana\n").unwrap();
+        fs::write(folder.join("clientes.schema"), "id PRIMARY KEY\nnombre\n").unwrap();
+        fs::write(
+            folder.join("ordenes.csv"),
+            "id,id_cliente\n101,1\n102,2\n103,1\n",
+        )
+        .unwrap();
+        fs::write(
+            folder.join("ordenes.schema"),
+            format!(
+                "id PRIMARY KEY\nid_cliente REFERENCES clientes (id) ON DELETE {} ON UPDATE CASCADE\n",
+                on_delete
+            ),
+        )
+        .unwrap();
+        folder
+    }
+
+    fn deleted_cliente() -> Vec<Register> {
+        let columns = Arc::new(Columns::new(vec![
+            String::from("id"),
+            String::from("nombre"),
+        ]));
+        vec![Register::from_csv("1,Juan", &columns)]
+    }
+
+    fn lines(tables: &PendingTables, name: &str) -> Vec<String> {
+        let table = tables.get(name).unwrap();
+        table.registers.iter().map(Register::to_csv).collect()
+    }
+
+    #[test]
+    fn new_from_tokens() {
+        let tokens =
+            tokens_from_query("REFERENCES clientes (id) ON UPDATE SET NULL ON DELETE NO ACTION");
+        let mut i = 0;
+        let foreign_key =
+            ForeignKey::new_from_tokens(vec![String::from("id_cliente")], &tokens, &mut i).unwrap();
+
+        assert_eq!(
+            foreign_key,
+            ForeignKey {
+                columns: vec![String::from("id_cliente")],
+                table: String::from("clientes"),
+                references: vec![String::from("id")],
+                on_delete: ReferentialAction::Restrict,
+                on_update: ReferentialAction::SetNull,
+            }
+        );
+        assert_eq!(i, tokens.len());
+    }
+
+    #[test]
+    fn new_from_tokens_with_invalid_action() {
+        let tokens = tokens_from_query("REFERENCES clientes (id) ON DELETE NOTHING");
+        let mut i = 0;
+
+        assert_eq!(
+            ForeignKey::new_from_tokens(vec![String::from("id_cliente")], &tokens, &mut i),
+            Err(SqlError::InvalidSyntax)
+        );
+    }
+
+    #[test]
+    fn delete_restrict() {
+        let folder = create_folder("rustic_sql_fk_delete_restrict", "RESTRICT");
+        let mut tables = PendingTables::new();
+
+        let result = cascade_delete(
+            folder.to_str().unwrap(),
+            "clientes",
+            &deleted_cliente(),
+            &mut tables,
+        );
+        assert_eq!(
+            result,
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("FOREIGN KEY (id_cliente) REFERENCES clientes (id)"),
+                value: String::from("'1'"),
+            })
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn delete_cascade() {
+        let folder = create_folder("rustic_sql_fk_delete_cascade", "CASCADE");
+        let mut tables = PendingTables::new();
+
+        cascade_delete(
+            folder.to_str().unwrap(),
+            "clientes",
+            &deleted_cliente(),
+            &mut tables,
+        )
+        .unwrap();
+        assert_eq!(lines(&tables, "ordenes"), vec!["102,2"]);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn delete_set_null() {
+        let folder = create_folder("rustic_sql_fk_delete_set_null", "SET NULL");
+        let mut tables = PendingTables::new();
+
+        cascade_delete(
+            folder.to_str().unwrap(),
+            "clientes",
+            &deleted_cliente(),
+            &mut tables,
+        )
+        .unwrap();
+        assert_eq!(lines(&tables, "ordenes"), vec!["101,", "102,2", "103,"]);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn update_cascade() {
+        let folder = create_folder("rustic_sql_fk_update_cascade", "RESTRICT");
+        let folder_path = folder.to_str().unwrap();
+        let clientes = read_table(folder_path, "clientes").unwrap();
+        let old = clientes.registers[1].clone();
+        let mut new = old.clone();
+        new.set("id", Value::Integer(5)).unwrap();
+        let mut tables = PendingTables::new();
+
+        cascade_update(folder_path, "clientes", &[(old, new)], &mut tables).unwrap();
+        assert_eq!(lines(&tables, "ordenes"), vec!["101,1", "102,5", "103,1"]);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+use crate::{
+    clauses::{condition::Condition, where_sql::Where},
+    errors::SqlError,
+    logical_operator::LogicalOperator,
+    operator::Operator,
+    register::Register,
+    snapshot::file_id,
+    utils::{find_file_in_folder, is_create, is_index, is_on, lines_with_offsets, write_file},
+};
+use std::{
+    collections::BTreeSet,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, Write},
+};
+
+/// Struct that represents a secondary index over a column of a table.
+///
+/// Each index is stored in a `<name>.index` file next to the table, where the first line holds the table and column names
+/// and the identifier of the table file the index was built from, and the following lines hold every value of the column with the byte offset of its register in the table file, sorted by value.
+/// The entries of the registers inserted since the index was built are appended after the sorted ones, and sorted when the index is opened.
+///
+/// # Fields
+///
+/// * `name` - The name of the index.
+/// * `table_name` - The name of the indexed table.
+/// * `column` - The name of the indexed column.
+/// * `table_id` - The identifier of the table file the offsets belong to, if the system provides one.
+/// * `entries` - The pairs of value and byte offset, sorted by value and then by offset.
+///
+#[derive(Debug, PartialEq)]
+pub struct Index {
+    pub name: String,
+    pub table_name: String,
+    pub column: String,
+    pub table_id: Option<u64>,
+    pub entries: Vec<(String, u64)>,
+}
+
+impl Index {
+    /// Creates and returns a new `Index` from the tokens of a `CREATE INDEX` statement, without entries.
+    ///
+    /// The tokens should be in the following order: `CREATE`, `INDEX`, `name`, `ON`, `table_name`, `column`.
+    /// The column should be between parentheses, only one column can be indexed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let tokens = tokens_from_query("CREATE INDEX idx ON ordenes (id_cliente)");
+    /// let index = Index::new_from_tokens(tokens).unwrap();
+    ///
+    /// assert_eq!(index.table_name, "ordenes");
+    /// assert_eq!(index.column, "id_cliente");
+    /// ```
+    ///
+    pub fn new_from_tokens(tokens: Vec<String>) -> Result<Self, SqlError> {
+        if tokens.len() != 6 || !is_create(&tokens[0]) || !is_index(&tokens[1]) {
+            return Err(SqlError::InvalidSyntax);
+        }
+        if !is_on(&tokens[3]) || tokens[5].contains(',') {
+            return Err(SqlError::InvalidSyntax);
+        }
+
+        Ok(Self {
+            name: tokens[2].to_string(),
+            table_name: tokens[4].to_string(),
+            column: tokens[5].trim().to_string(),
+            table_id: None,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Builds the index from the current content of its table and stores it in the folder.
+    ///
+    /// Returns an error if an index with the same name already exists, or if the column is not in the table.
+    pub fn create(&mut self, folder_path: &str) -> Result<(), SqlError> {
+        if find_file_in_folder(folder_path, &(self.name.to_string() + ".index")) {
+            return Err(SqlError::InvalidSyntax);
+        }
+        self.rebuild(folder_path)
+    }
+
+    /// Opens the index with the given name stored in the folder.
+    pub fn open(folder_path: &str, name: &str) -> Result<Self, SqlError> {
+        let file_name = name.to_string() + ".index";
+        if !find_file_in_folder(folder_path, &file_name) {
+            return Err(SqlError::InvalidTable);
+        }
+        let content = fs::read_to_string(folder_path.to_string() + "/" + &file_name)
+            .map_err(|_| SqlError::InvalidTable)?;
+        let mut lines = content.lines();
+
+        let header: Vec<&str> = lines.next().unwrap_or("").split(',').collect();
+        let (table_name, column, table_id) = match header.as_slice() {
+            [table_name, column] => (table_name, column, None),
+            [table_name, column, id] => (
+                table_name,
+                column,
+                Some(id.parse::<u64>().map_err(|_| SqlError::InvalidTable)?),
+            ),
+            _ => return Err(SqlError::InvalidTable),
+        };
+
+        let mut entries = Vec::new();
+        for line in lines {
+            let (value, offset) = line.rsplit_once(',').ok_or(SqlError::InvalidTable)?;
+            let offset = offset.parse::<u64>().map_err(|_| SqlError::InvalidTable)?;
+            entries.push((value.to_string(), offset));
+        }
+        entries.sort();
+
+        Ok(Self {
+            name: name.to_string(),
+            table_name: table_name.to_string(),
+            column: column.to_string(),
+            table_id,
+            entries,
+        })
+    }
+
+    /// Removes the index with the given name from the folder.
+    pub fn drop(folder_path: &str, name: &str) -> Result<(), SqlError> {
+        let file_name = name.to_string() + ".index";
+        if !find_file_in_folder(folder_path, &file_name) {
+            return Err(SqlError::InvalidTable);
+        }
+        fs::remove_file(folder_path.to_string() + "/" + &file_name).map_err(|_| SqlError::Error)
+    }
+
+    /// Returns every index declared over the given table.
+    pub fn for_table(folder_path: &str, table_name: &str) -> Result<Vec<Self>, SqlError> {
+        let mut indexes = Vec::new();
+        let entries = fs::read_dir(folder_path).map_err(|_| SqlError::InvalidTable)?;
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some(name) = file_name.strip_suffix(".index") {
+                let index = Self::open(folder_path, name)?;
+                if index.table_name == table_name {
+                    indexes.push(index);
+                }
+            }
+        }
+
+        Ok(indexes)
+    }
+
+    /// Reads the whole table again and stores the index with the new values and offsets.
+    ///
+    /// Used after the table file is rewritten, since the offsets of every register may change.
+    pub fn rebuild(&mut self, folder_path: &str) -> Result<(), SqlError> {
+        let path = folder_path.to_string() + "/" + &self.table_name + ".csv";
+        let file = File::open(path).map_err(|_| SqlError::InvalidTable)?;
+        let metadata = file.metadata().map_err(|_| SqlError::InvalidTable)?;
+
+        let mut position = None;
+        self.table_id = file_id(&metadata);
+        self.entries.clear();
+
+        for (idx, line) in lines_with_offsets(BufReader::new(file)).enumerate() {
+            let (offset, line) = line?;
+            if idx == 0 {
+                position = line.split(',').position(|col| col == self.column);
+                if position.is_none() {
+                    return Err(SqlError::InvalidColumn);
+                }
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let value = line.split(',').nth(position.unwrap_or(0)).unwrap_or("");
+            self.entries.push((value.to_string(), offset));
+        }
+
+        self.entries.sort();
+        self.write(folder_path)
+    }
+
+    /// Adds the register stored at the given offset of the table file to the index, appending its entry to the index file.
+    pub fn insert(
+        &mut self,
+        folder_path: &str,
+        register: &Register,
+        offset: u64,
+    ) -> Result<(), SqlError> {
+        let value = register.get(&self.column).ok_or(SqlError::InvalidColumn)?;
+        let entry = (value.to_string(), offset);
+
+        let path = folder_path.to_string() + "/" + &self.name + ".index";
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|_| SqlError::InvalidTable)?;
+        writeln!(file, "{},{}", entry.0, entry.1).map_err(|_| SqlError::Error)?;
+        file.sync_data().map_err(|_| SqlError::Error)?;
+
+        let position = self.entries.partition_point(|e| *e < entry);
+        self.entries.insert(position, entry);
+        Ok(())
+    }
+
+    /// Returns the offsets of the registers whose value in the indexed column meets the operator against the given value,
+    /// using the same comparison as the `WHERE` clause.
+    pub fn lookup(&self, operator: &Operator, value: &str) -> BTreeSet<u64> {
+        let lower = self.entries.partition_point(|(v, _)| v.as_str() < value);
+        let upper = self.entries.partition_point(|(v, _)| v.as_str() <= value);
+
+        let range = match operator {
+            Operator::Lesser => 0..lower,
+            Operator::Equal => lower..upper,
+            Operator::Greater => upper..self.entries.len(),
+        };
+
+        self.entries[range]
+            .iter()
+            .map(|(_, offset)| *offset)
+            .collect()
+    }
+
+    fn write(&self, folder_path: &str) -> Result<(), SqlError> {
+        let mut lines = match self.table_id {
+            Some(id) => vec![format!("{},{},{}", self.table_name, self.column, id)],
+            None => vec![format!("{},{}", self.table_name, self.column)],
+        };
+        for (value, offset) in &self.entries {
+            lines.push(format!("{},{}", value, offset));
+        }
+
+        write_file(folder_path, &(self.name.to_string() + ".index"), &lines)
+    }
+}
+
+fn candidates(condition: &Condition, indexes: &[Index]) -> Option<BTreeSet<u64>> {
+    match condition {
+        Condition::Simple {
+            field,
+            operator,
+            value,
+        } => indexes
+            .iter()
+            .find(|index| &index.column == field)
+            .map(|index| index.lookup(operator, value)),
+        Condition::Complex {
+            left,
+            operator,
+            right,
+        } => {
+            let left = left.as_ref().and_then(|left| candidates(left, indexes));
+            let right = candidates(right, indexes);
+            match (operator, left, right) {
+                (LogicalOperator::And, Some(left), Some(right)) => {
+                    Some(left.intersection(&right).copied().collect())
+                }
+                (LogicalOperator::And, Some(offsets), None)
+                | (LogicalOperator::And, None, Some(offsets)) => Some(offsets),
+                (LogicalOperator::Or, Some(left), Some(right)) => {
+                    Some(left.union(&right).copied().collect())
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Uses the indexes of the table to find the offsets of the registers that may meet the `WHERE` clause.
+///
+/// Returns `None` when there is no `WHERE` clause or it can't be answered with the indexes, so the whole table has to be read.
+/// The registers found still have to be checked against the `WHERE` clause.
+///
+/// When a table identifier is given, only the indexes built from that table file are used,
+/// so a reader of a snapshot doesn't use offsets that belong to a newer version of the table.
+///
+/// # Examples
+///
+/// ```ignore
+/// let where_clause = Where::new_from_tokens(vec!["WHERE", "id_cliente", "=", "1"]).unwrap();
+/// let offsets = find_candidates("tablas", "ordenes", Some(&where_clause), None).unwrap();
+/// ```
+///
+pub fn find_candidates(
+    folder_path: &str,
+    table_name: &str,
+    where_clause: Option<&Where>,
+    table_id: Option<u64>,
+) -> Result<Option<BTreeSet<u64>>, SqlError> {
+    let Some(where_clause) = where_clause else {
+        return Ok(None);
+    };
+    let indexes: Vec<Index> = Index::for_table(folder_path, table_name)?
+        .into_iter()
+        .filter(|index| table_id.is_none() || index.table_id == table_id)
+        .collect();
+    if indexes.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(candidates(&where_clause.condition, &indexes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_candidates, Index};
+    use crate::{
+        clauses::where_sql::Where,
+        errors::SqlError,
+        operator::Operator,
+        register::{Columns, Register},
+        snapshot::Snapshot,
+        tokens::tokens_from_query,
+    };
+    use std::{collections::BTreeSet, fs, io::Read, sync::Arc, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn new_index(entries: &[(&str, u64)]) -> Index {
+        Index {
+            name: String::from("idx"),
+            table_name: String::from("ordenes"),
+            column: String::from("id_cliente"),
+            table_id: None,
+            entries: entries
+                .iter()
+                .map(|(value, offset)| (value.to_string(), *offset))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn new_from_tokens() {
+        let tokens = tokens_from_query("CREATE INDEX idx ON ordenes (id_cliente)");
+        assert_eq!(Index::new_from_tokens(tokens), Ok(new_index(&[])));
+    }
+
+    #[test]
+    fn new_from_tokens_with_two_columns() {
+        let tokens = tokens_from_query("CREATE INDEX idx ON ordenes (id_cliente, producto)");
+        assert_eq!(Index::new_from_tokens(tokens), Err(SqlError::InvalidSyntax));
+    }
+
+    #[test]
+    fn lookup() {
+        let index = new_index(&[("1", 10), ("1", 30), ("2", 20), ("3", 40)]);
+
+        assert_eq!(
+            index.lookup(&Operator::Equal, "1"),
+            BTreeSet::from([10, 30])
+        );
+        assert_eq!(
+            index.lookup(&Operator::Greater, "1"),
+            BTreeSet::from([20, 40])
+        );
+        assert_eq!(
+            index.lookup(&Operator::Lesser, "3"),
+            BTreeSet::from([10, 20, 30])
+        );
+        assert_eq!(index.lookup(&Operator::Equal, "9"), BTreeSet::new());
+    }
+
+    #[test]
+    fn create_insert_and_find_candidates() {
+        let folder = std::env::temp_dir().join("rustic_sql_index_create");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(
+            folder.join("ordenes.csv"),
+            "id,id_cliente\n101,2\n102,1\n103,2\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let tokens = tokens_from_query("CREATE INDEX idx ON ordenes (id_cliente)");
+        let mut index = Index::new_from_tokens(tokens).unwrap();
+        index.create(folder_path).unwrap();
+        assert_eq!(
+            Index::open(folder_path, "idx").unwrap().entries,
+            new_index(&[("1", 20), ("2", 14), ("2", 26)]).entries
+        );
+
+        let columns = Arc::new(Columns::new(vec![
+            String::from("id"),
+            String::from("id_cliente"),
+        ]));
+        let register = Register::from_csv("104,1", &columns);
+        fs::write(
+            folder.join("ordenes.csv"),
+            "id,id_cliente\n101,2\n102,1\n103,2\n104,1\n",
+        )
+        .unwrap();
+        index.insert(folder_path, &register, 32).unwrap();
+        let content = fs::read_to_string(folder.join("idx.index")).unwrap();
+        assert!(content.ends_with("2,26\n1,32\n"));
+        assert_eq!(Index::open(folder_path, "idx").unwrap(), index);
+
+        let where_clause = Where::new_from_tokens(vec!["WHERE", "id_cliente", "=", "1"]).unwrap();
+        let snapshot = Snapshot::open(folder_path, "ordenes", TIMEOUT).unwrap();
+        let offsets = find_candidates(folder_path, "ordenes", Some(&where_clause), snapshot.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(offsets, BTreeSet::from([20, 32]));
+
+        let mut content = String::new();
+        snapshot
+            .lines_at(&offsets)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "id,id_cliente\n102,1\n104,1\n");
+
+        let where_clause = Where::new_from_tokens(vec!["WHERE", "id", "=", "101"]).unwrap();
+        assert_eq!(
+            find_candidates(folder_path, "ordenes", Some(&where_clause), None),
+            Ok(None)
+        );
+
+        fs::write(folder.join("new.csv"), "id,id_cliente\n102,1\n").unwrap();
+        fs::rename(folder.join("new.csv"), folder.join("ordenes.csv")).unwrap();
+        let snapshot = Snapshot::open(folder_path, "ordenes", TIMEOUT).unwrap();
+        let where_clause = Where::new_from_tokens(vec!["WHERE", "id_cliente", "=", "1"]).unwrap();
+        assert_eq!(
+            find_candidates(folder_path, "ordenes", Some(&where_clause), snapshot.id),
+            Ok(None)
+        );
+
+        Index::drop(folder_path, "idx").unwrap();
+        assert_eq!(Index::for_table(folder_path, "ordenes"), Ok(Vec::new()));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn create_with_unknown_column() {
+        let folder = std::env::temp_dir().join("rustic_sql_index_unknown_column");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("ordenes.csv"), "id,id_cliente\n101,2\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let mut index = Index {
+            column: String::from("producto"),
+            ..new_index(&[])
+        };
+        assert_eq!(index.create(folder_path), Err(SqlError::InvalidColumn));
+        assert!(Index::for_table(folder_path, "ordenes").unwrap().is_empty());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
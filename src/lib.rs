@@ -0,0 +1,385 @@
+//! Rustic SQL executes SQL statements over CSV tables stored in a folder.
+//!
+//! The tables are opened with a `Database`, which executes statements and returns the rows of queries:
+//!
+//! ```no_run
+//! use rustic_sql::{Database, SqlError};
+//!
+//! fn main() -> Result<(), SqlError> {
+//!     let mut database = Database::open("tablas")?;
+//!     database.execute("UPDATE clientes SET email = 'ana@email.com' WHERE id = 2")?;
+//!
+//!     for row in database.query("SELECT id, nombre FROM clientes")? {
+//!         let row = row?;
+//!         println!("{:?} {:?}", row.get("id"), row.get("nombre"));
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+mod arithmetic_operator;
+mod builder;
+mod clauses;
+mod cli;
+mod database;
+mod errors;
+mod foreign_key;
+mod index;
+mod line_editor;
+mod lock;
+mod logical_operator;
+mod operator;
+mod output;
+mod parallel;
+mod pipeline;
+mod query_result;
+mod register;
+mod repl;
+mod row;
+mod schema;
+mod script;
+mod sequence;
+#[cfg(feature = "serde")]
+mod serialization;
+mod session;
+mod settings;
+mod snapshot;
+mod sort;
+mod statement;
+mod table;
+mod tokens;
+mod transaction;
+mod utils;
+mod value;
+mod wal;
+
+pub use builder::{col, Col, DeleteQuery, Filter, InsertQuery, Query, SelectQuery, UpdateQuery};
+pub use clauses::orderby_sql::Order;
+pub use database::Database;
+pub use errors::SqlError;
+pub use row::{Column, Row, Rows};
+#[cfg(feature = "serde")]
+pub use serialization::{from_row, to_register};
+pub use statement::PreparedStatement;
+pub use value::Value;
+
+#[doc(hidden)]
+pub use cli::run_cli;
+
+use foreign_key::{cascade_delete, cascade_update, PendingTables};
+use index::{find_candidates, Index};
+use lock::{lock_for_write, TableLock};
+use query_result::QueryResult;
+use register::{Columns, Register};
+use schema::Schema;
+use sequence::Sequence;
+use settings::Settings;
+use snapshot::{file_id, publish};
+use statement::Statement;
+use tokens::tokens_from_query;
+use transaction::Transaction;
+use utils::{
+    is_index, is_sequence, read_table_file, remove_stale_temp_files, sync_folder, write_csv,
+    TempFile,
+};
+use wal::{Wal, WalRecord};
+
+use std::{
+    fs::File,
+    io::{BufReader, Write},
+    sync::Arc,
+};
+
+/// Matches the first token of the query and executes the corresponding SQL clause.
+///
+/// Returns a stream over the rows of the query for SELECT clauses, holding only the selected columns in the order they were selected.
+/// The rows are read from the table as the stream is iterated.
+///
+/// Returns the number of registers inserted, updated or deleted for INSERT, UPDATE and DELETE statements,
+/// not counting the registers changed in other tables by cascades. If the statement has a RETURNING clause, the changed
+/// registers are returned instead, like the rows of a SELECT: as they were inserted or updated, or as they were before being deleted.
+///
+/// Returns an empty result for CREATE and DROP statements because they don't show results in the console.
+///
+/// Returns an error for invalid syntax or unknown clauses.
+///
+/// # Arguments
+///
+/// * `folder_path` - A string slice that holds the path to the folder where the tables are stored.
+/// * `query` - A string slice that holds the SQL query to be executed.
+/// * `settings` - The settings of the session the query is executed in.
+///
+/// # Examples
+///
+/// ```ignore
+/// let settings = Settings::default();
+/// let query = "INSERT INTO clientes (id, nombre, apellido) VALUES (7, 'Sean', 'Brien')";
+/// let result = exec_query("tablas", query, &settings)?;
+///
+/// assert_eq!(result.rows(), 1);
+///
+/// let result = exec_query("tablas", "SELECT nombre FROM clientes WHERE id = 7", &settings)?;
+///
+/// assert_eq!(result.materialize()?.rows(), 1);
+/// ```
+///
+pub(crate) fn exec_query(
+    folder_path: &str,
+    query: &str,
+    settings: &Settings,
+) -> Result<QueryResult, SqlError> {
+    let tokens = tokens_from_query(query);
+
+    match tokens.first().map(|token| token.as_str()) {
+        Some("CREATE") => match tokens.get(1) {
+            Some(object) if is_sequence(object) => {
+                let sequence = Sequence::new_from_tokens(tokens)?;
+                sequence.create(folder_path)?;
+            }
+            Some(object) if is_index(object) => {
+                let mut index = Index::new_from_tokens(tokens)?;
+                let _lock =
+                    TableLock::shared(folder_path, &index.table_name, settings.lock_timeout)?;
+                index.create(folder_path)?;
+            }
+            _ => return Err(SqlError::InvalidSyntax),
+        },
+        Some("DROP") => match (tokens.get(1), tokens.get(2)) {
+            (Some(object), Some(name)) if is_sequence(object) && tokens.len() == 3 => {
+                Sequence::drop(folder_path, name)?;
+            }
+            (Some(object), Some(name)) if is_index(object) && tokens.len() == 3 => {
+                Index::drop(folder_path, name)?;
+            }
+            _ => return Err(SqlError::InvalidSyntax),
+        },
+        _ => return exec_statement(folder_path, Statement::new_from_tokens(tokens)?, settings),
+    }
+
+    Ok(QueryResult::Empty)
+}
+
+/// Executes a SELECT, INSERT, UPDATE or DELETE statement that was already parsed, see `exec_query`.
+///
+/// # Arguments
+///
+/// * `folder_path` - A string slice that holds the path to the folder where the tables are stored.
+/// * `statement` - The parsed statement to be executed.
+/// * `settings` - The settings of the session the statement is executed in.
+///
+pub(crate) fn exec_statement(
+    folder_path: &str,
+    statement: Statement,
+    settings: &Settings,
+) -> Result<QueryResult, SqlError> {
+    let command = statement.command().to_string();
+    let affected;
+    let mut returned = None;
+
+    match statement {
+        Statement::Select(clause) => {
+            let snapshot = clause.open_table(folder_path, settings.lock_timeout)?;
+            let candidates = find_candidates(
+                folder_path,
+                &clause.table_name,
+                clause.where_clause.as_ref(),
+                snapshot.id,
+            )?;
+
+            let stream = match candidates {
+                Some(offsets) => {
+                    clause.stream(snapshot.lines_at(&offsets)?, folder_path, settings)?
+                }
+                None => clause.stream_snapshot(snapshot, folder_path, settings)?,
+            };
+            return Ok(QueryResult::Stream(stream));
+        }
+        Statement::Insert(mut clause) => {
+            let table_name = clause.into_clause.table_name.to_string();
+            let _locks = lock_for_write(folder_path, &table_name, settings.lock_timeout)?;
+            let mut file = clause.open_table(folder_path)?;
+            let schema = Schema::open(folder_path, &table_name)?;
+            let metadata = file.metadata().map_err(|_| SqlError::Error)?;
+            let (offset, table_id) = (metadata.len(), file_id(&metadata));
+
+            let mut wal = Wal::new(folder_path);
+            wal.push(WalRecord::Truncate {
+                file: table_name.to_string() + ".csv",
+                len: offset,
+            });
+            wal.log()?;
+
+            let result = clause
+                .apply_to_table(&mut file, &schema, settings.lock_timeout)
+                .and_then(|inserted| {
+                    file.sync_all().map_err(|_| SqlError::Error)?;
+
+                    let columns = Arc::new(Columns::new(clause.into_clause.columns.to_vec()));
+                    let register = Register::from_csv(&clause.values.join(","), &columns);
+                    let returned = match &clause.returning_clause {
+                        Some(returning) => Some(returning.apply_to_registers(
+                            &clause.into_clause.columns,
+                            vec![register.clone()],
+                        )?),
+                        None => None,
+                    };
+                    for mut index in Index::for_table(folder_path, &table_name)? {
+                        match index.table_id == table_id {
+                            true => index.insert(folder_path, &register, offset)?,
+                            false => index.rebuild(folder_path)?,
+                        }
+                    }
+                    publish(folder_path, &table_name)?;
+                    Ok((inserted, returned))
+                });
+
+            match result {
+                Ok((inserted, inserted_returned)) => {
+                    wal.clear()?;
+                    affected = inserted;
+                    returned = inserted_returned;
+                }
+                Err(e) => {
+                    wal.abort()?;
+                    return Err(e);
+                }
+            }
+        }
+        Statement::Delete(clause) => {
+            let _locks = lock_for_write(folder_path, &clause.table_name, settings.lock_timeout)?;
+            let table = clause.open_table(folder_path)?;
+            let candidates = find_candidates(
+                folder_path,
+                &clause.table_name,
+                clause.where_clause.as_ref(),
+                table_id(&table)?,
+            )?;
+
+            let mut file =
+                TempFile::create(folder_path, &(clause.table_name.to_string() + ".csv"))?;
+            let (columns, deleted) =
+                clause.apply_to_table(table, candidates.as_ref(), &mut file)?;
+            affected = deleted.len();
+            if let Some(returning) = &clause.returning_clause {
+                returned = Some(returning.apply_to_registers(&columns, deleted.to_vec())?);
+            }
+
+            let mut tables = pending_tables(folder_path, &clause.table_name, &mut file)?;
+            cascade_delete(folder_path, &clause.table_name, &deleted, &mut tables)?;
+
+            write_tables(folder_path, (clause.table_name, file), tables)?;
+        }
+        Statement::Update(clause) => {
+            let _locks = lock_for_write(folder_path, &clause.table_name, settings.lock_timeout)?;
+            let table = clause.open_table(folder_path)?;
+            let schema = Schema::open(folder_path, &clause.table_name)?;
+            let candidates = find_candidates(
+                folder_path,
+                &clause.table_name,
+                clause.where_clause.as_ref(),
+                table_id(&table)?,
+            )?;
+
+            let mut file =
+                TempFile::create(folder_path, &(clause.table_name.to_string() + ".csv"))?;
+            let (columns, updated) =
+                clause.apply_to_table(table, &schema, candidates.as_ref(), &mut file)?;
+            affected = updated.len();
+            if let Some(returning) = &clause.returning_clause {
+                let registers = updated.iter().map(|(_, new)| new.clone()).collect();
+                returned = Some(returning.apply_to_registers(&columns, registers)?);
+            }
+
+            let mut tables = pending_tables(folder_path, &clause.table_name, &mut file)?;
+            cascade_update(folder_path, &clause.table_name, &updated, &mut tables)?;
+
+            write_tables(folder_path, (clause.table_name, file), tables)?;
+        }
+    }
+
+    match returned {
+        Some(table) => Ok(QueryResult::Rows(table)),
+        None => Ok(QueryResult::Affected {
+            command,
+            rows: affected,
+        }),
+    }
+}
+
+/// Returns the identifier of an open table file, so only the indexes built from it are used, see `find_candidates`.
+///
+/// An index may have been built from a previous version of the table if the program was interrupted after the table
+/// was replaced but before its indexes were rebuilt.
+fn table_id(table: &BufReader<File>) -> Result<Option<u64>, SqlError> {
+    let metadata = table.get_ref().metadata().map_err(|_| SqlError::Error)?;
+    Ok(file_id(&metadata))
+}
+
+/// Returns the tables that the cascades of a statement start from, once the statement wrote the new content of its table
+/// to the given temporary file.
+///
+/// The cascades read the tables they change from the folder, so a table that references itself is read from the temporary file instead.
+fn pending_tables(
+    folder_path: &str,
+    table_name: &str,
+    file: &mut TempFile,
+) -> Result<PendingTables, SqlError> {
+    let schema = Schema::open(folder_path, table_name)?;
+    if !schema.foreign_keys.iter().any(|fk| fk.table == table_name) {
+        return Ok(PendingTables::new());
+    }
+
+    file.flush().map_err(|_| SqlError::Error)?;
+    let table = read_table_file(&file.path)?;
+    Ok(PendingTables::from([(table_name.to_string(), table)]))
+}
+
+/// Replaces every table modified by a statement, once all of them were computed and checked against their constraints.
+///
+/// The new content of the table of the statement was already written to a temporary file, unless it's pending, and the
+/// pending tables changed by cascades are written to temporary files too. The renames of the temporary files over the tables
+/// are recorded in the write-ahead log first, so they are all made even if the program is interrupted.
+/// The indexes of the written tables are rebuilt, since the offsets of their registers may change.
+fn write_tables(
+    folder_path: &str,
+    written: (String, TempFile),
+    tables: PendingTables,
+) -> Result<(), SqlError> {
+    let mut files = Vec::new();
+    if !tables.contains_key(&written.0) {
+        files.push(written);
+    }
+    for (table_name, table) in &tables {
+        let mut file = TempFile::create(folder_path, &(table_name.to_string() + ".csv"))?;
+        write_csv(table, &mut file)?;
+        files.push((table_name.to_string(), file));
+    }
+
+    let mut wal = Wal::new(folder_path);
+    for (table_name, file) in &mut files {
+        file.sync()?;
+        wal.push(WalRecord::Rename {
+            file: table_name.to_string() + ".csv",
+            from: file.path.to_string(),
+        });
+    }
+    sync_folder(folder_path)?;
+    wal.commit()?;
+
+    for (table_name, _) in &files {
+        for mut index in Index::for_table(folder_path, table_name)? {
+            index.rebuild(folder_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Leaves the folder in a consistent state before executing any statement.
+///
+/// Recovers the changes of an interrupted statement from the write-ahead log, and then removes the temporary files
+/// and shadow folders left by processes that ended abruptly.
+pub(crate) fn open_folder(folder_path: &str) -> Result<(), SqlError> {
+    Wal::recover(folder_path)?;
+    remove_stale_temp_files(folder_path)?;
+    Transaction::remove_stale(folder_path)
+}
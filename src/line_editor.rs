@@ -0,0 +1,345 @@
+use crate::errors::SqlError;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufRead, IsTerminal, Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// Struct that reads the lines typed in the interactive shell, with line editing and a history of the previous entries.
+///
+/// When the standard input is a terminal, the terminal is put in raw mode while a line is read, so the line can be edited
+/// with the arrow keys, `Home`, `End`, `Backspace`, `Delete`, `Ctrl-A` and `Ctrl-E`, and the previous entries can be recalled
+/// with the up and down arrows. Otherwise the lines are read as they come.
+///
+/// # Fields
+///
+/// * `history` - The previous entries, from the oldest to the newest.
+/// * `history_path` - The path to the file where the history is persisted, if any.
+///
+#[derive(Debug)]
+pub struct LineEditor {
+    history: Vec<String>,
+    history_path: Option<PathBuf>,
+}
+
+/// Restores the terminal settings saved when raw mode was enabled, once the line was read.
+struct RawMode {
+    settings: String,
+}
+
+impl RawMode {
+    fn enable() -> Option<Self> {
+        let output = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let settings = String::from_utf8(output.stdout).ok()?.trim().to_string();
+
+        let status = Command::new("stty")
+            .args(["raw", "-echo"])
+            .stdin(Stdio::inherit())
+            .status()
+            .ok()?;
+        status.success().then_some(Self { settings })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = Command::new("stty")
+            .arg(&self.settings)
+            .stdin(Stdio::inherit())
+            .status();
+    }
+}
+
+fn read_byte<R: Read>(input: &mut R) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    match input.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+fn read_char<R: Read>(input: &mut R, first: u8) -> io::Result<Option<char>> {
+    let len = match first {
+        0xF0.. => 4,
+        0xE0.. => 3,
+        0xC0.. => 2,
+        _ => 1,
+    };
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => return Ok(None),
+        }
+    }
+
+    Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|text| text.chars().next()))
+}
+
+/// Escapes an entry of the history to store it in a single line of the history file.
+fn escape_entry(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Restores an entry of the history read from a line of the history file, see `escape_entry`.
+fn unescape_entry(line: &str) -> String {
+    let mut entry = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                entry.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                entry.push('\\');
+                chars.next();
+            }
+            _ => entry.push(c),
+        }
+    }
+    entry
+}
+
+/// Shows the prompt and the line, with the line breaks of a recalled entry shown as spaces, and moves the cursor to its place.
+fn redraw<W: Write>(output: &mut W, prompt: &str, line: &[char], cursor: usize) -> io::Result<()> {
+    let text: String = line
+        .iter()
+        .map(|&c| if c == '\n' { ' ' } else { c })
+        .collect();
+    write!(output, "\r{}{}\x1b[K", prompt, text)?;
+    if cursor < line.len() {
+        write!(output, "\x1b[{}D", line.len() - cursor)?;
+    }
+    output.flush()
+}
+
+impl LineEditor {
+    /// Creates a new `LineEditor`, loading the history stored in the given file, if it exists.
+    pub fn new(history_path: Option<PathBuf>) -> Self {
+        let history = history_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| content.lines().map(unescape_entry).collect())
+            .unwrap_or_default();
+
+        Self {
+            history,
+            history_path,
+        }
+    }
+
+    /// Shows the prompt and reads a line from the standard input.
+    ///
+    /// Returns `None` when the input ends, or when `Ctrl-D` is pressed on an empty line.
+    pub fn read_line(&mut self, prompt: &str) -> Result<Option<String>, SqlError> {
+        let mut stdout = io::stdout();
+
+        if io::stdin().is_terminal() {
+            if let Some(_raw_mode) = RawMode::enable() {
+                return self
+                    .edit(prompt, &mut io::stdin().lock(), &mut stdout)
+                    .map_err(|_| SqlError::Error);
+            }
+        }
+
+        print!("{}", prompt);
+        stdout.flush().map_err(|_| SqlError::Error)?;
+
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(line.trim_end_matches(['\n', '\r']).to_string())),
+            Err(_) => Err(SqlError::Error),
+        }
+    }
+
+    /// Adds an entry to the history and appends it to the history file, with its line breaks escaped.
+    ///
+    /// Empty entries and entries equal to the previous one are not added. Keeping the history is best-effort:
+    /// if the history file can't be written, a warning is shown once and the history is only kept in memory.
+    pub fn add_history(&mut self, entry: &str) {
+        if entry.trim().is_empty() || self.history.last().map(String::as_str) == Some(entry) {
+            return;
+        }
+        self.history.push(entry.to_string());
+
+        if let Some(path) = &self.history_path {
+            let result = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{}", escape_entry(entry)));
+            if result.is_err() {
+                eprintln!("Warning: the history can't be saved to {}", path.display());
+                self.history_path = None;
+            }
+        }
+    }
+
+    fn edit<R: Read, W: Write>(
+        &self,
+        prompt: &str,
+        input: &mut R,
+        output: &mut W,
+    ) -> io::Result<Option<String>> {
+        let mut line: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        let mut position = self.history.len();
+        let mut draft = Vec::new();
+
+        redraw(output, prompt, &line, cursor)?;
+
+        while let Some(byte) = read_byte(input)? {
+            match byte {
+                b'\r' | b'\n' => {
+                    write!(output, "\r\n")?;
+                    return Ok(Some(line.iter().collect()));
+                }
+                0x04 if line.is_empty() => {
+                    write!(output, "\r\n")?;
+                    return Ok(None);
+                }
+                0x04 if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                0x03 => {
+                    line.clear();
+                    cursor = 0;
+                }
+                0x7F | 0x08 if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                }
+                0x01 => cursor = 0,
+                0x05 => cursor = line.len(),
+                0x1B => {
+                    if read_byte(input)? != Some(b'[') {
+                        continue;
+                    }
+                    match read_byte(input)? {
+                        Some(b'A') if position > 0 => {
+                            if position == self.history.len() {
+                                draft = line.clone();
+                            }
+                            position -= 1;
+                            line = self.history[position].chars().collect();
+                            cursor = line.len();
+                        }
+                        Some(b'B') if position < self.history.len() => {
+                            position += 1;
+                            line = match self.history.get(position) {
+                                Some(entry) => entry.chars().collect(),
+                                None => draft.clone(),
+                            };
+                            cursor = line.len();
+                        }
+                        Some(b'C') if cursor < line.len() => cursor += 1,
+                        Some(b'D') if cursor > 0 => cursor -= 1,
+                        Some(b'H') => cursor = 0,
+                        Some(b'F') => cursor = line.len(),
+                        Some(b'3') if read_byte(input)? == Some(b'~') && cursor < line.len() => {
+                            line.remove(cursor);
+                        }
+                        _ => {}
+                    }
+                }
+                0x00..=0x1F | 0x7F => {}
+                first => {
+                    if let Some(c) = read_char(input, first)? {
+                        line.insert(cursor, c);
+                        cursor += 1;
+                    }
+                }
+            }
+            redraw(output, prompt, &line, cursor)?;
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineEditor;
+    use std::fs;
+
+    fn edit(editor: &LineEditor, input: &[u8]) -> Option<String> {
+        let mut output = Vec::new();
+        editor.edit("> ", &mut &input[..], &mut output).unwrap()
+    }
+
+    #[test]
+    fn edit_line() {
+        let editor = LineEditor::new(None);
+
+        assert_eq!(
+            edit(
+                &editor,
+                b"SELEC * FROM\x1b[D\x1b[D\x1b[D\x1b[D\x1b[D\x1b[D\x1b[DT\r"
+            ),
+            Some(String::from("SELECT * FROM"))
+        );
+        assert_eq!(
+            edit(&editor, b"ab\x7fc\x01d\x05e\x1b[H\x1b[3~\n"),
+            Some(String::from("ace"))
+        );
+        assert_eq!(
+            edit(&editor, "añ\x03ño\r".as_bytes()),
+            Some(String::from("ño"))
+        );
+        assert_eq!(edit(&editor, b"\x04"), None);
+        assert_eq!(edit(&editor, b"abc"), None);
+    }
+
+    #[test]
+    fn history() {
+        let path = std::env::temp_dir().join("rustic_sql_line_editor_history");
+        let _ = fs::remove_file(&path);
+
+        let mut editor = LineEditor::new(Some(path.clone()));
+        editor.add_history("SELECT * FROM clientes;");
+        editor.add_history("SELECT * -- todos\nFROM ordenes\\;");
+        editor.add_history("SELECT * -- todos\nFROM ordenes\\;");
+        editor.add_history("   ");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "SELECT * FROM clientes;\nSELECT * -- todos\\nFROM ordenes\\\\;\n"
+        );
+
+        let editor = LineEditor::new(Some(path.clone()));
+        assert_eq!(
+            edit(&editor, b"\x1b[A\r"),
+            Some(String::from("SELECT * -- todos\nFROM ordenes\\;"))
+        );
+        assert_eq!(
+            edit(&editor, b"\x1b[A\x1b[A\x1b[A\r"),
+            Some(String::from("SELECT * FROM clientes;"))
+        );
+        assert_eq!(
+            edit(&editor, b"DELETE\x1b[A\x1b[B\r"),
+            Some(String::from("DELETE"))
+        );
+
+        fs::remove_file(&path).unwrap();
+
+        let path = std::env::temp_dir().join("rustic_sql_missing_folder/history");
+        let mut editor = LineEditor::new(Some(path));
+        editor.add_history("SELECT * FROM clientes;");
+        assert_eq!(editor.history_path, None);
+        assert_eq!(
+            edit(&editor, b"\x1b[A\r"),
+            Some(String::from("SELECT * FROM clientes;"))
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{
+    errors::SqlError, foreign_key::dependent_tables, schema::Schema, utils::find_file_in_folder,
+    wal::Wal,
+};
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions, TryLockError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Struct that represents an advisory lock over a table, held on a hidden `.<table>.lock` file in the folder.
+///
+/// Many processes can hold a shared lock over the same table, while an exclusive lock can only be held by one process
+/// and excludes the shared ones. The lock is released when the struct is dropped.
+///
+/// # Fields
+///
+/// * `file` - The open lock file.
+///
+#[derive(Debug)]
+pub struct TableLock {
+    file: File,
+}
+
+impl TableLock {
+    /// Takes a shared lock over the table, used by statements that only read it.
+    ///
+    /// Waits until no other process holds an exclusive lock over the table, up to the given timeout.
+    pub fn shared(
+        folder_path: &str,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<Self, SqlError> {
+        Self::acquire(folder_path, table_name, false, timeout)
+    }
+
+    /// Takes an exclusive lock over the table, used by statements that change it.
+    ///
+    /// Waits until no other process holds a lock over the table, up to the given timeout.
+    pub fn exclusive(
+        folder_path: &str,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<Self, SqlError> {
+        Self::acquire(folder_path, table_name, true, timeout)
+    }
+
+    /// Takes an exclusive lock over a file of the folder that is not a table, like a sequence, held on a hidden `.<file>.lock` file.
+    ///
+    /// Waits until no other process holds the lock, up to the given timeout.
+    pub fn exclusive_file(
+        folder_path: &str,
+        file_name: &str,
+        timeout: Duration,
+    ) -> Result<Self, SqlError> {
+        Self::lock_file(folder_path, file_name, true, timeout)
+    }
+
+    /// Takes a shared lock over the table only if no other process holds an exclusive lock over it, without waiting.
+    ///
+    /// Returns `None` if the table is locked.
+    pub fn try_shared(folder_path: &str, table_name: &str) -> Result<Option<Self>, SqlError> {
+        match Self::acquire(folder_path, table_name, false, Duration::ZERO) {
+            Ok(lock) => Ok(Some(lock)),
+            Err(SqlError::Locked { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn acquire(
+        folder_path: &str,
+        table_name: &str,
+        exclusive: bool,
+        timeout: Duration,
+    ) -> Result<Self, SqlError> {
+        if !find_file_in_folder(folder_path, &(table_name.to_string() + ".csv")) {
+            return Err(SqlError::InvalidTable);
+        }
+        Self::lock_file(folder_path, table_name, exclusive, timeout)
+    }
+
+    fn lock_file(
+        folder_path: &str,
+        name: &str,
+        exclusive: bool,
+        timeout: Duration,
+    ) -> Result<Self, SqlError> {
+        let path = format!("{}/.{}.lock", folder_path, name);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .map_err(|_| SqlError::Error)?;
+
+        let start = Instant::now();
+
+        loop {
+            let result = if exclusive {
+                file.try_lock()
+            } else {
+                file.try_lock_shared()
+            };
+
+            match result {
+                Ok(()) => return Ok(Self { file }),
+                Err(TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(SqlError::Locked {
+                        table: name.to_string(),
+                    })
+                }
+                Err(TryLockError::Error(_)) => return Err(SqlError::Error),
+            }
+        }
+    }
+}
+
+impl Drop for TableLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// Returns the tables used by a statement that changes the given table, sorted by name, and whether each one may be changed by it.
+///
+/// The table and the tables that reference it may be changed by cascades, while the tables they reference are only read
+/// to check their foreign keys.
+pub fn tables_for_write(
+    folder_path: &str,
+    table_name: &str,
+) -> Result<BTreeMap<String, bool>, SqlError> {
+    let mut tables = BTreeMap::new();
+
+    for table in dependent_tables(folder_path, table_name)? {
+        for foreign_key in Schema::open(folder_path, &table)?.foreign_keys {
+            tables.entry(foreign_key.table).or_insert(false);
+        }
+        tables.insert(table, true);
+    }
+
+    Ok(tables)
+}
+
+/// Takes the locks needed by a statement that changes the given table.
+///
+/// The table and the tables that reference it, which may be changed by cascades, are locked exclusively.
+/// The tables they reference, which are read to check their foreign keys, are locked in shared mode.
+/// The locks are taken in the order of the table names, so two statements never wait for each other.
+///
+/// Once the locks are held, the changes left by statements of processes that ended abruptly are recovered.
+///
+pub fn lock_for_write(
+    folder_path: &str,
+    table_name: &str,
+    timeout: Duration,
+) -> Result<Vec<TableLock>, SqlError> {
+    let mut locks = Vec::new();
+    for (table, exclusive) in tables_for_write(folder_path, table_name)? {
+        if exclusive {
+            locks.push(TableLock::exclusive(folder_path, &table, timeout)?);
+        } else {
+            locks.push(TableLock::shared(folder_path, &table, timeout)?);
+        }
+    }
+
+    Wal::recover(folder_path)?;
+
+    Ok(locks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lock_for_write, TableLock};
+    use crate::errors::SqlError;
+    use std::{fs, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn shared_and_exclusive_locks() {
+        let folder = std::env::temp_dir().join("rustic_sql_lock_modes");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n").unwrap();
+        fs::write(folder.join("ordenes.csv"), "id\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        assert_eq!(
+            TableLock::shared(folder_path, "productos", TIMEOUT).unwrap_err(),
+            SqlError::InvalidTable
+        );
+        let first = TableLock::shared(folder_path, "clientes", TIMEOUT).unwrap();
+        let second = TableLock::shared(folder_path, "clientes", TIMEOUT).unwrap();
+        assert!(TableLock::try_shared(folder_path, "clientes")
+            .unwrap()
+            .is_some());
+        assert!(TableLock::exclusive(folder_path, "ordenes", TIMEOUT).is_ok());
+
+        assert_eq!(
+            TableLock::exclusive(folder_path, "clientes", TIMEOUT).unwrap_err(),
+            SqlError::Locked {
+                table: String::from("clientes")
+            }
+        );
+
+        drop(first);
+        drop(second);
+        assert!(TableLock::exclusive(folder_path, "clientes", TIMEOUT).is_ok());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn lock_for_write_includes_referencing_tables() {
+        let folder = std::env::temp_dir().join("rustic_sql_lock_for_write");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n").unwrap();
+        fs::write(folder.join("ordenes.csv"), "id,id_cliente\n").unwrap();
+        fs::write(folder.join("clientes.schema"), "id PRIMARY KEY\n").unwrap();
+        fs::write(
+            folder.join("ordenes.schema"),
+            "id PRIMARY KEY\nid_cliente REFERENCES clientes (id)\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let locks = lock_for_write(folder_path, "clientes", TIMEOUT).unwrap();
+        assert_eq!(locks.len(), 2);
+        assert!(TableLock::try_shared(folder_path, "clientes")
+            .unwrap()
+            .is_none());
+        assert!(TableLock::shared(folder_path, "ordenes", TIMEOUT).is_err());
+        drop(locks);
+
+        let locks = lock_for_write(folder_path, "ordenes", TIMEOUT).unwrap();
+        assert_eq!(locks.len(), 2);
+        assert!(TableLock::shared(folder_path, "clientes", TIMEOUT).is_ok());
+        assert!(TableLock::exclusive(folder_path, "clientes", TIMEOUT).is_err());
+        drop(locks);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn locked_error() {
+        assert_eq!(
+            SqlError::Locked {
+                table: String::from("clientes")
+            }
+            .to_string(),
+            "[Locked]: [Table clientes is locked]"
+        );
+    }
+}
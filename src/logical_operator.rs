@@ -3,7 +3,7 @@
 /// - `Or`: Logical OR operator
 /// - `Not`: Logical NOT operator
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LogicalOperator {
     And,
     Or,
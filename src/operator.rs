@@ -3,7 +3,7 @@
 /// - `Greater`: Greater than operator
 /// - `Lesser`: Lesser than operator
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     Equal,
     Greater,
@@ -0,0 +1,729 @@
+use crate::{
+    errors::SqlError, pipeline::RegisterStream, query_result::QueryResult, register::Register,
+    table::Table, value::Value,
+};
+use std::{
+    borrow::Cow,
+    io::{self, BufWriter, Write},
+};
+
+/// Trait for the formats the result of a query can be shown in.
+pub trait Formatter {
+    /// Formats the result of a query, returning the lines to show.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The result of the query, with the columns in the order they are shown.
+    /// * `header` - Whether the names of the columns are shown, for the formats where they are optional.
+    ///
+    fn format(&self, table: &Table, header: bool) -> Vec<String>;
+
+    /// Returns the formatter that shows each register as soon as it is read, or `None` if the format needs the whole
+    /// result, like to align its columns.
+    fn streaming(&self) -> Option<&dyn StreamFormatter> {
+        None
+    }
+}
+
+/// Trait for the formats that show each register of the result of a query as soon as it is read.
+pub trait StreamFormatter {
+    /// Returns the lines shown before the registers.
+    fn head(&self, columns: &[String], header: bool) -> Vec<String>;
+
+    /// Returns the lines of a register.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The columns of the result, in the order they are shown.
+    /// * `row` - The values of the register, in the order of the columns.
+    /// * `idx` - The position of the register in the result, from 0.
+    /// * `last` - Whether the register is the last one of the result.
+    ///
+    fn row(&self, columns: &[String], row: &[Cow<str>], idx: usize, last: bool) -> Vec<String>;
+
+    /// Returns the lines shown after the registers, given how many registers were shown.
+    fn tail(&self, rows: usize) -> Vec<String>;
+}
+/// Enum for the ways the result of a query is shown.
+///
+/// - `Csv`: the lines of the result as they are stored in the tables.
+/// - `Column`: the values aligned in columns, with the names of the columns underlined.
+/// - `Table`: the values aligned in an ASCII box.
+/// - `Json`: a JSON array with an object per register.
+/// - `JsonLines`: a JSON object per register, one per line.
+/// - `Markdown`: a Markdown table.
+/// - `Tsv`: the values separated by tabs.
+/// - `Html`: an HTML table.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mode {
+    Csv,
+    Column,
+    Table,
+    Json,
+    JsonLines,
+    Markdown,
+    Tsv,
+    Html,
+}
+
+/// The names of the modes, as accepted by `Mode::from_name`.
+pub const MODE_NAMES: &str = "csv, column, table, json, jsonl, markdown, tsv, html";
+
+impl Mode {
+    /// Returns the mode with the given name, or `None` if there is no mode with that name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(Mode::Csv),
+            "column" => Some(Mode::Column),
+            "table" => Some(Mode::Table),
+            "json" => Some(Mode::Json),
+            "jsonl" => Some(Mode::JsonLines),
+            "markdown" => Some(Mode::Markdown),
+            "tsv" => Some(Mode::Tsv),
+            "html" => Some(Mode::Html),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the mode, as accepted by `from_name`.
+    pub fn name(&self) -> &str {
+        match self {
+            Mode::Csv => "csv",
+            Mode::Column => "column",
+            Mode::Table => "table",
+            Mode::Json => "json",
+            Mode::JsonLines => "jsonl",
+            Mode::Markdown => "markdown",
+            Mode::Tsv => "tsv",
+            Mode::Html => "html",
+        }
+    }
+
+    /// Returns the formatter of the mode.
+    pub fn formatter(&self) -> &dyn Formatter {
+        match self {
+            Mode::Csv => &Csv,
+            Mode::Column => &Column,
+            Mode::Table => &BoxTable,
+            Mode::Json => &Json,
+            Mode::JsonLines => &JsonLines,
+            Mode::Markdown => &Markdown,
+            Mode::Tsv => &Tsv,
+            Mode::Html => &Html,
+        }
+    }
+}
+
+/// Returns the values of a register, in the order of the columns.
+fn values<'a>(columns: &[String], register: &'a Register) -> Vec<Cow<'a, str>> {
+    columns
+        .iter()
+        .map(|column| {
+            register
+                .get(column)
+                .map_or(Cow::Borrowed(""), Value::to_csv)
+        })
+        .collect()
+}
+
+/// Returns the values of every register of the table, in the order of its columns.
+fn rows(table: &Table) -> Vec<Vec<Cow<'_, str>>> {
+    table
+        .registers
+        .iter()
+        .map(|register| values(&table.columns, register))
+        .collect()
+}
+
+/// Formats a whole table with the lines a stream formatter shows for each of its registers.
+fn format_stream(formatter: &dyn StreamFormatter, table: &Table, header: bool) -> Vec<String> {
+    let rows = rows(table);
+    let mut lines = formatter.head(&table.columns, header);
+    for (idx, row) in rows.iter().enumerate() {
+        lines.extend(formatter.row(&table.columns, row, idx, idx + 1 == rows.len()));
+    }
+    lines.extend(formatter.tail(rows.len()));
+    lines
+}
+
+fn write_lines<W: Write>(writer: &mut W, lines: &[String]) -> Result<(), SqlError> {
+    for line in lines {
+        writeln!(writer, "{}", line).map_err(|_| SqlError::Error)?;
+    }
+    Ok(())
+}
+
+/// Returns the width of each column, wide enough for its values and, if shown, its name.
+fn widths(table: &Table, rows: &[Vec<Cow<str>>], header: bool) -> Vec<usize> {
+    let mut widths: Vec<usize> = table
+        .columns
+        .iter()
+        .map(|column| if header { column.chars().count() } else { 0 })
+        .collect();
+
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    widths
+}
+
+fn pad<S: AsRef<str>>(values: &[S], widths: &[usize], separator: &str) -> String {
+    values
+        .iter()
+        .zip(widths)
+        .map(|(value, width)| format!("{:width$}", value.as_ref(), width = width))
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Empty values are `null`, integers are numbers and any other value is a string.
+fn json_value(value: &str) -> String {
+    match Value::from_csv(value) {
+        Value::Null => String::from("null"),
+        Value::Integer(integer) => integer.to_string(),
+        Value::Text(text) => json_string(&text),
+    }
+}
+
+fn json_object(columns: &[String], row: &[Cow<str>]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(row)
+        .map(|(column, value)| format!("{}:{}", json_string(column), json_value(value)))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The lines of the result as they are stored in the tables.
+struct Csv;
+
+impl Formatter for Csv {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        format_stream(self, table, header)
+    }
+
+    fn streaming(&self) -> Option<&dyn StreamFormatter> {
+        Some(self)
+    }
+}
+
+impl StreamFormatter for Csv {
+    fn head(&self, columns: &[String], header: bool) -> Vec<String> {
+        match header {
+            true => vec![columns.join(",")],
+            false => Vec::new(),
+        }
+    }
+
+    fn row(&self, _columns: &[String], row: &[Cow<str>], _idx: usize, _last: bool) -> Vec<String> {
+        vec![row.join(",")]
+    }
+
+    fn tail(&self, _rows: usize) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The values aligned in columns, with the names of the columns underlined.
+struct Column;
+
+impl Formatter for Column {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        let rows = rows(table);
+        let widths = widths(table, &rows, header);
+        let mut lines = Vec::new();
+
+        if header {
+            let columns: Vec<&str> = table.columns.iter().map(|c| c.as_str()).collect();
+            let dashes: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+            let dashes: Vec<&str> = dashes.iter().map(|d| d.as_str()).collect();
+            lines.push(pad(&columns, &widths, "  ").trim_end().to_string());
+            lines.push(pad(&dashes, &widths, "  ").trim_end().to_string());
+        }
+        for row in &rows {
+            lines.push(pad(row, &widths, "  ").trim_end().to_string());
+        }
+        lines
+    }
+}
+
+/// The values aligned in an ASCII box.
+struct BoxTable;
+
+impl Formatter for BoxTable {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        let rows = rows(table);
+        let widths = widths(table, &rows, header);
+        let border: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+        let border = format!("+{}+", border.join("+"));
+        let mut lines = vec![border.to_string()];
+
+        if header {
+            let columns: Vec<&str> = table.columns.iter().map(|c| c.as_str()).collect();
+            lines.push(format!("| {} |", pad(&columns, &widths, " | ")));
+            lines.push(border.to_string());
+        }
+        for row in &rows {
+            lines.push(format!("| {} |", pad(row, &widths, " | ")));
+        }
+        if !rows.is_empty() {
+            lines.push(border);
+        }
+        lines
+    }
+}
+
+/// A JSON array with an object per register, where the keys are the names of the columns.
+struct Json;
+
+impl Formatter for Json {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        format_stream(self, table, header)
+    }
+
+    fn streaming(&self) -> Option<&dyn StreamFormatter> {
+        Some(self)
+    }
+}
+
+impl StreamFormatter for Json {
+    fn head(&self, _columns: &[String], _header: bool) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn row(&self, columns: &[String], row: &[Cow<str>], idx: usize, last: bool) -> Vec<String> {
+        let separator = if last { "" } else { "," };
+        let object = format!("  {}{}", json_object(columns, row), separator);
+        match idx {
+            0 => vec![String::from("["), object],
+            _ => vec![object],
+        }
+    }
+
+    fn tail(&self, rows: usize) -> Vec<String> {
+        match rows {
+            0 => vec![String::from("[]")],
+            _ => vec![String::from("]")],
+        }
+    }
+}
+
+/// A JSON object per register, one per line.
+struct JsonLines;
+
+impl Formatter for JsonLines {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        format_stream(self, table, header)
+    }
+
+    fn streaming(&self) -> Option<&dyn StreamFormatter> {
+        Some(self)
+    }
+}
+
+impl StreamFormatter for JsonLines {
+    fn head(&self, _columns: &[String], _header: bool) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn row(&self, columns: &[String], row: &[Cow<str>], _idx: usize, _last: bool) -> Vec<String> {
+        vec![json_object(columns, row)]
+    }
+
+    fn tail(&self, _rows: usize) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// A Markdown table, which always shows the names of the columns.
+struct Markdown;
+
+impl Formatter for Markdown {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        format_stream(self, table, header)
+    }
+
+    fn streaming(&self) -> Option<&dyn StreamFormatter> {
+        Some(self)
+    }
+}
+
+fn markdown_escape(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+impl StreamFormatter for Markdown {
+    fn head(&self, columns: &[String], _header: bool) -> Vec<String> {
+        let names: Vec<String> = columns.iter().map(|c| markdown_escape(c)).collect();
+        let separator: Vec<&str> = columns.iter().map(|_| "---").collect();
+
+        vec![
+            format!("| {} |", names.join(" | ")),
+            format!("| {} |", separator.join(" | ")),
+        ]
+    }
+
+    fn row(&self, _columns: &[String], row: &[Cow<str>], _idx: usize, _last: bool) -> Vec<String> {
+        let values: Vec<String> = row.iter().map(|value| markdown_escape(value)).collect();
+        vec![format!("| {} |", values.join(" | "))]
+    }
+
+    fn tail(&self, _rows: usize) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The values separated by tabs.
+struct Tsv;
+
+impl Formatter for Tsv {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        format_stream(self, table, header)
+    }
+
+    fn streaming(&self) -> Option<&dyn StreamFormatter> {
+        Some(self)
+    }
+}
+
+impl StreamFormatter for Tsv {
+    fn head(&self, columns: &[String], header: bool) -> Vec<String> {
+        match header {
+            true => vec![columns.join("\t")],
+            false => Vec::new(),
+        }
+    }
+
+    fn row(&self, _columns: &[String], row: &[Cow<str>], _idx: usize, _last: bool) -> Vec<String> {
+        vec![row.join("\t")]
+    }
+
+    fn tail(&self, _rows: usize) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// An HTML table, with the names of the columns in its head.
+struct Html;
+
+impl Formatter for Html {
+    fn format(&self, table: &Table, header: bool) -> Vec<String> {
+        format_stream(self, table, header)
+    }
+
+    fn streaming(&self) -> Option<&dyn StreamFormatter> {
+        Some(self)
+    }
+}
+
+impl StreamFormatter for Html {
+    fn head(&self, columns: &[String], header: bool) -> Vec<String> {
+        let mut lines = vec![String::from("<table>")];
+
+        if header {
+            let columns: Vec<String> = columns
+                .iter()
+                .map(|column| format!("<th>{}</th>", html_escape(column)))
+                .collect();
+            lines.push(format!("  <tr>{}</tr>", columns.concat()));
+        }
+        lines
+    }
+
+    fn row(&self, _columns: &[String], row: &[Cow<str>], _idx: usize, _last: bool) -> Vec<String> {
+        let values: Vec<String> = row
+            .iter()
+            .map(|value| format!("<td>{}</td>", html_escape(value)))
+            .collect();
+        vec![format!("  <tr>{}</tr>", values.concat())]
+    }
+
+    fn tail(&self, _rows: usize) -> Vec<String> {
+        vec![String::from("</table>")]
+    }
+}
+
+/// Struct that represents how the results of the queries are shown.
+///
+/// # Fields
+///
+/// * `mode` - The format of the results.
+/// * `header` - Whether the names of the columns are shown.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Output {
+    pub mode: Mode,
+    pub header: bool,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Csv,
+            header: true,
+        }
+    }
+}
+
+impl Output {
+    /// Formats the result of a query with the formatter of the mode.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let output = Output { mode: Mode::Column, header: true };
+    /// let lines = output.format(&table);
+    ///
+    /// assert_eq!(lines, vec!["id  nombre", "--  ------", "1   Juan"]);
+    /// ```
+    ///
+    pub fn format(&self, table: &Table) -> Vec<String> {
+        self.mode.formatter().format(table, self.header)
+    }
+
+    /// Shows the result of a statement in the standard output: the rows returned by a query,
+    /// or a status line like `UPDATE 3` for the statements that change registers.
+    ///
+    /// Returns the number of registers returned or affected, see `write`.
+    pub fn print(&self, result: QueryResult) -> Result<usize, SqlError> {
+        let mut stdout = BufWriter::new(io::stdout().lock());
+        let written = self.write(result, &mut stdout);
+        stdout.flush().map_err(|_| SqlError::Error)?;
+        written
+    }
+
+    /// Writes the result of a statement, see `print`, and returns the number of registers returned or affected.
+    ///
+    /// The rows of a stream are written as they are read from the table, unless the format needs the whole result,
+    /// like to align its columns. If reading a row fails, the rows before it are already written and the error is returned.
+    /// The first row is read before anything is written, so a query that fails on it writes nothing.
+    pub fn write<W: Write>(&self, result: QueryResult, writer: &mut W) -> Result<usize, SqlError> {
+        let rows = result.rows();
+        match result {
+            QueryResult::Stream(stream) => {
+                return match self.mode.formatter().streaming() {
+                    Some(formatter) => self.write_stream(formatter, stream, writer),
+                    None => self.write(QueryResult::Rows(stream.into_table()?), writer),
+                };
+            }
+            QueryResult::Rows(table) => write_lines(writer, &self.format(&table))?,
+            result @ QueryResult::Affected { .. } => write_lines(writer, &[result.to_string()])?,
+            QueryResult::Empty => {}
+        }
+        Ok(rows)
+    }
+
+    fn write_stream<W: Write>(
+        &self,
+        formatter: &dyn StreamFormatter,
+        stream: RegisterStream,
+        writer: &mut W,
+    ) -> Result<usize, SqlError> {
+        let columns = stream.columns.to_vec();
+        let mut registers = stream.peekable();
+        let mut written = 0;
+
+        if matches!(registers.peek(), Some(Err(_))) {
+            registers.next().transpose()?;
+        }
+        write_lines(writer, &formatter.head(&columns, self.header))?;
+        while let Some(register) = registers.next() {
+            let register = register?;
+            let last = registers.peek().is_none();
+            let lines = formatter.row(&columns, &values(&columns, &register), written, last);
+            write_lines(writer, &lines)?;
+            written += 1;
+        }
+        write_lines(writer, &formatter.tail(written))?;
+
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mode, Output};
+    use crate::{
+        errors::SqlError,
+        pipeline::RegisterStream,
+        query_result::QueryResult,
+        register::{Columns, Register},
+        table::Table,
+    };
+    use std::sync::Arc;
+
+    fn table() -> Table {
+        let columns = vec![String::from("id"), String::from("nombre")];
+        let shared = Arc::new(Columns::new(columns.to_vec()));
+        Table {
+            registers: vec![
+                Register::from_csv("1,Juan \"J\"", &shared),
+                Register::from_csv("10,", &shared),
+            ],
+            columns,
+        }
+    }
+
+    fn format(mode: Mode, header: bool) -> Vec<String> {
+        Output { mode, header }.format(&table())
+    }
+
+    #[test]
+    fn csv_and_tsv() {
+        assert_eq!(
+            format(Mode::Csv, true),
+            vec!["id,nombre", "1,Juan \"J\"", "10,"]
+        );
+        assert_eq!(format(Mode::Csv, false), vec!["1,Juan \"J\"", "10,"]);
+        assert_eq!(
+            format(Mode::Tsv, true),
+            vec!["id\tnombre", "1\tJuan \"J\"", "10\t"]
+        );
+    }
+
+    #[test]
+    fn aligned() {
+        assert_eq!(
+            format(Mode::Column, true),
+            vec!["id  nombre", "--  --------", "1   Juan \"J\"", "10"]
+        );
+        assert_eq!(format(Mode::Column, false), vec!["1   Juan \"J\"", "10"]);
+        assert_eq!(
+            format(Mode::Table, true),
+            vec![
+                "+----+----------+",
+                "| id | nombre   |",
+                "+----+----------+",
+                "| 1  | Juan \"J\" |",
+                "| 10 |          |",
+                "+----+----------+",
+            ]
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            format(Mode::Json, true),
+            vec![
+                "[",
+                "  {\"id\":1,\"nombre\":\"Juan \\\"J\\\"\"},",
+                "  {\"id\":10,\"nombre\":null}",
+                "]",
+            ]
+        );
+        assert_eq!(
+            format(Mode::JsonLines, false),
+            vec![
+                "{\"id\":1,\"nombre\":\"Juan \\\"J\\\"\"}",
+                "{\"id\":10,\"nombre\":null}",
+            ]
+        );
+        assert_eq!(
+            Output {
+                mode: Mode::Json,
+                header: true
+            }
+            .format(&Table::new()),
+            vec!["[]"]
+        );
+    }
+
+    #[test]
+    fn markdown_and_html() {
+        assert_eq!(
+            format(Mode::Markdown, false),
+            vec![
+                "| id | nombre |",
+                "| --- | --- |",
+                "| 1 | Juan \"J\" |",
+                "| 10 |  |",
+            ]
+        );
+        assert_eq!(
+            format(Mode::Html, true),
+            vec![
+                "<table>",
+                "  <tr><th>id</th><th>nombre</th></tr>",
+                "  <tr><td>1</td><td>Juan &quot;J&quot;</td></tr>",
+                "  <tr><td>10</td><td></td></tr>",
+                "</table>",
+            ]
+        );
+    }
+
+    #[test]
+    fn write_streams() {
+        let columns = [String::from("id"), String::from("nombre")];
+        let stream = |registers: Vec<Result<Register, SqlError>>| {
+            QueryResult::Stream(RegisterStream::new(columns.to_vec(), registers.into_iter()))
+        };
+        let write = |mode: Mode, result: QueryResult| {
+            let mut written = Vec::new();
+            let rows = Output { mode, header: true }.write(result, &mut written);
+            (rows, String::from_utf8(written).unwrap())
+        };
+
+        for mode in [Mode::Json, Mode::Table, Mode::Html] {
+            let registers = table().registers.into_iter().map(Ok).collect();
+            assert_eq!(
+                write(mode, stream(registers)),
+                (Ok(2), format(mode, true).join("\n") + "\n")
+            );
+        }
+
+        let registers = vec![
+            Ok(table().registers.remove(0)),
+            Err(SqlError::InvalidSyntax),
+        ];
+        assert_eq!(
+            write(Mode::Csv, stream(registers)),
+            (
+                Err(SqlError::InvalidSyntax),
+                String::from("id,nombre\n1,Juan \"J\"\n")
+            )
+        );
+        assert_eq!(
+            write(Mode::Json, stream(vec![Err(SqlError::InvalidSyntax)])),
+            (Err(SqlError::InvalidSyntax), String::new())
+        );
+        assert_eq!(
+            write(
+                Mode::Csv,
+                QueryResult::Affected {
+                    command: String::from("DELETE"),
+                    rows: 3
+                }
+            ),
+            (Ok(3), String::from("DELETE 3\n"))
+        );
+    }
+}
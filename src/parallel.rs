@@ -0,0 +1,241 @@
+use crate::{
+    clauses::where_sql::Where,
+    errors::SqlError,
+    register::{Columns, Register},
+    snapshot::Chunk,
+};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    vec,
+};
+
+/// The minimum size of each chunk a table is split in, so small tables are read by a single thread.
+const MIN_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// How many registers a thread sends at once.
+const BATCH_SIZE: usize = 1024;
+
+/// How many batches of registers a thread reads ahead of the ones already returned.
+const BATCHES_AHEAD: usize = 4;
+
+/// Returns how many chunks a table of the given size is split in, one per thread,
+/// as long as each chunk holds at least a megabyte.
+pub fn chunk_count(len: u64, threads: usize) -> usize {
+    let chunks = (len / MIN_CHUNK_SIZE).min(threads as u64) as usize;
+    chunks.max(1)
+}
+
+type Batch = Result<Vec<Register>, SqlError>;
+
+/// Reads the registers of a chunk that meet the condition, sending them in batches until the chunk ends,
+/// an error is found, the receiver is dropped or the `cancel` flag is set.
+fn filter_chunk(
+    chunk: Chunk,
+    columns: Arc<Columns>,
+    where_clause: Where,
+    sender: SyncSender<Batch>,
+    cancel: Arc<AtomicBool>,
+) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    for line in BufReader::new(chunk).lines() {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let result = line.map_err(|_| SqlError::Error).and_then(|line| {
+            let register = Register::from_csv(&line, &columns);
+            Ok(where_clause.execute(&register)?.then_some(register))
+        });
+
+        match result {
+            Ok(Some(register)) => {
+                batch.push(register);
+                if batch.len() == BATCH_SIZE && sender.send(Ok(mem::take(&mut batch))).is_err() {
+                    return;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let _ = sender.send(Ok(batch));
+                let _ = sender.send(Err(e));
+                return;
+            }
+        }
+    }
+
+    if !batch.is_empty() {
+        let _ = sender.send(Ok(batch));
+    }
+}
+
+/// Iterator over the registers found by the threads, returned in the order of the chunks they read.
+///
+/// Once it ends with an error or is dropped, the threads still reading are cancelled and joined.
+struct Merge {
+    batch: vec::IntoIter<Register>,
+    threads: VecDeque<(Receiver<Batch>, JoinHandle<()>)>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Merge {
+    /// Cancels the threads still reading and waits for them to end. Dropping each receiver wakes up
+    /// its thread if it's waiting to send a batch.
+    fn stop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        for (receiver, handle) in self.threads.drain(..) {
+            drop(receiver);
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Merge {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Iterator for Merge {
+    type Item = Result<Register, SqlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(register) = self.batch.next() {
+                return Some(Ok(register));
+            }
+
+            let (receiver, _) = self.threads.front()?;
+            match receiver.recv() {
+                Ok(Ok(batch)) => self.batch = batch.into_iter(),
+                Ok(Err(e)) => {
+                    self.stop();
+                    return Some(Err(e));
+                }
+                Err(_) => {
+                    let (_, handle) = self.threads.pop_front()?;
+                    if handle.join().is_err() {
+                        self.stop();
+                        return Some(Err(SqlError::Error));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the registers of the chunks that meet the condition of the `WHERE` clause, in the order of the chunks.
+///
+/// Each chunk is read by its own thread, which evaluates the condition on its registers and sends the ones that meet it
+/// to the returned iterator. A thread reads only a few batches ahead of the registers returned, so the registers are read
+/// with bounded memory. Dropping the iterator cancels the threads and waits for them to end.
+///
+/// # Arguments
+///
+/// * `columns` - The columns of the table.
+/// * `chunks` - The chunks of the table, in file order, see `Snapshot::split`.
+/// * `where_clause` - The `WHERE` clause the registers must meet.
+///
+pub fn filter_chunks(
+    columns: &Arc<Columns>,
+    chunks: Vec<Chunk>,
+    where_clause: &Where,
+) -> impl Iterator<Item = Result<Register, SqlError>> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let threads = chunks
+        .into_iter()
+        .map(|chunk| {
+            let (sender, receiver) = mpsc::sync_channel(BATCHES_AHEAD);
+            let columns = Arc::clone(columns);
+            let where_clause = where_clause.clone();
+            let cancel = Arc::clone(&cancel);
+            let handle =
+                thread::spawn(move || filter_chunk(chunk, columns, where_clause, sender, cancel));
+            (receiver, handle)
+        })
+        .collect();
+
+    Merge {
+        batch: Vec::new().into_iter(),
+        threads,
+        cancel,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::filter_chunks;
+    use crate::{
+        clauses::where_sql::Where,
+        errors::SqlError,
+        register::{Columns, Register},
+        snapshot::Snapshot,
+    };
+    use std::{fs, sync::Arc, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn filter_chunks_in_file_order() {
+        let folder = std::env::temp_dir().join("rustic_sql_parallel");
+        fs::create_dir_all(&folder).unwrap();
+        let lines: Vec<String> = (0..5000).map(|id| format!("{},{}", id, id % 7)).collect();
+        fs::write(
+            folder.join("ordenes.csv"),
+            format!("id,cantidad\n{}\n", lines.join("\n")),
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+        let where_clause = Where::new_from_tokens(vec!["WHERE", "cantidad", "=", "3"]).unwrap();
+
+        let columns = Arc::new(Columns::new(vec![
+            String::from("id"),
+            String::from("cantidad"),
+        ]));
+        let expected: Vec<Register> = lines
+            .iter()
+            .map(|line| Register::from_csv(line, &columns))
+            .filter(|register| where_clause.execute(register).unwrap())
+            .collect();
+
+        for count in [1, 3, 8] {
+            let snapshot = Snapshot::open(folder_path, "ordenes", TIMEOUT).unwrap();
+            let (header, chunks) = snapshot.split(count).unwrap();
+            assert_eq!(header, "id,cantidad");
+            assert_eq!(chunks.len(), count);
+
+            let registers: Vec<Register> = filter_chunks(&columns, chunks, &where_clause)
+                .map(|register| register.unwrap())
+                .collect();
+            assert_eq!(registers, expected);
+        }
+
+        let where_clause = Where::new_from_tokens(vec!["WHERE", "cantidad", "=", "x"]).unwrap();
+        let (_, chunks) = Snapshot::open(folder_path, "ordenes", TIMEOUT)
+            .unwrap()
+            .split(4)
+            .unwrap();
+        let mut registers = filter_chunks(&columns, chunks, &where_clause);
+        assert_eq!(registers.next(), Some(Err(SqlError::InvalidSyntax)));
+        assert_eq!(registers.next(), None);
+
+        let where_clause = Where::new_from_tokens(vec!["WHERE", "cantidad", ">", "0"]).unwrap();
+        let (_, chunks) = Snapshot::open(folder_path, "ordenes", TIMEOUT)
+            .unwrap()
+            .split(4)
+            .unwrap();
+        let mut registers = filter_chunks(&columns, chunks, &where_clause);
+        assert!(registers.next().unwrap().is_ok());
+        drop(registers);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{
+    clauses::{orderby_sql::OrderBy, where_sql::Where},
+    errors::SqlError,
+    parallel::{chunk_count, filter_chunks},
+    register::{Columns, Register},
+    snapshot::Snapshot,
+    sort::{sort, top},
+    table::Table,
+};
+use std::{fmt, io::BufRead, sync::Arc};
+
+/// Struct that represents the registers of the result of a query, read one at a time from the table.
+///
+/// A stream is built as a pipeline of steps over the lines of a table: `scan` reads them, `filter_where` keeps the
+/// registers that meet a condition, `project` keeps some of their columns and `limit` stops reading once enough
+/// registers were returned. Only `sort` and `top` need to read every register before returning the first one.
+///
+/// The registers are read as the stream is iterated, so a table of any size is read with bounded memory.
+///
+/// # Fields
+///
+/// * `columns` - The columns of the registers returned by the stream, in the order they are shown.
+/// * `registers` - The registers of the stream, or the error found while reading one of them.
+///
+/// # Examples
+///
+/// ```ignore
+/// let stream = RegisterStream::scan(snapshot)?
+///     .filter_where(where_clause)
+///     .project(vec![String::from("nombre")])?
+///     .limit(10);
+///
+/// for register in stream {
+///     println!("{:?}", register?);
+/// }
+/// ```
+///
+pub struct RegisterStream {
+    pub columns: Vec<String>,
+    registers: Box<dyn Iterator<Item = Result<Register, SqlError>>>,
+}
+
+impl RegisterStream {
+    /// Creates a stream over the given registers, which have the given columns.
+    pub fn new(
+        columns: Vec<String>,
+        registers: impl Iterator<Item = Result<Register, SqlError>> + 'static,
+    ) -> Self {
+        Self {
+            columns,
+            registers: Box::new(registers),
+        }
+    }
+
+    /// Reads the registers of a table, given by a reader over its csv lines, where the first one holds its columns.
+    pub fn scan<R: BufRead + 'static>(table: R) -> Result<Self, SqlError> {
+        let mut lines = table.lines();
+        let columns: Vec<String> = match lines.next() {
+            Some(header) => header
+                .map_err(|_| SqlError::Error)?
+                .split(',')
+                .map(|column| column.to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let scanned = Arc::new(Columns::new(columns.to_vec()));
+        let registers = lines.map(move |line| {
+            let line = line.map_err(|_| SqlError::Error)?;
+            Ok(Register::from_csv(&line, &scanned))
+        });
+
+        Ok(Self::new(columns, registers))
+    }
+
+    /// Reads the registers of a version of a table that meet the condition of the `WHERE` clause, like `scan` followed
+    /// by `filter_where`.
+    ///
+    /// A large table is split in chunks, one per thread, and the condition is evaluated on the
+    /// chunks at the same time, see `parallel::filter_chunks`. The registers are still returned in file order.
+    pub fn scan_where(
+        snapshot: Snapshot,
+        where_clause: Where,
+        threads: usize,
+    ) -> Result<Self, SqlError> {
+        let count = chunk_count(snapshot.len, threads);
+        if count < 2 {
+            return Ok(Self::scan(snapshot)?.filter_where(where_clause));
+        }
+
+        let (header, chunks) = snapshot.split(count)?;
+        let columns: Vec<String> = match header.is_empty() {
+            true => Vec::new(),
+            false => header.split(',').map(|column| column.to_string()).collect(),
+        };
+
+        let shared = Arc::new(Columns::new(columns.to_vec()));
+        let registers = filter_chunks(&shared, chunks, &where_clause);
+
+        Ok(Self::new(columns, registers))
+    }
+
+    /// Keeps only the registers that meet the condition of the `WHERE` clause.
+    pub fn filter_where(self, where_clause: Where) -> Self {
+        let registers = self.registers.filter_map(move |register| match register {
+            Ok(register) => match where_clause.execute(&register) {
+                Ok(true) => Some(Ok(register)),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            },
+            Err(e) => Some(Err(e)),
+        });
+
+        Self::new(self.columns, registers)
+    }
+
+    /// Sorts the registers by the columns of the `ORDER BY` clause, spilling them to temporary files in the folder
+    /// if they don't fit in the given bytes of memory, see `sort::sort`.
+    ///
+    /// Every register is read before returning the first one, so the first error found is returned instead.
+    pub fn sort(
+        self,
+        orderby: &OrderBy,
+        folder_path: &str,
+        memory: usize,
+    ) -> Result<Self, SqlError> {
+        let registers = sort(self.registers, &self.columns, orderby, folder_path, memory)?;
+        Ok(Self::new(self.columns, registers))
+    }
+
+    /// Keeps the first registers sorted by the columns of the `ORDER BY` clause, like `sort` followed by `limit`, but
+    /// keeping only as many registers as the limit in memory while reading the rest, see `sort::top`.
+    pub fn top(
+        self,
+        orderby: &OrderBy,
+        limit: usize,
+        folder_path: &str,
+        memory: usize,
+    ) -> Result<Self, SqlError> {
+        let registers = top(
+            self.registers,
+            &self.columns,
+            orderby,
+            limit,
+            folder_path,
+            memory,
+        )?;
+        Ok(Self::new(self.columns, registers))
+    }
+
+    /// Keeps only the given columns of the registers, which are returned in the given order.
+    ///
+    /// The position of each column is looked up once, so every register is projected by its positions.
+    /// Returns an `InvalidColumn` error if the registers don't have one of the columns.
+    pub fn project(self, columns: Vec<String>) -> Result<Self, SqlError> {
+        let positions = Columns::new(self.columns).positions(&columns)?;
+
+        let projected = Arc::new(Columns::new(columns.to_vec()));
+        let registers = self
+            .registers
+            .map(move |register| Ok(register?.project(&projected, &positions)));
+
+        Ok(Self::new(columns, registers))
+    }
+
+    /// Stops the stream after the given number of registers, without reading the rest of the table.
+    pub fn limit(self, limit: usize) -> Self {
+        Self::new(self.columns, self.registers.take(limit))
+    }
+
+    /// Reads every register of the stream into a table.
+    ///
+    /// Returns the first error found while reading the registers, if any.
+    pub fn into_table(self) -> Result<Table, SqlError> {
+        Ok(Table {
+            registers: self
+                .registers
+                .collect::<Result<Vec<Register>, SqlError>>()?,
+            columns: self.columns,
+        })
+    }
+}
+
+impl Iterator for RegisterStream {
+    type Item = Result<Register, SqlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.registers.next()
+    }
+}
+
+/// Shows the columns of the stream, since its registers can only be shown by reading them.
+impl fmt::Debug for RegisterStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RegisterStream")
+            .field("columns", &self.columns)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegisterStream;
+    use crate::{
+        clauses::{condition::Condition, where_sql::Where},
+        errors::SqlError,
+        operator::Operator,
+        register::{Columns, Register},
+    };
+    use std::{
+        io::{self, BufRead, Cursor, Read},
+        sync::Arc,
+    };
+
+    /// Reader that fails if the stream reads past the given content.
+    struct Bounded(Cursor<Vec<u8>>);
+
+    impl Read for Bounded {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl BufRead for Bounded {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            if self.0.position() == self.0.get_ref().len() as u64 {
+                return Err(io::Error::other("read past the limit"));
+            }
+            self.0.fill_buf()
+        }
+
+        fn consume(&mut self, amount: usize) {
+            self.0.consume(amount)
+        }
+    }
+
+    #[test]
+    fn scan_filter_project_limit() {
+        let table = Bounded(Cursor::new(
+            b"id,nombre,edad\n1,Juan,20\n2,Ana,15\n3,Luis,30\n".to_vec(),
+        ));
+        let where_clause = Where {
+            condition: Condition::Simple {
+                field: String::from("edad"),
+                operator: Operator::Greater,
+                value: String::from("18"),
+            },
+        };
+
+        let mut stream = RegisterStream::scan(table)
+            .unwrap()
+            .filter_where(where_clause)
+            .project(vec![String::from("nombre"), String::from("id")])
+            .unwrap()
+            .limit(2);
+        assert_eq!(stream.columns, vec!["nombre", "id"]);
+
+        let columns = Arc::new(Columns::new(vec![
+            String::from("nombre"),
+            String::from("id"),
+        ]));
+        for line in ["Juan,1", "Luis,3"] {
+            assert_eq!(stream.next(), Some(Ok(Register::from_csv(line, &columns))));
+        }
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn project_unknown_column() {
+        let table = Cursor::new(b"id,nombre\n1,Juan\n".to_vec());
+        let stream = RegisterStream::scan(table).unwrap();
+
+        assert_eq!(
+            stream.project(vec![String::from("email")]).err(),
+            Some(SqlError::InvalidColumn)
+        );
+    }
+}
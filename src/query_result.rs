@@ -0,0 +1,69 @@
+use crate::{errors::SqlError, pipeline::RegisterStream, table::Table};
+use std::fmt::Display;
+
+/// Enum for the result of a statement.
+///
+/// - `Rows`: the table returned by a statement, like the registers of a RETURNING clause.
+/// - `Stream`: the rows returned by a query, read from the table as they are iterated.
+/// - `Affected`: the number of registers inserted, updated or deleted by a statement, along with its command.
+/// - `Empty`: the result of the statements that neither return nor change registers, like CREATE, DROP or BEGIN.
+///
+#[derive(Debug)]
+pub enum QueryResult {
+    Rows(Table),
+    Stream(RegisterStream),
+    Affected { command: String, rows: usize },
+    Empty,
+}
+
+impl QueryResult {
+    /// Returns the number of registers returned or affected by the statement.
+    ///
+    /// The rows of a stream are only known once they are read, see `materialize`, so a stream has none.
+    pub fn rows(&self) -> usize {
+        match self {
+            QueryResult::Rows(table) => table.registers.len(),
+            QueryResult::Affected { rows, .. } => *rows,
+            QueryResult::Stream(_) | QueryResult::Empty => 0,
+        }
+    }
+
+    /// Reads the rows of a stream into a table, returning the first error found while reading them.
+    ///
+    /// Any other result is returned as it is.
+    pub fn materialize(self) -> Result<Self, SqlError> {
+        match self {
+            QueryResult::Stream(stream) => Ok(QueryResult::Rows(stream.into_table()?)),
+            result => Ok(result),
+        }
+    }
+}
+
+/// Streams are never equal, since comparing their rows would consume them.
+impl PartialEq for QueryResult {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (QueryResult::Rows(a), QueryResult::Rows(b)) => a == b,
+            (
+                QueryResult::Affected { command, rows },
+                QueryResult::Affected {
+                    command: other_command,
+                    rows: other_rows,
+                },
+            ) => command == other_command && rows == other_rows,
+            (QueryResult::Empty, QueryResult::Empty) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Shows the command of the statement followed by the number of registers returned or affected, like `UPDATE 3`.
+impl Display for QueryResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryResult::Rows(table) => write!(f, "SELECT {}", table.registers.len()),
+            QueryResult::Affected { command, rows } => write!(f, "{} {}", command, rows),
+            QueryResult::Stream(_) | QueryResult::Empty => Ok(()),
+        }
+    }
+}
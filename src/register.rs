@@ -1,5 +1,64 @@
-use crate::errors::SqlError;
-use std::collections::HashMap;
+use crate::{errors::SqlError, value::Value};
+use std::{collections::HashMap, sync::Arc};
+
+/// Columns struct
+///
+/// The `Columns` struct represents the columns of a table, shared by all of its registers,
+/// so the position of a column is looked up once instead of storing its name in every register.
+///
+/// # Fields
+///
+/// * `names` - The names of the columns, in the order of the table.
+/// * `positions` - The position of each column, by its name.
+///
+/// # Examples
+///
+/// ```ignore
+/// let columns = Columns::new(vec!["id".to_string(), "name".to_string()]);
+///
+/// assert_eq!(columns.position("name"), Some(1));
+/// assert_eq!(columns.position("age"), None);
+/// ```
+///
+#[derive(Debug, PartialEq)]
+pub struct Columns {
+    names: Vec<String>,
+    positions: HashMap<String, usize>,
+}
+
+impl Columns {
+    /// Creates the columns with the given names, in the order of the table.
+    ///
+    /// If a name is repeated, its position is the one of its first appearance.
+    pub fn new(names: Vec<String>) -> Self {
+        let mut positions = HashMap::new();
+        for (idx, name) in names.iter().enumerate() {
+            positions.entry(name.to_string()).or_insert(idx);
+        }
+
+        Self { names, positions }
+    }
+
+    /// Returns the names of the columns, in the order of the table.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the position of a column, or `None` if there is no column with that name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.positions.get(name).copied()
+    }
+
+    /// Returns the positions of the given columns, in the same order.
+    ///
+    /// Returns an `InvalidColumn` error if one of the columns doesn't exist.
+    pub fn positions(&self, names: &[String]) -> Result<Vec<usize>, SqlError> {
+        names
+            .iter()
+            .map(|name| self.position(name).ok_or(SqlError::InvalidColumn))
+            .collect()
+    }
+}
 
 /// Register struct
 ///
@@ -7,58 +66,104 @@ use std::collections::HashMap;
 ///
 /// # Fields
 ///
-/// * A `HashMap` containing the column name and the column value.
+/// * `columns` - The columns of the register, shared with the rest of the registers of the table.
+/// * `values` - The values of the register, in the same order as the columns.
 ///
 /// # Examples
 ///
-/// ```
-/// let register = Register(HashMap::new());
+/// ```ignore
+/// let columns = Arc::new(Columns::new(vec!["id".to_string(), "name".to_string()]));
+/// let register = Register::new(columns, vec![Value::Integer(1), Value::from("Alen")]);
 ///
 /// ```
 ///
 #[derive(Clone, Debug, PartialEq)]
-pub struct Register(pub HashMap<String, String>);
+pub struct Register {
+    pub columns: Arc<Columns>,
+    pub values: Vec<Value>,
+}
 
 impl Register {
-    /// Converts a register to a csv format.
-    /// The column order is given by the columns parameter.
-    ///
-    /// Returns a string with the values of the register separated by commas.
+    /// Creates a register with the given values, in the same order as the columns.
+    pub fn new(columns: Arc<Columns>, values: Vec<Value>) -> Self {
+        Self { columns, values }
+    }
+
+    /// Builds a register from a line of a csv table.
+    /// The values in the line are in the same order as the columns.
     ///
-    /// If a column is not found in the register, returns an error.
+    /// Missing values are stored as empty values.
     ///
     /// # Examples
     ///
-    /// ```
-    /// let register = Register(HashMap::new());
-    /// let columns = vec!["id".to_string(), "name".to_string(), "age".to_string()];
-    /// let result = register.to_csv(&columns);
+    /// ```ignore
+    /// let columns = Arc::new(Columns::new(vec!["id".to_string(), "name".to_string()]));
+    /// let register = Register::from_csv("1,Alen", &columns);
     ///
-    /// assert_eq!(result, Err(SqlError::Error));
+    /// assert_eq!(register.get("name"), Some(&Value::from("Alen")));
+    /// ```
     ///
+    pub fn from_csv(line: &str, columns: &Arc<Columns>) -> Self {
+        let mut values: Vec<Value> = line
+            .split(',')
+            .take(columns.names.len())
+            .map(Value::from_csv)
+            .collect();
+        values.resize(columns.names.len(), Value::Null);
+
+        Self::new(Arc::clone(columns), values)
+    }
+
+    /// Returns the value of a column of the register, or `None` if the register doesn't have that column.
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.columns
+            .position(column)
+            .and_then(|idx| self.values.get(idx))
+    }
+
+    /// Sets the value of a column of the register.
     ///
-    /// let mut table = HashMap::new();
-    /// table.insert("id".to_string(), "1".to_string());
-    /// table.insert("name".to_string(), "Alen".to_string());
-    /// table.insert("age".to_string(), "25".to_string());
+    /// Returns an `InvalidColumn` error if the register doesn't have that column.
+    pub fn set(&mut self, column: &str, value: Value) -> Result<(), SqlError> {
+        let idx = self
+            .columns
+            .position(column)
+            .ok_or(SqlError::InvalidColumn)?;
+        self.values[idx] = value;
+        Ok(())
+    }
+
+    /// Returns a register with the values at the given positions, for the given columns.
     ///
-    /// let register = Register(table);
-    /// let columns = vec!["id".to_string(), "name".to_string(), "age".to_string()];
-    /// let result = register.to_csv(&columns);
+    /// # Arguments
     ///
-    /// assert_eq!(result, Ok("1,Alen,25".to_string()));
-    /// ```
+    /// * `columns` - The columns of the returned register.
+    /// * `positions` - The position in this register of each of the columns, see `Columns::positions`.
     ///
-    pub fn to_csv(&self, columns: &Vec<String>) -> Result<String, SqlError> {
-        let mut values = Vec::new();
-
-        for col in columns {
-            let value = self.0.get(col).ok_or(SqlError::Error)?;
-            values.push(value.to_string());
-        }
+    pub fn project(&self, columns: &Arc<Columns>, positions: &[usize]) -> Self {
+        let values = positions
+            .iter()
+            .map(|idx| self.values.get(*idx).cloned().unwrap_or(Value::Null))
+            .collect();
 
-        let csv = values.join(",");
+        Self::new(Arc::clone(columns), values)
+    }
 
-        Ok(csv)
+    /// Converts a register to a csv format.
+    ///
+    /// Returns a string with the values of the register separated by commas, in the order of its columns.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let columns = Arc::new(Columns::new(vec!["id".to_string(), "name".to_string(), "age".to_string()]));
+    /// let register = Register::from_csv("1,Alen,", &columns);
+    ///
+    /// assert_eq!(register.to_csv(), "1,Alen,");
+    /// ```
+    ///
+    pub fn to_csv(&self) -> String {
+        let values: Vec<String> = self.values.iter().map(|value| value.to_string()).collect();
+        values.join(",")
     }
 }
@@ -0,0 +1,311 @@
+use crate::{
+    errors::SqlError,
+    line_editor::LineEditor,
+    output::{Mode, Output},
+    script::{is_complete, split_statements},
+    session::Session,
+    utils::find_file_in_folder,
+};
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, IsTerminal},
+    path::PathBuf,
+    time::Instant,
+};
+
+const PROMPT: &str = "rustic-sql> ";
+const CONTINUATION_PROMPT: &str = "       ...> ";
+const HELP: &[&str] = &[
+    ".headers on|off    Show or hide the names of the columns",
+    ".help              Show this message",
+    ".mode [MODE]       Show or set the output mode: csv, column, table, json, jsonl, markdown, tsv or html",
+    ".quit              Exit the shell",
+    ".schema [table]    Show the columns and constraints of the tables",
+    ".tables            List the tables of the folder",
+    ".timer on|off      Show the time taken by each statement",
+];
+
+/// Struct that represents an interactive shell over the tables of a folder.
+///
+/// Statements can span several lines, and are executed once a line ends with `;`, outside of string literals and comments.
+/// Lines starting with `.` at the beginning of a statement are meta-commands of the shell, like `.tables` or `.quit`.
+///
+/// # Fields
+///
+/// * `session` - The session where the statements are executed.
+/// * `output` - How the results of the queries are shown.
+/// * `timer` - Whether the time taken by each statement is shown.
+/// * `editor` - The editor used to read the lines, with the history of the shell.
+///
+#[derive(Debug)]
+pub struct Repl {
+    pub session: Session,
+    pub output: Output,
+    pub timer: bool,
+    editor: LineEditor,
+}
+
+/// Returns the names of the tables stored in the folder, sorted.
+pub fn table_names(folder_path: &str) -> Result<Vec<String>, SqlError> {
+    let entries = fs::read_dir(folder_path).map_err(|_| SqlError::InvalidTable)?;
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".csv")
+                .filter(|table| !table.starts_with('.'))
+                .map(String::from)
+        })
+        .collect();
+
+    names.sort();
+    Ok(names)
+}
+
+/// Returns the definition of a table as a `CREATE TABLE` statement, with the columns of the table
+/// and the lines of its schema file.
+///
+/// # Examples
+///
+/// ```ignore
+/// let lines = table_definition("tablas", "clientes").unwrap();
+///
+/// assert_eq!(lines[0], "CREATE TABLE clientes (");
+/// assert_eq!(lines[1], "  id INTEGER PRIMARY KEY AUTOINCREMENT,");
+/// ```
+///
+pub fn table_definition(folder_path: &str, table_name: &str) -> Result<Vec<String>, SqlError> {
+    if !find_file_in_folder(folder_path, &(table_name.to_string() + ".csv")) {
+        return Err(SqlError::InvalidTable);
+    }
+    let path = folder_path.to_string() + "/" + table_name;
+    let file = fs::File::open(path.to_string() + ".csv").map_err(|_| SqlError::InvalidTable)?;
+    let mut header = String::new();
+    BufReader::new(file)
+        .read_line(&mut header)
+        .map_err(|_| SqlError::Error)?;
+
+    let schema = fs::read_to_string(path + ".schema").unwrap_or_default();
+    let mut constraints: Vec<&str> = schema
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let mut definitions = Vec::new();
+    for column in header.trim_end().split(',') {
+        match constraints
+            .iter()
+            .position(|line| line.split(' ').next() == Some(column))
+        {
+            Some(idx) => definitions.push(constraints.remove(idx).to_string()),
+            None => definitions.push(column.to_string()),
+        }
+    }
+    definitions.extend(constraints.iter().map(|line| line.to_string()));
+
+    let mut lines = vec![format!("CREATE TABLE {} (", table_name)];
+    let last = definitions.len().saturating_sub(1);
+    for (idx, definition) in definitions.iter().enumerate() {
+        let separator = if idx < last { "," } else { "" };
+        lines.push(format!("  {}{}", definition, separator));
+    }
+    lines.push(String::from(");"));
+
+    Ok(lines)
+}
+
+/// Returns the path to the history file, or `None` if the standard input is not a terminal, like when statements are piped to the shell.
+fn history_path() -> Option<PathBuf> {
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".rustic_sql_history"))
+}
+
+impl Repl {
+    /// Creates a new shell over the given session, with the history stored in `~/.rustic_sql_history` when the standard
+    /// input is a terminal.
+    pub fn new(session: Session, output: Output) -> Self {
+        Self {
+            session,
+            output,
+            timer: false,
+            editor: LineEditor::new(history_path()),
+        }
+    }
+
+    /// Reads and executes statements and meta-commands until `.quit` is entered or the input ends.
+    pub fn run(&mut self) -> Result<(), SqlError> {
+        let mut statement = String::new();
+
+        loop {
+            let prompt = if statement.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            };
+            let Some(line) = self.editor.read_line(prompt)? else {
+                break;
+            };
+
+            if statement.is_empty() && line.trim_start().starts_with('.') {
+                self.editor.add_history(line.trim());
+                match self.exec_meta(line.trim()) {
+                    Ok(Some(lines)) => lines.iter().for_each(|line| println!("{}", line)),
+                    Ok(None) => break,
+                    Err(e) => eprintln!("{}", e),
+                }
+                continue;
+            }
+
+            if !statement.is_empty() {
+                statement.push('\n');
+            }
+            statement.push_str(&line);
+            if statement.trim().is_empty() {
+                statement.clear();
+                continue;
+            }
+            if !is_complete(&statement) {
+                continue;
+            }
+
+            self.editor.add_history(&statement);
+            for query in split_statements(&statement) {
+                self.exec_statement(&query);
+            }
+            statement.clear();
+        }
+
+        Ok(())
+    }
+
+    /// Executes a meta-command of the shell and returns the lines to show.
+    ///
+    /// Returns `None` when the meta-command ends the shell.
+    pub fn exec_meta(&mut self, command: &str) -> Result<Option<Vec<String>>, SqlError> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+
+        let lines = match words.as_slice() {
+            [".quit" | ".exit"] => return Ok(None),
+            [".help"] => HELP.iter().map(|line| line.to_string()).collect(),
+            [".tables"] => table_names(&self.session.folder_path)?,
+            [".schema"] => {
+                let mut lines = Vec::new();
+                for table_name in table_names(&self.session.folder_path)? {
+                    lines.extend(table_definition(&self.session.folder_path, &table_name)?);
+                }
+                lines
+            }
+            [".schema", table_name] => table_definition(&self.session.folder_path, table_name)?,
+            [".mode"] => vec![self.output.mode.name().to_string()],
+            [".mode", name] => {
+                self.output.mode = Mode::from_name(name).ok_or(SqlError::InvalidSyntax)?;
+                Vec::new()
+            }
+            [".headers", "on"] => {
+                self.output.header = true;
+                Vec::new()
+            }
+            [".headers", "off"] => {
+                self.output.header = false;
+                Vec::new()
+            }
+            [".timer", "on"] => {
+                self.timer = true;
+                Vec::new()
+            }
+            [".timer", "off"] => {
+                self.timer = false;
+                Vec::new()
+            }
+            _ => return Err(SqlError::InvalidSyntax),
+        };
+
+        Ok(Some(lines))
+    }
+
+    fn exec_statement(&mut self, statement: &str) {
+        let start = Instant::now();
+        let result = self
+            .session
+            .exec(statement)
+            .and_then(|result| self.output.print(result));
+        let elapsed = start.elapsed();
+
+        if let Err(e) = result {
+            eprintln!("{}", e);
+        }
+        if self.timer {
+            println!("Run Time: {:.3}s", elapsed.as_secs_f64());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{table_definition, table_names, Repl};
+    use crate::{
+        errors::SqlError,
+        output::{Mode, Output},
+        session::Session,
+    };
+    use std::fs;
+
+    #[test]
+    fn tables_and_schema() {
+        let folder = std::env::temp_dir().join("rustic_sql_repl_tables");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("ordenes.csv"), "id,producto\n").unwrap();
+        fs::write(folder.join("clientes.csv"), "id,nombre,email\n").unwrap();
+        fs::write(
+            folder.join("clientes.schema"),
+            "UNIQUE (nombre, email)\nnombre NOT NULL\nid INTEGER PRIMARY KEY\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        assert_eq!(
+            table_names(folder_path),
+            Ok(vec![String::from("clientes"), String::from("ordenes")])
+        );
+        assert_eq!(
+            table_definition(folder_path, "clientes"),
+            Ok(vec![
+                String::from("CREATE TABLE clientes ("),
+                String::from("  id INTEGER PRIMARY KEY,"),
+                String::from("  nombre NOT NULL,"),
+                String::from("  email,"),
+                String::from("  UNIQUE (nombre, email)"),
+                String::from(");"),
+            ])
+        );
+        assert_eq!(
+            table_definition(folder_path, "productos"),
+            Err(SqlError::InvalidTable)
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn meta_commands() {
+        let mut repl = Repl::new(Session::new("tablas", false), Output::default());
+
+        assert_eq!(repl.exec_meta(".mode column"), Ok(Some(Vec::new())));
+        assert_eq!(repl.output.mode, Mode::Column);
+        assert_eq!(
+            repl.exec_meta(".mode"),
+            Ok(Some(vec![String::from("column")]))
+        );
+        assert_eq!(repl.exec_meta(".timer on"), Ok(Some(Vec::new())));
+        assert!(repl.timer);
+        assert_eq!(repl.exec_meta(".headers off"), Ok(Some(Vec::new())));
+        assert!(!repl.output.header);
+        assert_eq!(repl.exec_meta(".mode xml"), Err(SqlError::InvalidSyntax));
+        assert_eq!(repl.exec_meta(".quit"), Ok(None));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{errors::SqlError, pipeline::RegisterStream, value::Value};
+use std::{mem, sync::Arc};
+
+/// Struct that represents a column of the result of a query.
+///
+/// # Fields
+///
+/// * `name` - The name of the column.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+}
+
+/// Struct that represents a register returned by a query.
+///
+/// # Fields
+///
+/// * `columns` - The columns of the result of the query, shared by all of its rows.
+/// * `values` - The values of the register, in the same order as the columns.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub columns: Arc<[Column]>,
+    pub values: Vec<Value>,
+}
+
+impl Row {
+    /// Returns the value of a column of the row, or `None` if the result of the query doesn't have that column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_sql::{Database, SqlError, Value};
+    ///
+    /// # fn main() -> Result<(), SqlError> {
+    /// let mut database = Database::open("tablas")?;
+    /// let row = database.query("SELECT id, nombre FROM clientes WHERE id = 1")?.next().unwrap()?;
+    ///
+    /// assert_eq!(row.get("id"), Some(&Value::Integer(1)));
+    /// assert_eq!(row.get("email"), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.columns
+            .iter()
+            .position(|c| c.name == column)
+            .and_then(|idx| self.values.get(idx))
+    }
+}
+
+/// Iterator over the rows returned by a query, in the order of the result.
+///
+/// The rows are read from the table as they are iterated, so each one is returned along with the error found while reading it, if any.
+///
+/// # Fields
+///
+/// * `columns` - The columns of the result of the query.
+///
+#[derive(Debug)]
+pub struct Rows {
+    pub columns: Arc<[Column]>,
+    registers: RegisterStream,
+}
+
+impl Rows {
+    /// Creates the iterator over the registers of the result of a query.
+    pub(crate) fn new(stream: RegisterStream) -> Self {
+        let columns = stream
+            .columns
+            .iter()
+            .map(|name| Column {
+                name: name.to_string(),
+            })
+            .collect();
+
+        Self {
+            columns,
+            registers: stream,
+        }
+    }
+}
+
+impl Iterator for Rows {
+    type Item = Result<Row, SqlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut register = match self.registers.next()? {
+            Ok(register) => register,
+            Err(e) => return Some(Err(e)),
+        };
+        let values = self
+            .columns
+            .iter()
+            .map(|column| {
+                register
+                    .columns
+                    .position(&column.name)
+                    .and_then(|idx| register.values.get_mut(idx))
+                    .map_or(Value::Null, |value| mem::replace(value, Value::Null))
+            })
+            .collect();
+
+        Some(Ok(Row {
+            columns: Arc::clone(&self.columns),
+            values,
+        }))
+    }
+}
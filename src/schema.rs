@@ -0,0 +1,914 @@
+use crate::{
+    clauses::where_sql::Where,
+    errors::SqlError,
+    foreign_key::ForeignKey,
+    register::Register,
+    sequence::Sequence,
+    tokens::tokens_from_query,
+    utils::{
+        find_file_in_folder, is_autoincrement, is_check, is_default, is_foreign, is_generated,
+        is_key, is_not, is_null, is_primary, is_references, is_unique, read_table,
+    },
+    value::Value,
+};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufRead, BufReader},
+    time::Duration,
+};
+
+/// Enum for the kinds of key constraints that can be declared in a table schema.
+/// - `Primary`: `PRIMARY KEY` constraint, the values must be unique and can't be empty.
+/// - `Unique`: `UNIQUE` constraint, the non empty values must be unique.
+///
+#[derive(Debug, PartialEq)]
+pub enum KeyKind {
+    Primary,
+    Unique,
+}
+
+/// Struct that represents a `PRIMARY KEY` or `UNIQUE` constraint.
+///
+/// # Fields
+///
+/// * `kind` - The kind of the key.
+/// * `columns` - The columns that form the key.
+///
+#[derive(Debug, PartialEq)]
+pub struct Key {
+    pub kind: KeyKind,
+    pub columns: Vec<String>,
+}
+
+impl Key {
+    /// Returns the name of the key as it is declared in the schema, for example `PRIMARY KEY (id)`.
+    pub fn name(&self) -> String {
+        let kind = match self.kind {
+            KeyKind::Primary => "PRIMARY KEY",
+            KeyKind::Unique => "UNIQUE",
+        };
+        format!("{} ({})", kind, self.columns.join(", "))
+    }
+
+    fn values(&self, register: &Register) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|col| register.get(col).map(Value::to_string).unwrap_or_default())
+            .collect()
+    }
+
+    fn violation(&self, values: &[String]) -> SqlError {
+        SqlError::ConstraintViolation {
+            constraint: self.name(),
+            value: format_values(values),
+        }
+    }
+}
+
+/// Struct that represents a `CHECK` constraint.
+///
+/// # Fields
+///
+/// * `expression` - The expression of the constraint as it is declared in the schema.
+/// * `where_clause` - The expression parsed as the condition of a `WHERE` clause.
+///
+#[derive(Debug, PartialEq)]
+pub struct Check {
+    pub expression: String,
+    pub where_clause: Where,
+}
+
+impl Check {
+    /// Creates and returns a new `Check` from its expression.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let check = Check::new("cantidad > 0").unwrap();
+    /// assert_eq!(check.name(), "CHECK (cantidad > 0)");
+    /// ```
+    ///
+    pub fn new(expression: &str) -> Result<Self, SqlError> {
+        let tokens = tokens_from_query(expression);
+        let mut where_tokens = vec!["WHERE"];
+        where_tokens.extend(tokens.iter().map(|t| t.as_str()));
+
+        Ok(Self {
+            expression: expression.to_string(),
+            where_clause: Where::new_from_tokens(where_tokens)?,
+        })
+    }
+
+    /// Returns the name of the constraint as it is declared in the schema, for example `CHECK (cantidad > 0)`.
+    pub fn name(&self) -> String {
+        format!("CHECK ({})", self.expression)
+    }
+
+    /// Evaluates the constraint on the given register.
+    ///
+    /// As in SQL, the constraint is met when any of the values it references is empty.
+    /// Values that can't be compared with the expression don't meet the constraint.
+    ///
+    pub fn execute(&self, register: &Register) -> Result<(), SqlError> {
+        let values: Vec<String> = self
+            .where_clause
+            .condition
+            .fields()
+            .iter()
+            .map(|field| {
+                register
+                    .get(field)
+                    .map(Value::to_string)
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        if values.iter().any(|value| value.is_empty()) {
+            return Ok(());
+        }
+
+        match self.where_clause.execute(register) {
+            Ok(true) => Ok(()),
+            _ => Err(SqlError::ConstraintViolation {
+                constraint: self.name(),
+                value: format_values(&values),
+            }),
+        }
+    }
+}
+
+/// Struct that represents a column declared in a table schema.
+///
+/// # Fields
+///
+/// * `name` - The name of the column.
+/// * `data_type` - The type of the column, if it was declared.
+/// * `not_null` - Whether the column has a `NOT NULL` constraint.
+/// * `default` - The value used when an `INSERT` doesn't give one for the column.
+/// * `identity` - Whether the column is an identity column, whose values are generated by an implicit sequence.
+///
+#[derive(Debug, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub data_type: Option<String>,
+    pub not_null: bool,
+    pub default: Option<DefaultValue>,
+    pub identity: Option<Identity>,
+}
+
+/// Enum for the default values of a column.
+/// - `Literal`: A constant value.
+/// - `NextVal`: The next value of the named sequence, declared as `DEFAULT nextval('sequence')`.
+///
+#[derive(Debug, PartialEq)]
+pub enum DefaultValue {
+    Literal(String),
+    NextVal(String),
+}
+
+impl DefaultValue {
+    /// Returns the value to store in the column, advancing the sequence for `nextval` defaults,
+    /// waiting up to the given timeout for its lock.
+    pub fn resolve(&self, folder_path: &str, timeout: Duration) -> Result<String, SqlError> {
+        match self {
+            DefaultValue::Literal(value) => Ok(value.to_string()),
+            DefaultValue::NextVal(sequence) => {
+                Ok(Sequence::next_value(folder_path, sequence, timeout)?.to_string())
+            }
+        }
+    }
+}
+
+/// Enum for the kinds of identity columns.
+/// - `Always`: Declared as `GENERATED ALWAYS AS IDENTITY`, the values can't be given by an `INSERT`.
+/// - `ByDefault`: Declared as `AUTOINCREMENT` or `GENERATED BY DEFAULT AS IDENTITY`, the values are generated when an `INSERT` doesn't give one.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Identity {
+    Always,
+    ByDefault,
+}
+
+/// Struct that represents the schema of a table.
+///
+/// The schema of a table is declared in a `<table>.schema` file stored next to the `<table>.csv` file.
+/// Each line of the file declares a column followed by its type and constraints, or a table constraint:
+///
+/// ```text
+/// id INTEGER PRIMARY KEY
+/// nombre NOT NULL
+/// email UNIQUE DEFAULT 'sin email'
+/// cantidad INTEGER CHECK (cantidad > 0)
+/// UNIQUE (nombre, apellido)
+/// ```
+///
+/// Tables without a schema file have no constraints.
+///
+/// # Fields
+///
+/// * `columns` - The columns declared in the schema.
+/// * `keys` - The `PRIMARY KEY` and `UNIQUE` constraints of the table.
+/// * `checks` - The `CHECK` constraints of the table.
+/// * `foreign_keys` - The `FOREIGN KEY` constraints of the table.
+/// * `table_name` - The name of the table.
+/// * `folder_path` - The path to the folder where the table is stored, used to read the referenced tables.
+///
+#[derive(Debug, PartialEq, Default)]
+pub struct Schema {
+    pub columns: Vec<ColumnDef>,
+    pub keys: Vec<Key>,
+    pub checks: Vec<Check>,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub table_name: String,
+    pub folder_path: String,
+}
+
+fn split_columns(token: &str) -> Vec<String> {
+    token.split(',').map(|c| c.trim().to_string()).collect()
+}
+
+/// Formats the values of a constraint for a `ConstraintViolation` error: `'1'` for a single value, `('1', 'Juan')` for many.
+pub fn format_values(values: &[String]) -> String {
+    if values.len() == 1 {
+        format!("'{}'", values[0])
+    } else {
+        format!("('{}')", values.join("', '"))
+    }
+}
+
+fn is_nextval(token: &str) -> bool {
+    token.eq_ignore_ascii_case("nextval")
+}
+
+fn parse_identity(tokens: &[String]) -> Result<(Identity, usize), SqlError> {
+    let tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+    match tokens[..] {
+        ["GENERATED", "ALWAYS", "AS", "IDENTITY", ..] => Ok((Identity::Always, 4)),
+        ["GENERATED", "BY", "DEFAULT", "AS", "IDENTITY", ..] => Ok((Identity::ByDefault, 5)),
+        _ => Err(SqlError::InvalidSyntax),
+    }
+}
+
+fn is_constraint(token: &str) -> bool {
+    is_primary(token)
+        || is_unique(token)
+        || is_not(token)
+        || is_default(token)
+        || is_check(token)
+        || is_references(token)
+        || is_autoincrement(token)
+        || is_generated(token)
+}
+
+impl Schema {
+    /// Opens the schema of the given table, stored in the `<table_name>.schema` file of the folder.
+    ///
+    /// Returns an empty schema if the table has no schema file.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_path` - A string slice that contains the path to the folder where the tables are stored.
+    /// * `table_name` - A string slice that contains the name of the table.
+    ///
+    pub fn open(folder_path: &str, table_name: &str) -> Result<Self, SqlError> {
+        let file_name = table_name.to_string() + ".schema";
+        let mut schema = if find_file_in_folder(folder_path, &file_name) {
+            let path = folder_path.to_string() + "/" + &file_name;
+            let file = File::open(path).map_err(|_| SqlError::InvalidTable)?;
+
+            let mut lines = Vec::new();
+            for line in BufReader::new(file).lines() {
+                lines.push(line.map_err(|_| SqlError::InvalidTable)?);
+            }
+
+            Self::new_from_lines(&lines)?
+        } else {
+            Self::default()
+        };
+
+        schema.table_name = table_name.to_string();
+        schema.folder_path = folder_path.to_string();
+
+        Ok(schema)
+    }
+
+    /// Creates and returns a new `Schema` from the lines of a schema file.
+    ///
+    /// Empty lines and lines starting with `--` are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let lines = vec!["id PRIMARY KEY".to_string(), "email UNIQUE".to_string()];
+    /// let schema = Schema::new_from_lines(&lines).unwrap();
+    ///
+    /// assert_eq!(schema.keys[0].name(), "PRIMARY KEY (id)");
+    /// assert_eq!(schema.keys[1].name(), "UNIQUE (email)");
+    /// ```
+    ///
+    pub fn new_from_lines(lines: &[String]) -> Result<Self, SqlError> {
+        let mut schema = Self::default();
+
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("--") {
+                continue;
+            }
+            let tokens = tokens_from_query(line);
+            if tokens.is_empty() {
+                return Err(SqlError::InvalidSyntax);
+            }
+
+            if is_primary(&tokens[0])
+                || is_unique(&tokens[0])
+                || is_check(&tokens[0])
+                || is_foreign(&tokens[0])
+            {
+                schema.parse_table_constraint(&tokens)?;
+            } else {
+                schema.parse_column(&tokens)?;
+            }
+        }
+
+        if schema
+            .keys
+            .iter()
+            .filter(|key| key.kind == KeyKind::Primary)
+            .count()
+            > 1
+        {
+            return Err(SqlError::InvalidSyntax);
+        }
+
+        Ok(schema)
+    }
+
+    fn parse_table_constraint(&mut self, tokens: &[String]) -> Result<(), SqlError> {
+        if let [check, expression] = tokens {
+            if is_check(check) {
+                self.checks.push(Check::new(expression)?);
+                return Ok(());
+            }
+        }
+
+        if tokens.len() > 3 && is_foreign(&tokens[0]) && is_key(&tokens[1]) {
+            let mut i = 3;
+            let foreign_key =
+                ForeignKey::new_from_tokens(split_columns(&tokens[2]), tokens, &mut i)?;
+            if i < tokens.len() {
+                return Err(SqlError::InvalidSyntax);
+            }
+            self.foreign_keys.push(foreign_key);
+            return Ok(());
+        }
+
+        let key = match tokens {
+            [primary, key, columns] if is_primary(primary) && is_key(key) => Key {
+                kind: KeyKind::Primary,
+                columns: split_columns(columns),
+            },
+            [unique, columns] if is_unique(unique) => Key {
+                kind: KeyKind::Unique,
+                columns: split_columns(columns),
+            },
+            _ => return Err(SqlError::InvalidSyntax),
+        };
+        self.keys.push(key);
+
+        Ok(())
+    }
+
+    fn parse_column(&mut self, tokens: &[String]) -> Result<(), SqlError> {
+        let name = tokens[0].to_string();
+        let mut data_type = None;
+        let mut not_null = false;
+        let mut default = None;
+        let mut identity = None;
+        let mut i = 1;
+
+        if i < tokens.len() && !is_constraint(&tokens[i]) {
+            data_type = Some(tokens[i].to_string());
+            i += 1;
+        }
+
+        while i < tokens.len() {
+            if is_primary(&tokens[i]) && i + 1 < tokens.len() && is_key(&tokens[i + 1]) {
+                self.keys.push(Key {
+                    kind: KeyKind::Primary,
+                    columns: vec![name.to_string()],
+                });
+                i += 2;
+            } else if is_unique(&tokens[i]) {
+                self.keys.push(Key {
+                    kind: KeyKind::Unique,
+                    columns: vec![name.to_string()],
+                });
+                i += 1;
+            } else if is_not(&tokens[i]) && i + 1 < tokens.len() && is_null(&tokens[i + 1]) {
+                not_null = true;
+                i += 2;
+            } else if is_default(&tokens[i]) && i + 2 < tokens.len() && is_nextval(&tokens[i + 1]) {
+                let sequence = tokens[i + 2].trim_matches('\'').to_string();
+                default = Some(DefaultValue::NextVal(sequence));
+                i += 3;
+            } else if is_default(&tokens[i]) && i + 1 < tokens.len() {
+                default = Some(DefaultValue::Literal(tokens[i + 1].to_string()));
+                i += 2;
+            } else if is_autoincrement(&tokens[i]) {
+                identity = Some(Identity::ByDefault);
+                i += 1;
+            } else if is_generated(&tokens[i]) {
+                let (kind, len) = parse_identity(&tokens[i..])?;
+                identity = Some(kind);
+                i += len;
+            } else if is_check(&tokens[i]) && i + 1 < tokens.len() {
+                self.checks.push(Check::new(&tokens[i + 1])?);
+                i += 2;
+            } else if is_references(&tokens[i]) {
+                let foreign_key =
+                    ForeignKey::new_from_tokens(vec![name.to_string()], tokens, &mut i)?;
+                self.foreign_keys.push(foreign_key);
+            } else {
+                return Err(SqlError::InvalidSyntax);
+            }
+        }
+
+        self.columns.push(ColumnDef {
+            name,
+            data_type,
+            not_null,
+            default,
+            identity,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the definition of the given column, if it is declared in the schema.
+    pub fn column(&self, column: &str) -> Option<&ColumnDef> {
+        self.columns.iter().find(|col| col.name == column)
+    }
+
+    /// Returns the value an `INSERT` stores in a column it doesn't give a value for.
+    ///
+    /// Identity columns take the next value of their sequence, columns with a default take it,
+    /// and the rest are left empty. The sequences are advanced waiting up to the given timeout for their locks.
+    ///
+    pub fn missing_value(&self, column: &str, timeout: Duration) -> Result<String, SqlError> {
+        let Some(col) = self.column(column) else {
+            return Ok(String::new());
+        };
+
+        if col.identity.is_some() {
+            let value =
+                Sequence::next_identity(&self.folder_path, &self.table_name, column, timeout)?;
+            return Ok(value.to_string());
+        }
+
+        match &col.default {
+            Some(default) => default.resolve(&self.folder_path, timeout),
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Checks the `NOT NULL` and `CHECK` constraints of the schema on a register inserted or modified by a statement.
+    ///
+    /// Returns a `ConstraintViolation` error naming the constraint and the conflicting value for the first constraint that is not met.
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - A reference to the `Register` to check.
+    ///
+    pub fn check_register(&self, register: &Register) -> Result<(), SqlError> {
+        for col in self.columns.iter().filter(|col| col.not_null) {
+            let value = register
+                .get(&col.name)
+                .map(Value::to_string)
+                .unwrap_or_default();
+            if value.is_empty() {
+                return Err(SqlError::ConstraintViolation {
+                    constraint: format!("NOT NULL ({})", col.name),
+                    value: format_values(&[value]),
+                });
+            }
+        }
+
+        for check in &self.checks {
+            check.execute(register)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the `FOREIGN KEY` constraints of the schema.
+    ///
+    /// Every register whose position is listed in `changed` must reference an existing register of the referenced table,
+    /// unless any of its referencing values is empty. When a table references itself, the registers given are used as the referenced table.
+    ///
+    /// # Arguments
+    ///
+    /// * `registers` - A slice with every register of the table, after the statement is applied.
+    /// * `changed` - A slice with the positions of the registers inserted or modified by the statement.
+    ///
+    pub fn check_foreign_keys(
+        &self,
+        registers: &[Register],
+        changed: &[usize],
+    ) -> Result<(), SqlError> {
+        let keys = self.table_keys(registers, changed);
+        let changed: Vec<&Register> = changed.iter().map(|idx| &registers[*idx]).collect();
+        keys.check_foreign_keys(&changed)
+    }
+
+    /// Checks the `PRIMARY KEY` and `UNIQUE` constraints of the schema.
+    ///
+    /// The registers whose positions are listed in `changed` are compared against every other register,
+    /// so a table that already held duplicated values doesn't make every statement fail.
+    ///
+    /// Returns a `ConstraintViolation` error naming the key and the conflicting value for the first duplicate found.
+    ///
+    /// # Arguments
+    ///
+    /// * `registers` - A slice with every register of the table, after the statement is applied.
+    /// * `changed` - A slice with the positions of the registers inserted or modified by the statement.
+    ///
+    pub fn check_keys(&self, registers: &[Register], changed: &[usize]) -> Result<(), SqlError> {
+        let mut keys = self.table_keys(registers, changed);
+        let changed: Vec<&Register> = changed.iter().map(|idx| &registers[*idx]).collect();
+        keys.check_keys(&changed)
+    }
+
+    fn table_keys(&self, registers: &[Register], changed: &[usize]) -> TableKeys<'_> {
+        let changed: HashSet<&usize> = changed.iter().collect();
+        let mut keys = TableKeys::new(self);
+        for (idx, register) in registers.iter().enumerate() {
+            keys.add(register, changed.contains(&idx));
+        }
+        keys
+    }
+}
+
+/// Struct that collects the values of the keys of a table while its registers are read one at a time, to check the
+/// registers changed by a statement against the rest without keeping every register in memory.
+///
+/// # Fields
+///
+/// * `schema` - The schema of the table.
+/// * `keys` - The values of each `PRIMARY KEY` and `UNIQUE` constraint in the registers that were not changed.
+/// * `referenced` - The values referenced by each foreign key of the schema in every register, only for the foreign keys of a table that references itself.
+///
+#[derive(Debug)]
+pub struct TableKeys<'a> {
+    schema: &'a Schema,
+    keys: Vec<HashSet<Vec<String>>>,
+    referenced: Vec<HashSet<Vec<String>>>,
+}
+
+impl<'a> TableKeys<'a> {
+    /// Creates the collector for the keys of the table with the given schema, without registers.
+    pub fn new(schema: &'a Schema) -> Self {
+        Self {
+            schema,
+            keys: vec![HashSet::new(); schema.keys.len()],
+            referenced: vec![HashSet::new(); schema.foreign_keys.len()],
+        }
+    }
+
+    /// Returns true if the schema has no constraint that needs the values of the registers, so they don't need to be added.
+    pub fn is_empty(&self) -> bool {
+        self.schema.keys.is_empty() && !self.references_itself()
+    }
+
+    /// Adds a register of the table, after the statement is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `register` - The register of the table.
+    /// * `changed` - Whether the register was inserted or modified by the statement.
+    ///
+    pub fn add(&mut self, register: &Register, changed: bool) {
+        if !changed {
+            for (key, seen) in self.schema.keys.iter().zip(&mut self.keys) {
+                seen.insert(key.values(register));
+            }
+        }
+        for (fk, referenced) in self.schema.foreign_keys.iter().zip(&mut self.referenced) {
+            if fk.table == self.schema.table_name {
+                referenced.insert(fk.referenced_values(register));
+            }
+        }
+    }
+
+    /// Checks the `PRIMARY KEY` and `UNIQUE` constraints on the changed registers, against the registers added
+    /// and against each other, see `Schema::check_keys`.
+    pub fn check_keys(&mut self, changed: &[&Register]) -> Result<(), SqlError> {
+        for (key, seen) in self.schema.keys.iter().zip(&mut self.keys) {
+            for register in changed {
+                let values = key.values(register);
+
+                if values.iter().any(|value| value.is_empty()) {
+                    if key.kind == KeyKind::Primary {
+                        return Err(key.violation(&values));
+                    }
+                    continue;
+                }
+                if !seen.insert(values.clone()) {
+                    return Err(key.violation(&values));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the `FOREIGN KEY` constraints on the changed registers, see `Schema::check_foreign_keys`.
+    ///
+    /// When the table references itself, the registers added are used as the referenced table.
+    pub fn check_foreign_keys(&self, changed: &[&Register]) -> Result<(), SqlError> {
+        for (fk, referenced) in self.schema.foreign_keys.iter().zip(&self.referenced) {
+            let read;
+            let referenced = if fk.table == self.schema.table_name {
+                referenced
+            } else {
+                read = read_table(&self.schema.folder_path, &fk.table)?
+                    .registers
+                    .iter()
+                    .map(|register| fk.referenced_values(register))
+                    .collect::<HashSet<Vec<String>>>();
+                &read
+            };
+
+            for register in changed {
+                let values = fk.values(register);
+                if values.iter().any(|value| value.is_empty()) {
+                    continue;
+                }
+                if !referenced.contains(&values) {
+                    return Err(fk.violation(&values));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn references_itself(&self) -> bool {
+        self.schema
+            .foreign_keys
+            .iter()
+            .any(|fk| fk.table == self.schema.table_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, ColumnDef, DefaultValue, Identity, Key, KeyKind, Schema};
+    use crate::{
+        errors::SqlError,
+        register::{Columns, Register},
+    };
+    use std::sync::Arc;
+
+    fn registers(lines: &[&str]) -> Vec<Register> {
+        let columns = Arc::new(Columns::new(vec![
+            String::from("id"),
+            String::from("email"),
+        ]));
+        lines
+            .iter()
+            .map(|line| Register::from_csv(line, &columns))
+            .collect()
+    }
+
+    #[test]
+    fn new_with_column_keys() {
+        let lines = vec![
+            String::from("id INTEGER PRIMARY KEY"),
+            String::from(""),
+            String::from("email UNIQUE"),
+        ];
+        let schema = Schema::new_from_lines(&lines).unwrap();
+
+        assert_eq!(
+            schema,
+            Schema {
+                columns: vec![
+                    ColumnDef {
+                        name: String::from("id"),
+                        data_type: Some(String::from("INTEGER")),
+                        not_null: false,
+                        default: None,
+                        identity: None,
+                    },
+                    ColumnDef {
+                        name: String::from("email"),
+                        data_type: None,
+                        not_null: false,
+                        default: None,
+                        identity: None,
+                    },
+                ],
+                keys: vec![
+                    Key {
+                        kind: KeyKind::Primary,
+                        columns: vec![String::from("id")],
+                    },
+                    Key {
+                        kind: KeyKind::Unique,
+                        columns: vec![String::from("email")],
+                    },
+                ],
+                checks: vec![],
+                foreign_keys: vec![],
+                table_name: String::new(),
+                folder_path: String::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn new_with_not_null_default_and_check() {
+        let lines = vec![
+            String::from("nombre TEXT NOT NULL"),
+            String::from("email DEFAULT 'sin email'"),
+            String::from("cantidad INTEGER DEFAULT 1 CHECK (cantidad > 0)"),
+        ];
+        let schema = Schema::new_from_lines(&lines).unwrap();
+
+        assert!(schema.columns[0].not_null);
+        assert_eq!(
+            schema.column("email").unwrap().default.as_ref(),
+            Some(&DefaultValue::Literal(String::from("sin email")))
+        );
+        assert_eq!(
+            schema.column("cantidad").unwrap().default.as_ref(),
+            Some(&DefaultValue::Literal(String::from("1")))
+        );
+        assert_eq!(schema.column("nombre").unwrap().default, None);
+        assert_eq!(schema.checks, vec![Check::new("cantidad > 0").unwrap()]);
+    }
+
+    #[test]
+    fn new_with_identity_and_nextval() {
+        let lines = vec![
+            String::from("id INTEGER PRIMARY KEY AUTOINCREMENT"),
+            String::from("codigo INTEGER GENERATED ALWAYS AS IDENTITY"),
+            String::from("numero INTEGER DEFAULT nextval('numeros_seq') NOT NULL"),
+        ];
+        let schema = Schema::new_from_lines(&lines).unwrap();
+
+        assert_eq!(schema.columns[0].identity, Some(Identity::ByDefault));
+        assert_eq!(schema.columns[1].identity, Some(Identity::Always));
+        assert_eq!(
+            schema.column("numero").unwrap().default.as_ref(),
+            Some(&DefaultValue::NextVal(String::from("numeros_seq")))
+        );
+        assert!(schema.columns[2].not_null);
+    }
+
+    #[test]
+    fn check_register_not_null() {
+        let schema = Schema::new_from_lines(&[String::from("email NOT NULL")]).unwrap();
+        let registers = registers(&["1,a@email.com", "2,"]);
+
+        assert_eq!(schema.check_register(&registers[0]), Ok(()));
+        assert_eq!(
+            schema.check_register(&registers[1]),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("NOT NULL (email)"),
+                value: String::from("''"),
+            })
+        );
+    }
+
+    #[test]
+    fn check_register_check() {
+        let schema = Schema::new_from_lines(&[String::from("CHECK (id > 1)")]).unwrap();
+        let registers = registers(&["1,a@email.com", "2,b@email.com", ",c@email.com"]);
+
+        assert_eq!(
+            schema.check_register(&registers[0]),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("CHECK (id > 1)"),
+                value: String::from("'1'"),
+            })
+        );
+        assert_eq!(schema.check_register(&registers[1]), Ok(()));
+        assert_eq!(schema.check_register(&registers[2]), Ok(()));
+    }
+
+    #[test]
+    fn new_with_table_keys() {
+        let lines = vec![String::from("PRIMARY KEY (id, email)")];
+        let schema = Schema::new_from_lines(&lines).unwrap();
+
+        assert_eq!(schema.keys[0].name(), "PRIMARY KEY (id, email)");
+    }
+
+    #[test]
+    fn new_with_two_primary_keys() {
+        let lines = vec![
+            String::from("id PRIMARY KEY"),
+            String::from("email PRIMARY KEY"),
+        ];
+
+        assert_eq!(Schema::new_from_lines(&lines), Err(SqlError::InvalidSyntax));
+    }
+
+    #[test]
+    fn new_with_foreign_keys() {
+        let lines = vec![
+            String::from("id_cliente INTEGER NOT NULL REFERENCES clientes (id) ON DELETE CASCADE"),
+            String::from("FOREIGN KEY (producto) REFERENCES productos (nombre) ON UPDATE SET NULL"),
+        ];
+        let schema = Schema::new_from_lines(&lines).unwrap();
+
+        assert!(schema.columns[0].not_null);
+        assert_eq!(
+            schema.foreign_keys[0].name(),
+            "FOREIGN KEY (id_cliente) REFERENCES clientes (id)"
+        );
+        assert_eq!(
+            schema.foreign_keys[1].name(),
+            "FOREIGN KEY (producto) REFERENCES productos (nombre)"
+        );
+    }
+
+    #[test]
+    fn open_without_schema_file() {
+        let schema = Schema::open("tablas", "testing").unwrap();
+        assert!(schema.keys.is_empty());
+        assert!(schema.foreign_keys.is_empty());
+        assert_eq!(schema.table_name, "testing");
+    }
+
+    #[test]
+    fn check_foreign_keys() {
+        let schema = Schema::open("tablas", "ordenes").unwrap();
+        let columns = Arc::new(Columns::new(vec![
+            String::from("id"),
+            String::from("id_cliente"),
+        ]));
+        let registers = vec![
+            Register::from_csv("201,1", &columns),
+            Register::from_csv("202,", &columns),
+            Register::from_csv("203,99", &columns),
+        ];
+
+        assert_eq!(schema.check_foreign_keys(&registers, &[0, 1]), Ok(()));
+        assert_eq!(
+            schema.check_foreign_keys(&registers, &[2]),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("FOREIGN KEY (id_cliente) REFERENCES clientes (id)"),
+                value: String::from("'99'"),
+            })
+        );
+    }
+
+    #[test]
+    fn check_keys_duplicated_primary_key() {
+        let schema = Schema::new_from_lines(&[String::from("id PRIMARY KEY")]).unwrap();
+        let registers = registers(&["1,a@email.com", "2,b@email.com", "1,c@email.com"]);
+
+        assert_eq!(
+            schema.check_keys(&registers, &[2]),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("PRIMARY KEY (id)"),
+                value: String::from("'1'"),
+            })
+        );
+        assert_eq!(schema.check_keys(&registers, &[1]), Ok(()));
+    }
+
+    #[test]
+    fn check_keys_empty_values() {
+        let schema =
+            Schema::new_from_lines(&[String::from("id PRIMARY KEY"), String::from("email UNIQUE")])
+                .unwrap();
+        let registers = registers(&["1,", "2,", ","]);
+
+        assert_eq!(schema.check_keys(&registers, &[1]), Ok(()));
+        assert_eq!(
+            schema.check_keys(&registers, &[2]),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("PRIMARY KEY (id)"),
+                value: String::from("''"),
+            })
+        );
+    }
+
+    #[test]
+    fn check_keys_between_changed_registers() {
+        let schema = Schema::new_from_lines(&[String::from("UNIQUE (id, email)")]).unwrap();
+        let registers = registers(&["1,a@email.com", "1,b@email.com", "1,b@email.com"]);
+
+        assert_eq!(
+            schema.check_keys(&registers, &[1, 2]),
+            Err(SqlError::ConstraintViolation {
+                constraint: String::from("UNIQUE (id, email)"),
+                value: String::from("('1', 'b@email.com')"),
+            })
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{errors::SqlError, output::Output, query_result::QueryResult, session::Session};
+use std::fmt;
+
+/// Enum for what a script does when one of its statements fails.
+///
+/// - `Stop`: the statements after the failed one are not executed.
+/// - `Continue`: the error is shown and the next statements are executed.
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OnError {
+    Stop,
+    Continue,
+}
+
+/// Struct that represents the summary of the execution of a script.
+///
+/// # Fields
+///
+/// * `executed` - The number of statements that were executed successfully.
+/// * `failed` - The number of statements that failed.
+/// * `rows` - The number of rows returned by the queries of the script.
+/// * `affected` - The number of rows inserted, updated or deleted by the statements of the script.
+/// * `error` - The error of the first statement that failed, if any.
+///
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub executed: usize,
+    pub failed: usize,
+    pub rows: usize,
+    pub affected: usize,
+    pub error: Option<SqlError>,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} statements executed, {} failed, {} rows returned, {} rows affected",
+            self.executed, self.failed, self.rows, self.affected
+        )
+    }
+}
+
+/// Splits a script into the statements ended by `;`, and the text after the last one.
+///
+/// Semicolons inside string literals don't end a statement, and `--` and `/* */` comments are removed.
+fn scan(script: &str) -> (Vec<String>, String) {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = script.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\'' => {
+                in_quotes = !in_quotes;
+                current.push(char);
+            }
+            '-' if !in_quotes && chars.peek() == Some(&'-') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        current.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if !in_quotes && chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+                current.push(' ');
+            }
+            ';' if !in_quotes => {
+                if !current.trim().is_empty() {
+                    statements.push(current.trim().to_string());
+                }
+                current.clear();
+            }
+            _ => current.push(char),
+        }
+    }
+
+    (statements, current.trim().to_string())
+}
+
+/// Returns the statements of a script, ended by `;`, without their comments.
+///
+/// The text after the last `;` is returned as the last statement, if it is not empty.
+///
+/// # Examples
+///
+/// ```ignore
+/// let statements = split_statements("-- clientes\nINSERT INTO clientes (nombre) VALUES ('a;b'); SELECT * FROM clientes;");
+///
+/// assert_eq!(statements, vec!["INSERT INTO clientes (nombre) VALUES ('a;b')", "SELECT * FROM clientes"]);
+/// ```
+///
+pub fn split_statements(script: &str) -> Vec<String> {
+    let (mut statements, rest) = scan(script);
+    if !rest.is_empty() {
+        statements.push(rest);
+    }
+    statements
+}
+
+/// Returns whether every statement of the text is ended by `;`, ignoring comments and blank text.
+pub fn is_complete(script: &str) -> bool {
+    scan(script).1.is_empty()
+}
+
+/// Executes statements in order in the same session, showing their results in the standard output and their errors
+/// in the standard error.
+///
+/// Returns the summary of the execution.
+///
+/// # Arguments
+///
+/// * `session` - The session where the statements are executed.
+/// * `statements` - The statements to execute.
+/// * `on_error` - Whether the execution stops at the first statement that fails.
+/// * `output` - How the results of the queries are shown.
+///
+pub fn run_statements(
+    session: &mut Session,
+    statements: &[String],
+    on_error: OnError,
+    output: &Output,
+) -> Summary {
+    let mut summary = Summary::default();
+
+    for statement in statements {
+        let result = session.exec(statement).and_then(|result| {
+            let returned = matches!(result, QueryResult::Rows(_) | QueryResult::Stream(_));
+            Ok((returned, output.print(result)?))
+        });
+
+        match result {
+            Ok((returned, rows)) => {
+                summary.executed += 1;
+                match returned {
+                    true => summary.rows += rows,
+                    false => summary.affected += rows,
+                }
+            }
+            Err(e) => {
+                summary.failed += 1;
+                eprintln!("{}", e);
+                if summary.error.is_none() {
+                    summary.error = Some(e);
+                }
+                if on_error == OnError::Stop {
+                    break;
+                }
+            }
+        }
+    }
+
+    summary
+}
+
+/// Executes the statements of a script, separated by `;`, in the same session.
+///
+/// Returns the summary of the execution, see `run_statements`.
+pub fn run_script(
+    session: &mut Session,
+    script: &str,
+    on_error: OnError,
+    output: &Output,
+) -> Summary {
+    run_statements(session, &split_statements(script), on_error, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_complete, run_script, split_statements, OnError, Summary};
+    use crate::{errors::SqlError, output::Output, session::Session};
+    use std::fs;
+
+    #[test]
+    fn split_with_comments_and_strings() {
+        let script = "-- seed; data\nINSERT INTO t (a) VALUES ('x;y'); /* two;\nlines */ DELETE FROM t -- end\n;\nSELECT '--' FROM t";
+
+        assert_eq!(
+            split_statements(script),
+            vec![
+                "INSERT INTO t (a) VALUES ('x;y')",
+                "DELETE FROM t",
+                "SELECT '--' FROM t",
+            ]
+        );
+        assert!(is_complete("SELECT * FROM t; -- done\n"));
+        assert!(!is_complete("SELECT * FROM t WHERE a = ';'"));
+        assert!(!is_complete("SELECT 'a;"));
+    }
+
+    #[test]
+    fn run_stopping_and_continuing() {
+        let folder = std::env::temp_dir().join("rustic_sql_script_run");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id,nombre\n1,Juan\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+        let script = "INSERT INTO clientes (id, nombre) VALUES (2, 'Ana;Maria');\nINSERT INTO productos (id) VALUES (1);\nSELECT * FROM clientes;";
+
+        let mut session = Session::new(folder_path, false);
+        let output = Output::default();
+
+        let summary = run_script(&mut session, script, OnError::Stop, &output);
+        assert_eq!(
+            summary,
+            Summary {
+                executed: 1,
+                failed: 1,
+                rows: 0,
+                affected: 1,
+                error: Some(SqlError::InvalidTable),
+            }
+        );
+
+        let summary = run_script(&mut session, script, OnError::Continue, &output);
+        assert_eq!(
+            summary,
+            Summary {
+                executed: 2,
+                failed: 1,
+                rows: 3,
+                affected: 1,
+                error: Some(SqlError::InvalidTable),
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id,nombre\n1,Juan\n2,Ana;Maria\n2,Ana;Maria\n"
+        );
+        assert_eq!(
+            summary.to_string(),
+            "2 statements executed, 1 failed, 3 rows returned, 1 rows affected"
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{
+    errors::SqlError,
+    lock::TableLock,
+    utils::{find_file_in_folder, is_create, is_sequence, read_table, write_file},
+};
+use std::{fs, time::Duration};
+
+/// Struct that represents a sequence, a counter stored in the folder that generates unique numbers.
+///
+/// Each sequence is stored in a `<name>.sequence` file, so its values survive restarts.
+///
+/// # Fields
+///
+/// * `name` - The name of the sequence.
+/// * `next` - The value the next call to `nextval` returns.
+/// * `increment` - The amount added to the sequence on every call to `nextval`.
+///
+#[derive(Debug, PartialEq)]
+pub struct Sequence {
+    pub name: String,
+    pub next: i64,
+    pub increment: i64,
+}
+
+fn parse_number(tokens: &[String], i: &mut usize) -> Result<i64, SqlError> {
+    let mut sign = 1;
+    if tokens.get(*i).is_some_and(|t| t == "-") {
+        sign = -1;
+        *i += 1;
+    }
+    let number = tokens
+        .get(*i)
+        .ok_or(SqlError::InvalidSyntax)?
+        .parse::<i64>()
+        .map_err(|_| SqlError::InvalidSyntax)?;
+    *i += 1;
+
+    Ok(sign * number)
+}
+
+/// Returns the name of the sequence if the value is a call to `nextval`, for example `nextval('ordenes_seq')`.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_nextval("nextval('ordenes_seq')"), Some("ordenes_seq"));
+/// assert_eq!(parse_nextval("Laptop"), None);
+/// ```
+///
+pub fn parse_nextval(value: &str) -> Option<&str> {
+    let (function, argument) = value.split_once('(')?;
+    if !function.trim().eq_ignore_ascii_case("nextval") {
+        return None;
+    }
+    let name = argument.strip_suffix(')')?.trim().trim_matches('\'');
+
+    Some(name)
+}
+
+impl Sequence {
+    /// Creates and returns a new `Sequence` from the tokens of a `CREATE SEQUENCE` statement.
+    ///
+    /// The tokens should be in the following order: `CREATE`, `SEQUENCE`, `name`, followed by optional `START WITH value` and `INCREMENT BY value`.
+    /// If they are not specified, the sequence starts with 1 and increments by 1.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let tokens = tokens_from_query("CREATE SEQUENCE ordenes_seq START WITH 200 INCREMENT BY 10");
+    /// let sequence = Sequence::new_from_tokens(tokens).unwrap();
+    ///
+    /// assert_eq!(sequence, Sequence { name: "ordenes_seq".to_string(), next: 200, increment: 10 });
+    /// ```
+    ///
+    pub fn new_from_tokens(tokens: Vec<String>) -> Result<Self, SqlError> {
+        if tokens.len() < 3 || !is_create(&tokens[0]) || !is_sequence(&tokens[1]) {
+            return Err(SqlError::InvalidSyntax);
+        }
+        let name = tokens[2].to_string();
+        let mut next = 1;
+        let mut increment = 1;
+        let mut i = 3;
+
+        while i < tokens.len() {
+            let option = tokens[i].as_str();
+            i += 1;
+            if let ("START", Some("WITH")) | ("INCREMENT", Some("BY")) =
+                (option, tokens.get(i).map(|t| t.as_str()))
+            {
+                i += 1;
+            }
+            match option {
+                "START" => next = parse_number(&tokens, &mut i)?,
+                "INCREMENT" => increment = parse_number(&tokens, &mut i)?,
+                _ => return Err(SqlError::InvalidSyntax),
+            }
+        }
+
+        if increment == 0 {
+            return Err(SqlError::InvalidSyntax);
+        }
+
+        Ok(Self {
+            name,
+            next,
+            increment,
+        })
+    }
+
+    /// Stores a new sequence in the folder.
+    ///
+    /// Returns an error if a sequence with the same name already exists.
+    pub fn create(&self, folder_path: &str) -> Result<(), SqlError> {
+        if find_file_in_folder(folder_path, &(self.name.to_string() + ".sequence")) {
+            return Err(SqlError::InvalidSyntax);
+        }
+        self.write(folder_path)
+    }
+
+    /// Opens the sequence with the given name stored in the folder.
+    pub fn open(folder_path: &str, name: &str) -> Result<Self, SqlError> {
+        let file_name = name.to_string() + ".sequence";
+        if !find_file_in_folder(folder_path, &file_name) {
+            return Err(SqlError::InvalidTable);
+        }
+        let content = fs::read_to_string(folder_path.to_string() + "/" + &file_name)
+            .map_err(|_| SqlError::InvalidTable)?;
+
+        let values: Vec<i64> = content
+            .lines()
+            .nth(1)
+            .ok_or(SqlError::InvalidTable)?
+            .split(',')
+            .map(|value| value.trim().parse::<i64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| SqlError::InvalidTable)?;
+
+        match values[..] {
+            [next, increment] => Ok(Self {
+                name: name.to_string(),
+                next,
+                increment,
+            }),
+            _ => Err(SqlError::InvalidTable),
+        }
+    }
+
+    /// Removes the sequence with the given name from the folder.
+    pub fn drop(folder_path: &str, name: &str) -> Result<(), SqlError> {
+        let file_name = name.to_string() + ".sequence";
+        if !find_file_in_folder(folder_path, &file_name) {
+            return Err(SqlError::InvalidTable);
+        }
+        fs::remove_file(folder_path.to_string() + "/" + &file_name).map_err(|_| SqlError::Error)
+    }
+
+    /// Advances the sequence with the given name and returns its value, storing the new state in the folder.
+    ///
+    /// The sequence is locked exclusively while it's read and stored, so two statements never get the same value.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let first = Sequence::next_value("tablas", "ordenes_seq", Duration::from_secs(5)).unwrap();
+    /// let second = Sequence::next_value("tablas", "ordenes_seq", Duration::from_secs(5)).unwrap();
+    ///
+    /// assert_eq!(second, first + 1);
+    /// ```
+    ///
+    pub fn next_value(folder_path: &str, name: &str, timeout: Duration) -> Result<i64, SqlError> {
+        let _lock =
+            TableLock::exclusive_file(folder_path, &(name.to_string() + ".sequence"), timeout)?;
+        Self::advance(folder_path, name)
+    }
+
+    fn advance(folder_path: &str, name: &str) -> Result<i64, SqlError> {
+        let mut sequence = Self::open(folder_path, name)?;
+        let value = sequence.next;
+
+        sequence.next += sequence.increment;
+        sequence.write(folder_path)?;
+
+        Ok(value)
+    }
+
+    /// Advances the sequence that backs an identity column and returns its value.
+    ///
+    /// The sequence is named `<table>_<column>_seq`. The first time it is used it is created,
+    /// starting after the greatest numeric value already stored in the column.
+    ///
+    pub fn next_identity(
+        folder_path: &str,
+        table_name: &str,
+        column: &str,
+        timeout: Duration,
+    ) -> Result<i64, SqlError> {
+        let name = format!("{}_{}_seq", table_name, column);
+        let _lock =
+            TableLock::exclusive_file(folder_path, &(name.to_string() + ".sequence"), timeout)?;
+
+        if !find_file_in_folder(folder_path, &(name.to_string() + ".sequence")) {
+            let table = read_table(folder_path, table_name)?;
+            let max = table
+                .registers
+                .iter()
+                .filter_map(|register| register.get(column)?.to_csv().parse::<i64>().ok())
+                .max()
+                .unwrap_or(0);
+
+            Self {
+                name: name.to_string(),
+                next: max + 1,
+                increment: 1,
+            }
+            .create(folder_path)?;
+        }
+
+        Self::advance(folder_path, &name)
+    }
+
+    fn write(&self, folder_path: &str) -> Result<(), SqlError> {
+        let lines = vec![
+            String::from("next,increment"),
+            format!("{},{}", self.next, self.increment),
+        ];
+
+        write_file(folder_path, &(self.name.to_string() + ".sequence"), &lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_nextval, Sequence};
+    use crate::{errors::SqlError, tokens::tokens_from_query};
+    use std::{fs, thread, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn new_from_tokens() {
+        let tokens =
+            tokens_from_query("CREATE SEQUENCE ordenes_seq START WITH 200 INCREMENT BY -2");
+        let sequence = Sequence::new_from_tokens(tokens).unwrap();
+
+        assert_eq!(
+            sequence,
+            Sequence {
+                name: String::from("ordenes_seq"),
+                next: 200,
+                increment: -2,
+            }
+        );
+    }
+
+    #[test]
+    fn new_from_tokens_with_defaults() {
+        let tokens = tokens_from_query("CREATE SEQUENCE ordenes_seq");
+        let sequence = Sequence::new_from_tokens(tokens).unwrap();
+
+        assert_eq!(sequence.next, 1);
+        assert_eq!(sequence.increment, 1);
+    }
+
+    #[test]
+    fn new_from_invalid_tokens() {
+        let tokens = tokens_from_query("CREATE SEQUENCE ordenes_seq START WITH hola");
+        assert_eq!(
+            Sequence::new_from_tokens(tokens),
+            Err(SqlError::InvalidSyntax)
+        );
+    }
+
+    #[test]
+    fn nextval() {
+        assert_eq!(parse_nextval("nextval('ordenes_seq')"), Some("ordenes_seq"));
+        assert_eq!(parse_nextval("NEXTVAL(ordenes_seq)"), Some("ordenes_seq"));
+        assert_eq!(parse_nextval("Laptop"), None);
+    }
+
+    #[test]
+    fn next_value_persists() {
+        let folder = std::env::temp_dir().join("rustic_sql_sequence_next_value");
+        fs::create_dir_all(&folder).unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let tokens = tokens_from_query("CREATE SEQUENCE seq START WITH 10 INCREMENT BY 5");
+        Sequence::new_from_tokens(tokens)
+            .unwrap()
+            .create(folder_path)
+            .unwrap();
+
+        assert_eq!(Sequence::next_value(folder_path, "seq", TIMEOUT), Ok(10));
+        assert_eq!(Sequence::next_value(folder_path, "seq", TIMEOUT), Ok(15));
+        assert_eq!(Sequence::open(folder_path, "seq").unwrap().next, 20);
+
+        Sequence::drop(folder_path, "seq").unwrap();
+        assert_eq!(
+            Sequence::next_value(folder_path, "seq", TIMEOUT),
+            Err(SqlError::InvalidTable)
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn next_value_from_many_threads() {
+        let folder = std::env::temp_dir().join("rustic_sql_sequence_threads");
+        fs::create_dir_all(&folder).unwrap();
+        let folder_path = folder.to_str().unwrap().to_string();
+
+        let tokens = tokens_from_query("CREATE SEQUENCE seq");
+        Sequence::new_from_tokens(tokens)
+            .unwrap()
+            .create(&folder_path)
+            .unwrap();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let folder_path = folder_path.to_string();
+                thread::spawn(move || {
+                    (0..25)
+                        .map(|_| Sequence::next_value(&folder_path, "seq", TIMEOUT).unwrap())
+                        .collect::<Vec<i64>>()
+                })
+            })
+            .collect();
+        let mut values: Vec<i64> = threads
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+        values.sort();
+
+        assert_eq!(values, (1..=100).collect::<Vec<i64>>());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn next_identity_starts_after_max() {
+        let folder = std::env::temp_dir().join("rustic_sql_sequence_next_identity");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(
+            folder.join("ordenes.csv"),
+            "id,producto\n7,Laptop\n3,Mouse\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        assert_eq!(
+            Sequence::next_identity(folder_path, "ordenes", "id", TIMEOUT),
+            Ok(8)
+        );
+        assert_eq!(
+            Sequence::next_identity(folder_path, "ordenes", "id", TIMEOUT),
+            Ok(9)
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
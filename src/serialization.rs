@@ -0,0 +1,639 @@
+use crate::{errors::SqlError, row::Row, value::Value};
+use serde::{
+    de::{self, value::StringDeserializer, DeserializeOwned, IntoDeserializer, MapAccess, Visitor},
+    forward_to_deserialize_any,
+    ser::{self, Impossible, Serialize, SerializeStruct},
+};
+use std::vec;
+
+impl de::Error for SqlError {
+    fn custom<T: std::fmt::Display>(_msg: T) -> Self {
+        SqlError::InvalidColumn
+    }
+}
+
+impl ser::Error for SqlError {
+    fn custom<T: std::fmt::Display>(_msg: T) -> Self {
+        SqlError::InvalidColumn
+    }
+}
+
+/// Deserializes a row into a struct whose fields are named like the columns of the row.
+///
+/// Empty values are `None` for `Option` fields, integers and text can be read into numbers, strings, booleans
+/// and unit enum variants, and the columns without a field are ignored.
+///
+/// Returns an `InvalidColumn` error if a field has no column, or its value can't be read into the type of the field.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_sql::{from_row, Database, SqlError};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Cliente {
+///     id: i64,
+///     nombre: String,
+///     email: Option<String>,
+/// }
+///
+/// # fn main() -> Result<(), SqlError> {
+/// let mut database = Database::open("tablas")?;
+/// let row = database.query("SELECT * FROM clientes WHERE id = 1")?.next().unwrap()?;
+/// let cliente: Cliente = from_row(row)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub fn from_row<T: DeserializeOwned>(row: Row) -> Result<T, SqlError> {
+    T::deserialize(RowDeserializer {
+        columns: row
+            .columns
+            .iter()
+            .map(|c| c.name.to_string())
+            .collect::<Vec<String>>()
+            .into_iter(),
+        values: row.values.into_iter(),
+        value: None,
+    })
+}
+
+/// Serializes a struct into the columns and values of a register, named like the fields of the struct.
+///
+/// The fields that are `None` are left out, so they take the default of the column when the register is inserted.
+///
+/// Returns an `InvalidColumn` error if the value is not a struct, or a field holds a sequence, a map or another struct.
+pub fn to_register<T: Serialize>(value: &T) -> Result<Vec<(String, Value)>, SqlError> {
+    value.serialize(RegisterSerializer)
+}
+
+struct RowDeserializer {
+    columns: vec::IntoIter<String>,
+    values: vec::IntoIter<Value>,
+    value: Option<Value>,
+}
+
+impl<'de> de::Deserializer<'de> for RowDeserializer {
+    type Error = SqlError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        visitor.visit_map(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> MapAccess<'de> for RowDeserializer {
+    type Error = SqlError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SqlError> {
+        match (self.columns.next(), self.values.next()) {
+            (Some(column), Some(value)) => {
+                self.value = Some(value);
+                let column: StringDeserializer<SqlError> = column.into_deserializer();
+                seed.deserialize(column).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SqlError> {
+        let value = self.value.take().ok_or(SqlError::InvalidColumn)?;
+        seed.deserialize(ValueDeserializer(value))
+    }
+}
+
+struct ValueDeserializer(Value);
+
+impl ValueDeserializer {
+    fn parse<T: std::str::FromStr>(&self) -> Result<T, SqlError> {
+        self.0
+            .to_string()
+            .parse()
+            .map_err(|_| SqlError::InvalidColumn)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = SqlError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            Value::Integer(integer) => visitor.visit_i64(integer),
+            Value::Text(text) => visitor.visit_string(text),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        visitor.visit_bool(self.parse()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        visitor.visit_f64(self.parse()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        visitor.visit_f64(self.parse()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        visitor.visit_u64(self.parse()?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        visitor.visit_string(self.0.to_string())
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SqlError> {
+        visitor.visit_string(self.0.to_string())
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SqlError> {
+        let variant: StringDeserializer<SqlError> = self.0.to_string().into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SqlError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u128 char bytes byte_buf unit unit_struct
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct RegisterSerializer;
+
+impl ser::Serializer for RegisterSerializer {
+    type Ok = Vec<(String, Value)>;
+    type Error = SqlError;
+    type SerializeSeq = Impossible<Self::Ok, SqlError>;
+    type SerializeTuple = Impossible<Self::Ok, SqlError>;
+    type SerializeTupleStruct = Impossible<Self::Ok, SqlError>;
+    type SerializeTupleVariant = Impossible<Self::Ok, SqlError>;
+    type SerializeMap = Impossible<Self::Ok, SqlError>;
+    type SerializeStruct = FieldsSerializer;
+    type SerializeStructVariant = Impossible<Self::Ok, SqlError>;
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<FieldsSerializer, SqlError> {
+        Ok(FieldsSerializer(Vec::with_capacity(len)))
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, SqlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, SqlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+}
+
+struct FieldsSerializer(Vec<(String, Value)>);
+
+impl SerializeStruct for FieldsSerializer {
+    type Ok = Vec<(String, Value)>;
+    type Error = SqlError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SqlError> {
+        match value.serialize(ValueSerializer)? {
+            Value::Null => {}
+            value => self.0.push((key.to_string(), value)),
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, SqlError> {
+        Ok(self.0)
+    }
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SqlError;
+    type SerializeSeq = Impossible<Value, SqlError>;
+    type SerializeTuple = Impossible<Value, SqlError>;
+    type SerializeTupleStruct = Impossible<Value, SqlError>;
+    type SerializeTupleVariant = Impossible<Value, SqlError>;
+    type SerializeMap = Impossible<Value, SqlError>;
+    type SerializeStruct = Impossible<Value, SqlError>;
+    type SerializeStructVariant = Impossible<Value, SqlError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SqlError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SqlError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SqlError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SqlError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SqlError> {
+        Ok(Value::Integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SqlError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SqlError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SqlError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SqlError> {
+        Ok(Value::from_csv(&v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SqlError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SqlError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SqlError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SqlError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Value, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_none(self) -> Result<Value, SqlError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, SqlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SqlError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, SqlError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SqlError> {
+        Ok(Value::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, SqlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SqlError> {
+        Err(SqlError::InvalidColumn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_row, to_register};
+    use crate::{
+        errors::SqlError,
+        row::{Column, Row},
+        value::Value,
+    };
+    use serde::{Deserialize, Serialize};
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Estado {
+        Activo,
+        Inactivo,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Cliente {
+        id: Option<u32>,
+        nombre: String,
+        telefono: String,
+        saldo: f64,
+        vip: bool,
+        estado: Estado,
+    }
+
+    fn new_row(columns: &[&str], values: Vec<Value>) -> Row {
+        let columns: Vec<Column> = columns
+            .iter()
+            .map(|name| Column {
+                name: name.to_string(),
+            })
+            .collect();
+        Row {
+            columns: Arc::from(columns),
+            values,
+        }
+    }
+
+    #[test]
+    fn deserialize_by_column_name() {
+        let row = new_row(
+            &[
+                "estado", "saldo", "email", "vip", "telefono", "nombre", "id",
+            ],
+            vec![
+                Value::from("activo"),
+                Value::from("10.5"),
+                Value::from("ana@email.com"),
+                Value::from("true"),
+                Value::Integer(1234),
+                Value::from("Ana"),
+                Value::Null,
+            ],
+        );
+
+        assert_eq!(
+            from_row::<Cliente>(row),
+            Ok(Cliente {
+                id: None,
+                nombre: String::from("Ana"),
+                telefono: String::from("1234"),
+                saldo: 10.5,
+                vip: true,
+                estado: Estado::Activo,
+            })
+        );
+
+        let missing = new_row(
+            &["id", "nombre"],
+            vec![Value::Integer(1), Value::from("Ana")],
+        );
+        assert_eq!(from_row::<Cliente>(missing), Err(SqlError::InvalidColumn));
+    }
+
+    #[test]
+    fn serialize_fields_as_columns() {
+        let cliente = Cliente {
+            id: None,
+            nombre: String::from("Sean"),
+            telefono: String::from("0800"),
+            saldo: 0.25,
+            vip: false,
+            estado: Estado::Inactivo,
+        };
+
+        assert_eq!(
+            to_register(&cliente),
+            Ok(vec![
+                (String::from("nombre"), Value::from("Sean")),
+                (String::from("telefono"), Value::from("0800")),
+                (String::from("saldo"), Value::from("0.25")),
+                (String::from("vip"), Value::from("false")),
+                (String::from("estado"), Value::from("inactivo")),
+            ])
+        );
+        assert_eq!(to_register(&vec![1, 2]), Err(SqlError::InvalidColumn));
+    }
+}
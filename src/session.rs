@@ -0,0 +1,187 @@
+use crate::{
+    errors::SqlError,
+    exec_query, exec_statement,
+    query_result::QueryResult,
+    settings::Settings,
+    statement::Statement,
+    tokens::tokens_from_query,
+    transaction::Transaction,
+    utils::{is_create, is_index, is_on},
+};
+
+/// Struct that represents a session over the tables of a folder, where the statements between BEGIN and COMMIT
+/// are executed inside a transaction.
+///
+/// # Fields
+///
+/// * `folder_path` - The path to the folder where the tables are stored.
+/// * `transaction` - The open transaction of the session, if there is one.
+/// * `readonly` - Whether the session only accepts queries that don't change the tables.
+/// * `settings` - The settings the statements of the session are executed with.
+///
+#[derive(Debug)]
+pub struct Session {
+    pub folder_path: String,
+    pub transaction: Option<Transaction>,
+    pub readonly: bool,
+    pub settings: Settings,
+}
+
+impl Session {
+    /// Creates a new session over the tables of the folder, without an open transaction and with the default settings.
+    pub fn new(folder_path: &str, readonly: bool) -> Self {
+        Self {
+            folder_path: folder_path.to_string(),
+            transaction: None,
+            readonly,
+            settings: Settings::default(),
+        }
+    }
+
+    /// Executes a statement of the session.
+    ///
+    /// BEGIN, COMMIT, ROLLBACK, SAVEPOINT, ROLLBACK TO and RELEASE control the transaction. Any other statement is executed
+    /// against the tables of the open transaction, or against the tables of the folder if there is none.
+    ///
+    /// A read-only session rejects every statement but SELECT with a `ReadOnly` error.
+    ///
+    /// Returns the result of the statement, see `exec_query`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut session = Session::new("tablas", false);
+    /// session.exec("BEGIN").unwrap();
+    /// session.exec("DELETE FROM ordenes WHERE id = 101").unwrap();
+    /// session.exec("ROLLBACK").unwrap();
+    /// ```
+    ///
+    pub fn exec(&mut self, query: &str) -> Result<QueryResult, SqlError> {
+        let tokens = tokens_from_query(query);
+        let words: Vec<&str> = tokens.iter().map(|token| token.as_str()).collect();
+
+        if self.readonly && words.first() != Some(&"SELECT") {
+            return Err(SqlError::ReadOnly);
+        }
+
+        match (words.as_slice(), self.transaction.as_mut()) {
+            (["BEGIN"] | ["BEGIN", "TRANSACTION"], None) => {
+                self.transaction = Some(Transaction::begin(
+                    &self.folder_path,
+                    self.settings.lock_timeout,
+                )?);
+            }
+            (["COMMIT"], Some(_)) => {
+                if let Some(transaction) = self.transaction.take() {
+                    transaction.commit()?;
+                }
+            }
+            (["ROLLBACK"], Some(_)) => self.transaction = None,
+            (["SAVEPOINT", name], Some(open)) => open.savepoint(name)?,
+            (["ROLLBACK", "TO", name] | ["ROLLBACK", "TO", "SAVEPOINT", name], Some(open)) => {
+                open.rollback_to(name)?;
+            }
+            (["RELEASE", name] | ["RELEASE", "SAVEPOINT", name], Some(open)) => {
+                open.release(name)?
+            }
+            (["BEGIN" | "COMMIT" | "ROLLBACK" | "SAVEPOINT" | "RELEASE", ..], _) => {
+                return Err(SqlError::InvalidSyntax);
+            }
+            (_, Some(open)) => {
+                prepare_query(open, &tokens)?;
+                return exec_query(&open.shadow_path, query, &self.settings);
+            }
+            (_, None) => return exec_query(&self.folder_path, query, &self.settings),
+        }
+
+        Ok(QueryResult::Empty)
+    }
+
+    /// Executes a statement that was already parsed, like the ones of a `PreparedStatement`, against the tables of
+    /// the open transaction, or against the tables of the folder if there is none.
+    ///
+    /// A read-only session rejects every statement but SELECT with a `ReadOnly` error.
+    pub(crate) fn exec_statement(&mut self, statement: Statement) -> Result<QueryResult, SqlError> {
+        if self.readonly && !matches!(statement, Statement::Select(_)) {
+            return Err(SqlError::ReadOnly);
+        }
+
+        match &mut self.transaction {
+            Some(open) => {
+                prepare_statement(open, &statement)?;
+                exec_statement(&open.shadow_path, statement, &self.settings)
+            }
+            None => exec_statement(&self.folder_path, statement, &self.settings),
+        }
+    }
+}
+
+/// Copies the tables used by a statement to the shadow folder of the transaction, before it's executed against it.
+fn prepare_statement(transaction: &mut Transaction, statement: &Statement) -> Result<(), SqlError> {
+    let changes = !matches!(statement, Statement::Select(_));
+    transaction.prepare(statement.table_name(), changes)
+}
+
+/// Copies the tables used by a query to the shadow folder of the transaction, before it's executed against it.
+///
+/// Queries that can't be parsed are left to fail when they are executed.
+fn prepare_query(transaction: &mut Transaction, tokens: &[String]) -> Result<(), SqlError> {
+    let words: Vec<&str> = tokens.iter().map(|token| token.as_str()).collect();
+
+    match words.as_slice() {
+        [create, index, _, on, table, _] if is_create(create) && is_index(index) && is_on(on) => {
+            transaction.prepare(table, false)
+        }
+        ["DROP", index, name] if is_index(index) => transaction.prepare_index(name),
+        ["CREATE" | "DROP", ..] => Ok(()),
+        _ => match Statement::new_from_tokens(tokens.to_vec()) {
+            Ok(statement) => prepare_statement(transaction, &statement),
+            Err(_) => Ok(()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use crate::{errors::SqlError, query_result::QueryResult};
+    use std::fs;
+
+    #[test]
+    fn readonly_and_transactions() {
+        let folder = std::env::temp_dir().join("rustic_sql_session_readonly");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id,nombre\n1,Juan\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let mut session = Session::new(folder_path, true);
+        let Ok(QueryResult::Stream(result)) = session.exec("SELECT nombre FROM clientes") else {
+            panic!("SELECT should return rows");
+        };
+        assert_eq!(result.columns, vec!["nombre"]);
+        assert_eq!(result.count(), 1);
+        assert_eq!(
+            session.exec("DELETE FROM clientes"),
+            Err(SqlError::ReadOnly)
+        );
+        assert_eq!(session.exec("BEGIN"), Err(SqlError::ReadOnly));
+
+        let mut session = Session::new(folder_path, false);
+        assert_eq!(session.exec("COMMIT"), Err(SqlError::InvalidSyntax));
+        assert_eq!(session.exec("BEGIN"), Ok(QueryResult::Empty));
+        assert_eq!(
+            session.exec("DELETE FROM clientes"),
+            Ok(QueryResult::Affected {
+                command: String::from("DELETE"),
+                rows: 1
+            })
+        );
+        session.exec("ROLLBACK").unwrap();
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id,nombre\n1,Juan\n"
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+/// Struct that represents the settings the statements of a session are executed with.
+///
+/// Each `Database` keeps its own settings, so two databases opened in the same process don't change each other's.
+///
+/// # Fields
+///
+/// * `lock_timeout` - How long a statement waits for the lock of a table before failing with a `Locked` error. 5 seconds by default.
+/// * `threads` - How many threads evaluate the `WHERE` clause of a query over a large table. 1 by default.
+/// * `sort_memory` - How many bytes of registers an `ORDER BY` sorts in memory before spilling them to temporary files. 64 MiB by default.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    pub lock_timeout: Duration,
+    pub threads: usize,
+    pub sort_memory: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            lock_timeout: Duration::from_secs(5),
+            threads: 1,
+            sort_memory: 64 * 1024 * 1024,
+        }
+    }
+}
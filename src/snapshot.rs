@@ -0,0 +1,333 @@
+use crate::{
+    errors::SqlError,
+    lock::TableLock,
+    utils::{find_file_in_folder, write_file},
+};
+use std::{
+    collections::BTreeSet,
+    fs::{self, File, Metadata},
+    io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Take},
+    time::Duration,
+};
+
+/// Struct that represents a consistent version of a table, read without blocking the statements that change it.
+///
+/// Tables are changed by appending registers to their file or by replacing the file with a new one, so an open file
+/// always holds a version of the table, and a replaced file is kept by the system until its last reader closes it.
+/// After every change, the writer publishes the identifier of the file and its committed length in a hidden
+/// `.<table>.version` file. While a writer holds the table, readers only read up to the published length,
+/// so they never see a register that is being appended.
+///
+/// # Fields
+///
+/// * `id` - The identifier of the table file, which changes every time the file is replaced.
+/// * `len` - The length of the version, in bytes.
+/// * `reader` - A reader over the version.
+///
+#[derive(Debug)]
+pub struct Snapshot {
+    pub id: Option<u64>,
+    pub len: u64,
+    reader: BufReader<Take<File>>,
+}
+
+/// Returns the identifier of a file, which changes when the file is replaced, or `None` if the system doesn't provide one.
+#[cfg(unix)]
+pub fn file_id(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+pub fn file_id(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+/// Reads from the given offset of a file, without moving the position shared by the handles of the file.
+#[cfg(unix)]
+fn read_at(file: &mut File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+    file.read_at(buf, offset)
+}
+
+/// Reads from the given offset of a file, moving its position, so only one handle of the file can be read at a time.
+#[cfg(not(unix))]
+fn read_at(file: &mut File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.read(buf)
+}
+
+/// Returns the offset where the first register that starts at or after the given offset begins, or the end if there is none.
+fn register_start(file: &mut File, offset: u64, end: u64) -> Result<u64, SqlError> {
+    let mut position = offset - 1;
+    let mut buf = [0; 4096];
+
+    while position < end {
+        let len = buf.len().min((end - position) as usize);
+        let read = read_at(file, &mut buf[..len], position).map_err(|_| SqlError::Error)?;
+        if read == 0 {
+            break;
+        }
+        if let Some(idx) = buf[..read].iter().position(|byte| *byte == b'\n') {
+            return Ok(position + idx as u64 + 1);
+        }
+        position += read as u64;
+    }
+
+    Ok(end)
+}
+
+/// Struct that represents a range of the registers of a version of a table, read with its own handle of the file,
+/// so the chunks of a version can be read at the same time by different threads.
+///
+/// # Fields
+///
+/// * `file` - The handle of the table file.
+/// * `position` - The offset of the next byte to read.
+/// * `end` - The offset where the chunk ends, which is the start of a register or the end of the version.
+///
+#[derive(Debug)]
+pub struct Chunk {
+    file: File,
+    position: u64,
+    end: u64,
+}
+
+impl Read for Chunk {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min((self.end - self.position) as usize);
+        let read = read_at(&mut self.file, &mut buf[..len], self.position)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+fn version_path(folder_path: &str, table_name: &str) -> String {
+    format!("{}/.{}.version", folder_path, table_name)
+}
+
+fn read_version(folder_path: &str, table_name: &str) -> Option<(u64, u64)> {
+    let content = fs::read_to_string(version_path(folder_path, table_name)).ok()?;
+    let (id, len) = content.lines().next()?.split_once(',')?;
+
+    Some((id.parse().ok()?, len.parse().ok()?))
+}
+
+fn open_table_file(folder_path: &str, table_name: &str) -> Result<(File, Metadata), SqlError> {
+    let path = folder_path.to_string() + "/" + table_name + ".csv";
+    let file = File::open(path).map_err(|_| SqlError::InvalidTable)?;
+    let metadata = file.metadata().map_err(|_| SqlError::InvalidTable)?;
+
+    Ok((file, metadata))
+}
+
+/// Publishes the current content of the table as its committed version, so readers can see it.
+///
+/// Must be called by the writer holding the exclusive lock of the table, after every change to the table file.
+pub fn publish(folder_path: &str, table_name: &str) -> Result<(), SqlError> {
+    let path = folder_path.to_string() + "/" + table_name + ".csv";
+    let metadata = fs::metadata(path).map_err(|_| SqlError::InvalidTable)?;
+
+    let Some(id) = file_id(&metadata) else {
+        return Ok(());
+    };
+    let lines = vec![format!("{},{}", id, metadata.len())];
+    write_file(folder_path, &format!(".{}.version", table_name), &lines)
+}
+
+impl Snapshot {
+    /// Opens the latest committed version of the table.
+    ///
+    /// If no writer holds the table, the whole file is read. Otherwise the version published by the last writer is read,
+    /// without waiting for the current one. Only if that version can't be found in the file, for example because
+    /// the file was just replaced, it waits for the writer to release the table.
+    ///
+    pub fn open(folder_path: &str, table_name: &str, timeout: Duration) -> Result<Self, SqlError> {
+        if !find_file_in_folder(folder_path, &(table_name.to_string() + ".csv")) {
+            return Err(SqlError::InvalidTable);
+        }
+
+        if let Some(_lock) = TableLock::try_shared(folder_path, table_name)? {
+            let (file, metadata) = open_table_file(folder_path, table_name)?;
+            return Ok(Self::new(file, &metadata, metadata.len()));
+        }
+
+        if let Some((id, len)) = read_version(folder_path, table_name) {
+            let (file, metadata) = open_table_file(folder_path, table_name)?;
+            if file_id(&metadata) == Some(id) && len <= metadata.len() {
+                return Ok(Self::new(file, &metadata, len));
+            }
+        }
+
+        let _lock = TableLock::shared(folder_path, table_name, timeout)?;
+        let (file, metadata) = open_table_file(folder_path, table_name)?;
+        Ok(Self::new(file, &metadata, metadata.len()))
+    }
+
+    fn new(file: File, metadata: &Metadata, len: u64) -> Self {
+        Self {
+            id: file_id(metadata),
+            len,
+            reader: BufReader::new(file.take(len)),
+        }
+    }
+
+    /// Reads the header of the version and the registers stored at the given offsets, in file order.
+    ///
+    /// Returns a reader with the same format as the table file, holding only those registers.
+    /// The offsets past the end of the version belong to registers appended later, and are skipped.
+    ///
+    pub fn lines_at(mut self, offsets: &BTreeSet<u64>) -> Result<Cursor<Vec<u8>>, SqlError> {
+        let mut content = String::new();
+        self.reader
+            .read_line(&mut content)
+            .map_err(|_| SqlError::Error)?;
+
+        let mut file = self.reader.into_inner().into_inner();
+
+        for offset in offsets.range(..self.len) {
+            file.seek(SeekFrom::Start(*offset))
+                .map_err(|_| SqlError::Error)?;
+            let mut line = String::new();
+            BufReader::new((&mut file).take(self.len - offset))
+                .read_line(&mut line)
+                .map_err(|_| SqlError::Error)?;
+            if !line.ends_with('\n') {
+                line.push('\n');
+            }
+            content.push_str(&line);
+        }
+
+        Ok(Cursor::new(content.into_bytes()))
+    }
+
+    /// Reads the header of the version and splits its registers in up to the given number of chunks of about the same size.
+    ///
+    /// Every chunk starts and ends on the boundary of a register, so each register is read by exactly one chunk,
+    /// and the chunks are returned in file order. The chunks can only be read at the same time on Unix systems,
+    /// elsewhere the registers are returned in a single chunk.
+    ///
+    pub fn split(mut self, count: usize) -> Result<(String, Vec<Chunk>), SqlError> {
+        let mut header = String::new();
+        self.reader
+            .read_line(&mut header)
+            .map_err(|_| SqlError::Error)?;
+
+        let start = header.len() as u64;
+        let count = if cfg!(unix) { count.max(1) as u64 } else { 1 };
+        let mut file = self.reader.into_inner().into_inner();
+
+        let mut bounds = vec![start];
+        for idx in 1..count {
+            let offset = start + (self.len - start) * idx / count;
+            let bound = register_start(&mut file, offset.max(start + 1), self.len)?;
+            if bound > bounds[bounds.len() - 1] && bound < self.len {
+                bounds.push(bound);
+            }
+        }
+        bounds.push(self.len);
+
+        let mut chunks = Vec::new();
+        for range in bounds.windows(2).filter(|range| range[0] < range[1]) {
+            chunks.push(Chunk {
+                file: file.try_clone().map_err(|_| SqlError::Error)?,
+                position: range[0],
+                end: range[1],
+            });
+        }
+
+        Ok((header.trim_end_matches(['\n', '\r']).to_string(), chunks))
+    }
+}
+
+impl Read for Snapshot {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl BufRead for Snapshot {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{publish, Snapshot};
+    use crate::lock::TableLock;
+    use std::{
+        collections::BTreeSet,
+        fs::{self, OpenOptions},
+        io::{BufRead, Read, Write},
+        time::Duration,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn reads_published_version_while_locked() {
+        let folder = std::env::temp_dir().join("rustic_sql_snapshot_version");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("ordenes.csv"), "id\n101\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+        publish(folder_path, "ordenes").unwrap();
+
+        let lock = TableLock::exclusive(folder_path, "ordenes", Duration::ZERO).unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(folder.join("ordenes.csv"))
+            .unwrap();
+        write!(file, "102\n10").unwrap();
+
+        let lines: Vec<String> = Snapshot::open(folder_path, "ordenes", TIMEOUT)
+            .unwrap()
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, vec!["id", "101"]);
+
+        let snapshot = Snapshot::open(folder_path, "ordenes", TIMEOUT).unwrap();
+        let mut content = String::new();
+        snapshot
+            .lines_at(&BTreeSet::from([3, 7]))
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "id\n101\n");
+
+        writeln!(file, "3").unwrap();
+        publish(folder_path, "ordenes").unwrap();
+        drop(lock);
+
+        let mut content = String::new();
+        Snapshot::open(folder_path, "ordenes", TIMEOUT)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "id\n101\n102\n103\n");
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn keeps_replaced_version() {
+        let folder = std::env::temp_dir().join("rustic_sql_snapshot_replaced");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("ordenes.csv"), "id\n101\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let snapshot = Snapshot::open(folder_path, "ordenes", TIMEOUT).unwrap();
+        fs::write(folder.join("new.csv"), "id\n102\n").unwrap();
+        fs::rename(folder.join("new.csv"), folder.join("ordenes.csv")).unwrap();
+
+        let lines: Vec<String> = snapshot.lines().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["id", "101"]);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
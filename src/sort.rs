@@ -0,0 +1,431 @@
+use crate::{
+    clauses::orderby_sql::OrderBy,
+    errors::SqlError,
+    register::{Columns, Register},
+    utils::temp_file_path,
+    value::Value,
+};
+use std::{
+    cmp::{self, Reverse},
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Lines, Write},
+    mem,
+    sync::Arc,
+};
+
+/// The most runs merged at once, so merging many runs doesn't keep too many files open. Beyond it, the runs are
+/// merged in groups into longer runs, in as many passes as needed.
+const MAX_MERGE_RUNS: usize = 64;
+
+/// Returns an estimate of the memory used by a register, counting the register itself, the capacity of its values
+/// and the text they hold.
+fn register_size(register: &Register) -> usize {
+    let text: usize = register
+        .values
+        .iter()
+        .map(|value| value.as_text().map_or(0, str::len))
+        .sum();
+    mem::size_of::<Register>() + register.values.capacity() * mem::size_of::<Value>() + text
+}
+
+/// Struct that represents a run of sorted registers spilled to a hidden temporary file in the folder.
+///
+/// The file is removed when the run is dropped.
+///
+/// # Fields
+///
+/// * `path` - The path to the temporary file.
+/// * `lines` - The lines of the file not read yet, one per register, with the values in the order of the columns.
+///
+struct Run {
+    path: String,
+    lines: Lines<BufReader<File>>,
+}
+
+impl Run {
+    /// Writes the sorted registers to a new temporary file in the folder.
+    fn write(
+        folder_path: &str,
+        registers: impl Iterator<Item = Result<Register, SqlError>>,
+    ) -> Result<Self, SqlError> {
+        let path = temp_file_path(folder_path, "sort");
+
+        let result = (|| {
+            let mut file = BufWriter::new(File::create(&path).map_err(|_| SqlError::Error)?);
+            for register in registers {
+                writeln!(file, "{}", register?.to_csv()).map_err(|_| SqlError::Error)?;
+            }
+            file.flush().map_err(|_| SqlError::Error)?;
+            File::open(&path).map_err(|_| SqlError::Error)
+        })();
+
+        match result {
+            Ok(file) => Ok(Self {
+                lines: BufReader::new(file).lines(),
+                path,
+            }),
+            Err(e) => {
+                let _ = fs::remove_file(&path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Reads the next register of the run.
+    fn next(&mut self, columns: &Arc<Columns>) -> Option<Result<Register, SqlError>> {
+        let line = self.lines.next()?;
+        Some(
+            line.map(|line| Register::from_csv(&line, columns))
+                .map_err(|_| SqlError::Error),
+        )
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Struct that represents a register ranked by the `ORDER BY` clause and then by its position, which is the run it
+/// was read from when merging runs, or the order it was read in when keeping the first registers.
+///
+/// The ordering is reversed, so a heap of heads returns the lowest one first, and registers that compare equal are
+/// returned by their position, which keeps the sort stable.
+struct Head {
+    register: Register,
+    run: usize,
+    orderby: Arc<OrderBy>,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.orderby
+            .compare(&other.register, &self.register)
+            .then(other.run.cmp(&self.run))
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Head {}
+
+/// Iterator over the registers of many sorted runs, merged in order.
+struct Merge {
+    columns: Arc<Columns>,
+    runs: Vec<Run>,
+    heads: BinaryHeap<Head>,
+}
+
+impl Merge {
+    fn new(columns: &[String], mut runs: Vec<Run>, orderby: &OrderBy) -> Result<Self, SqlError> {
+        let orderby = Arc::new(orderby.clone());
+        let columns = Arc::new(Columns::new(columns.to_vec()));
+        let mut heads = BinaryHeap::new();
+
+        for (run, registers) in runs.iter_mut().enumerate() {
+            if let Some(register) = registers.next(&columns) {
+                heads.push(Head {
+                    register: register?,
+                    run,
+                    orderby: Arc::clone(&orderby),
+                });
+            }
+        }
+
+        Ok(Self {
+            columns,
+            runs,
+            heads,
+        })
+    }
+}
+
+impl Iterator for Merge {
+    type Item = Result<Register, SqlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let head = self.heads.pop()?;
+
+        match self.runs[head.run].next(&self.columns) {
+            Some(Ok(register)) => self.heads.push(Head {
+                register,
+                run: head.run,
+                orderby: Arc::clone(&head.orderby),
+            }),
+            Some(Err(e)) => return Some(Err(e)),
+            None => {}
+        }
+
+        Some(Ok(head.register))
+    }
+}
+
+/// Sorts the registers by the columns of the `ORDER BY` clause, keeping the order of the registers that compare equal.
+///
+/// The registers are sorted in memory, up to the given bytes. Beyond that, each batch of registers
+/// is sorted and spilled to a hidden temporary file in the folder, and the files are merged as the returned iterator is read,
+/// so a table larger than memory can be sorted. At most 64 files are merged at once; if there are more, they are first
+/// merged in groups into longer files. The files are removed once the iterator is dropped.
+///
+/// Every register is read before returning, so the first error found reading them is returned instead.
+///
+/// # Arguments
+///
+/// * `registers` - The registers to sort.
+/// * `columns` - The columns of the registers, every one of them holding a value for each column.
+/// * `orderby` - The `ORDER BY` clause to sort the registers by.
+/// * `folder_path` - The path to the folder where the temporary files are written.
+/// * `memory` - The bytes of registers sorted in memory before spilling them to a temporary file.
+///
+pub fn sort(
+    registers: impl Iterator<Item = Result<Register, SqlError>>,
+    columns: &[String],
+    orderby: &OrderBy,
+    folder_path: &str,
+    memory: usize,
+) -> Result<Box<dyn Iterator<Item = Result<Register, SqlError>>>, SqlError> {
+    let mut runs = Vec::new();
+    let mut batch = Vec::new();
+    let mut size = 0;
+
+    for register in registers {
+        let register = register?;
+        size += register_size(&register);
+        batch.push(register);
+
+        if size > memory {
+            orderby.execute(&mut batch);
+            runs.push(Run::write(folder_path, batch.drain(..).map(Ok))?);
+            size = 0;
+        }
+    }
+
+    orderby.execute(&mut batch);
+    if runs.is_empty() {
+        return Ok(Box::new(batch.into_iter().map(Ok)));
+    }
+    if !batch.is_empty() {
+        runs.push(Run::write(folder_path, batch.into_iter().map(Ok))?);
+    }
+
+    while runs.len() > MAX_MERGE_RUNS {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(MAX_MERGE_RUNS));
+        while !runs.is_empty() {
+            let group: Vec<Run> = runs.drain(..MAX_MERGE_RUNS.min(runs.len())).collect();
+            let group = Merge::new(columns, group, orderby)?;
+            merged.push(Run::write(folder_path, group)?);
+        }
+        runs = merged;
+    }
+
+    Ok(Box::new(Merge::new(columns, runs, orderby)?))
+}
+
+/// Returns the first registers sorted by the columns of the `ORDER BY` clause, like `sort` followed by a limit.
+///
+/// Instead of sorting every register, only the first `limit` ones read so far are kept in a heap, so the registers are
+/// sorted in memory proportional to the limit. If the kept registers don't fit in the given memory,
+/// they are sorted along with the rest of the registers with `sort`.
+///
+/// # Arguments
+///
+/// * `registers` - The registers to sort.
+/// * `columns` - The columns of the registers, every one of them holding a value for each column.
+/// * `orderby` - The `ORDER BY` clause to sort the registers by.
+/// * `limit` - The number of registers to return.
+/// * `folder_path` - The path to the folder where the temporary files are written, if needed.
+/// * `memory` - The bytes of registers sorted in memory before spilling them to a temporary file.
+///
+pub fn top(
+    mut registers: impl Iterator<Item = Result<Register, SqlError>>,
+    columns: &[String],
+    orderby: &OrderBy,
+    limit: usize,
+    folder_path: &str,
+    memory: usize,
+) -> Result<Box<dyn Iterator<Item = Result<Register, SqlError>>>, SqlError> {
+    let orderby = Arc::new(orderby.clone());
+    let mut kept = BinaryHeap::new();
+    let mut size = 0;
+    let mut position = 0;
+
+    while let Some(register) = registers.next() {
+        let head = Reverse(Head {
+            register: register?,
+            run: position,
+            orderby: Arc::clone(&orderby),
+        });
+        position += 1;
+
+        if kept.len() < limit {
+            size += register_size(&head.0.register);
+            kept.push(head);
+        } else if kept.peek().is_some_and(|last| head < *last) {
+            size += register_size(&head.0.register);
+            if let Some(Reverse(last)) = kept.pop() {
+                size -= register_size(&last.register);
+            }
+            kept.push(head);
+        }
+
+        if size > memory {
+            let mut kept: Vec<Head> = kept.into_iter().map(|Reverse(head)| head).collect();
+            kept.sort_by_key(|head| head.run);
+            let kept = kept.into_iter().map(|head| Ok(head.register));
+            let sorted = sort(
+                kept.chain(registers),
+                columns,
+                &orderby,
+                folder_path,
+                memory,
+            )?;
+            return Ok(Box::new(sorted.take(limit)));
+        }
+    }
+
+    let kept = kept.into_sorted_vec();
+    Ok(Box::new(
+        kept.into_iter().map(|Reverse(head)| Ok(head.register)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sort, top, MAX_MERGE_RUNS};
+    use crate::{
+        clauses::orderby_sql::{Order, OrderBy},
+        register::{Columns, Register},
+    };
+    use std::{fs, sync::Arc};
+
+    #[test]
+    fn spill_and_merge_runs() {
+        let folder = std::env::temp_dir().join("rustic_sql_sort");
+        fs::create_dir_all(&folder).unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let columns = vec![String::from("id"), String::from("edad")];
+        let shared = Arc::new(Columns::new(columns.to_vec()));
+        let mut registers: Vec<Register> = (0..50)
+            .map(|id| Register::from_csv(&format!("{},{}", id, id * 7 % 10), &shared))
+            .collect();
+        let orderby = OrderBy {
+            columns: vec![(String::from("edad"), Order::Desc)],
+        };
+
+        let sorted = sort(
+            registers.clone().into_iter().map(Ok),
+            &columns,
+            &orderby,
+            folder_path,
+            1000,
+        )
+        .unwrap();
+        assert!(fs::read_dir(&folder).unwrap().count() > 1);
+
+        let sorted: Vec<Register> = sorted.map(|register| register.unwrap()).collect();
+        orderby.execute(&mut registers);
+        assert_eq!(sorted, registers);
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 0);
+
+        let sorted = sort(
+            registers.clone().into_iter().map(Ok),
+            &columns,
+            &orderby,
+            folder_path,
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 0);
+        assert_eq!(sorted.count(), 50);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn merge_runs_in_passes() {
+        let folder = std::env::temp_dir().join("rustic_sql_sort_passes");
+        fs::create_dir_all(&folder).unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let columns = vec![String::from("id"), String::from("edad")];
+        let shared = Arc::new(Columns::new(columns.to_vec()));
+        let mut registers: Vec<Register> = (0..1000)
+            .map(|id| Register::from_csv(&format!("{},{}", id, id * 7 % 100), &shared))
+            .collect();
+        let orderby = OrderBy {
+            columns: vec![(String::from("edad"), Order::Asc)],
+        };
+
+        let sorted = sort(
+            registers.clone().into_iter().map(Ok),
+            &columns,
+            &orderby,
+            folder_path,
+            1000,
+        )
+        .unwrap();
+        let files = fs::read_dir(&folder).unwrap().count();
+        assert!(files > 1 && files <= MAX_MERGE_RUNS);
+
+        let sorted: Vec<Register> = sorted.map(|register| register.unwrap()).collect();
+        orderby.execute(&mut registers);
+        assert_eq!(sorted, registers);
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 0);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn keep_first_registers() {
+        let folder = std::env::temp_dir().join("rustic_sql_sort_top");
+        fs::create_dir_all(&folder).unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let columns = vec![String::from("id"), String::from("edad")];
+        let shared = Arc::new(Columns::new(columns.to_vec()));
+        let registers: Vec<Register> = (0..50)
+            .map(|id| Register::from_csv(&format!("{},{}", id, id * 7 % 10), &shared))
+            .collect();
+        let orderby = OrderBy {
+            columns: vec![(String::from("edad"), Order::Desc)],
+        };
+        let mut sorted = registers.clone();
+        orderby.execute(&mut sorted);
+
+        for (limit, memory) in [
+            (0, usize::MAX),
+            (3, usize::MAX),
+            (12, 1000),
+            (80, usize::MAX),
+        ] {
+            let top: Vec<Register> = top(
+                registers.clone().into_iter().map(Ok),
+                &columns,
+                &orderby,
+                limit,
+                folder_path,
+                memory,
+            )
+            .unwrap()
+            .map(|register| register.unwrap())
+            .collect();
+            assert_eq!(top, sorted.iter().take(limit).cloned().collect::<Vec<_>>());
+        }
+        assert_eq!(fs::read_dir(&folder).unwrap().count(), 0);
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
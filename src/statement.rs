@@ -0,0 +1,332 @@
+use crate::{
+    clauses::{delete_sql::Delete, insert_sql::Insert, select_sql::Select, update_sql::Update},
+    errors::SqlError,
+    tokens::tokens_from_query,
+    utils::{is_delete, is_insert, is_select, is_update},
+    value::Value,
+};
+
+/// Character that starts the marks left in the query in place of its parameters, which can't be typed in a query.
+const PARAMETER_MARK: char = '\u{0}';
+
+/// Enum for the statements that read or change the registers of a table, once parsed.
+///
+/// - `Select`: a `SELECT` statement.
+/// - `Insert`: an `INSERT` statement.
+/// - `Update`: an `UPDATE` statement.
+/// - `Delete`: a `DELETE` statement.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Select(Select),
+    Insert(Insert),
+    Update(Update),
+    Delete(Delete),
+}
+
+impl Statement {
+    /// Creates and returns a new `Statement` from the tokens of a query, parsed by the clause of its first token.
+    pub fn new_from_tokens(tokens: Vec<String>) -> Result<Self, SqlError> {
+        match tokens.first() {
+            Some(first) if is_select(first) => {
+                Ok(Statement::Select(Select::new_from_tokens(tokens)?))
+            }
+            Some(first) if is_insert(first) => {
+                Ok(Statement::Insert(Insert::new_from_tokens(tokens)?))
+            }
+            Some(first) if is_update(first) => {
+                Ok(Statement::Update(Update::new_from_tokens(tokens)?))
+            }
+            Some(first) if is_delete(first) => {
+                Ok(Statement::Delete(Delete::new_from_tokens(tokens)?))
+            }
+            _ => Err(SqlError::InvalidSyntax),
+        }
+    }
+
+    /// Returns the command of the statement, like `UPDATE`.
+    pub fn command(&self) -> &str {
+        match self {
+            Statement::Select(_) => "SELECT",
+            Statement::Insert(_) => "INSERT",
+            Statement::Update(_) => "UPDATE",
+            Statement::Delete(_) => "DELETE",
+        }
+    }
+
+    /// Returns the name of the table the statement reads or changes.
+    pub fn table_name(&self) -> &str {
+        match self {
+            Statement::Select(select) => &select.table_name,
+            Statement::Insert(insert) => &insert.into_clause.table_name,
+            Statement::Update(update) => &update.table_name,
+            Statement::Delete(delete) => &delete.table_name,
+        }
+    }
+
+    /// Returns mutable references to the values given in the statement: the values compared in the `WHERE` clause,
+    /// the values set by an `UPDATE` and the values inserted by an `INSERT`.
+    pub(crate) fn values_mut(&mut self) -> Vec<&mut String> {
+        let mut values = Vec::new();
+        match self {
+            Statement::Select(select) => {
+                if let Some(where_clause) = &mut select.where_clause {
+                    values.extend(where_clause.condition.values_mut());
+                }
+            }
+            Statement::Insert(insert) => values.extend(insert.values.iter_mut()),
+            Statement::Update(update) => {
+                values.extend(update.set_clause.0.iter_mut().map(|(_, value)| value));
+                if let Some(where_clause) = &mut update.where_clause {
+                    values.extend(where_clause.condition.values_mut());
+                }
+            }
+            Statement::Delete(delete) => {
+                if let Some(where_clause) = &mut delete.where_clause {
+                    values.extend(where_clause.condition.values_mut());
+                }
+            }
+        }
+        values
+    }
+}
+
+/// Returns an `InvalidSyntax` error if a value given outside a query holds a comma or a line break, since the tables
+/// don't quote their values.
+pub(crate) fn check_value(value: &str) -> Result<(), SqlError> {
+    match value.contains([',', '\n', '\r']) {
+        true => Err(SqlError::InvalidSyntax),
+        false => Ok(()),
+    }
+}
+
+/// Replaces the parameters of a query, `?` or `:name` outside string literals, with quoted marks holding their position.
+///
+/// Returns the query with the marks, along with the name of each parameter, or `None` for the positional ones.
+fn mark_parameters(query: &str) -> (String, Vec<Option<String>>) {
+    let mut marked = String::new();
+    let mut parameters = Vec::new();
+    let mut in_quotes = false;
+    let mut chars = query.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\'' => {
+                in_quotes = !in_quotes;
+                marked.push(char);
+            }
+            '?' if !in_quotes => {
+                marked.push_str(&format!("'{}{}'", PARAMETER_MARK, parameters.len()));
+                parameters.push(None);
+            }
+            ':' if !in_quotes
+                && chars
+                    .peek()
+                    .is_some_and(|next| next.is_alphabetic() || *next == '_') =>
+            {
+                let mut name = String::new();
+                while let Some(next) = chars.next_if(|next| next.is_alphanumeric() || *next == '_')
+                {
+                    name.push(next);
+                }
+                marked.push_str(&format!("'{}{}'", PARAMETER_MARK, parameters.len()));
+                parameters.push(Some(name));
+            }
+            _ => marked.push(char),
+        }
+    }
+
+    (marked, parameters)
+}
+
+/// Returns the position of the parameter left in place of a value by `mark_parameters`, if the value is a mark.
+fn parameter_position(value: &str) -> Option<usize> {
+    value.strip_prefix(PARAMETER_MARK)?.parse().ok()
+}
+
+/// Struct that represents a statement parsed once, to be executed many times with different values for its parameters.
+///
+/// The parameters take the place of the values of the statement, and are written `?` for positional parameters or
+/// `:name` for named ones. The bound values are never parsed as part of the query, so they can hold quotes or keywords.
+///
+/// # Fields
+///
+/// * `statement` - The parsed statement, with marks in place of its parameters.
+/// * `parameters` - The name of each parameter in the order they appear, or `None` for the positional ones.
+/// * `values` - The value bound to each parameter, if any.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_sql::{Database, SqlError};
+///
+/// # fn main() -> Result<(), SqlError> {
+/// let mut database = Database::open("tablas")?;
+/// let mut statement = database.prepare("SELECT * FROM clientes WHERE id = ? AND apellido = :apellido")?;
+/// statement.bind(1, 7)?;
+/// statement.bind_named("apellido", "O'Brien")?;
+///
+/// let rows = database.query_prepared(&statement)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedStatement {
+    statement: Statement,
+    pub parameters: Vec<Option<String>>,
+    values: Vec<Option<Value>>,
+}
+
+impl PreparedStatement {
+    /// Parses a query with parameters.
+    ///
+    /// Only SELECT, INSERT, UPDATE and DELETE statements can be prepared. Returns an `InvalidSyntax` error if a parameter
+    /// is not in place of a value, like a table or column name.
+    pub fn new(query: &str) -> Result<Self, SqlError> {
+        let (marked, parameters) = mark_parameters(query);
+        let mut statement = Statement::new_from_tokens(tokens_from_query(&marked))?;
+
+        let mut positions: Vec<usize> = statement
+            .values_mut()
+            .iter()
+            .filter_map(|value| parameter_position(value))
+            .collect();
+        positions.sort();
+        if positions != (0..parameters.len()).collect::<Vec<usize>>() {
+            return Err(SqlError::InvalidSyntax);
+        }
+
+        Ok(Self {
+            statement,
+            values: vec![None; parameters.len()],
+            parameters,
+        })
+    }
+
+    /// Binds a value to a positional parameter, counting the `?` of the statement from 1.
+    ///
+    /// Returns an `InvalidSyntax` error if the statement doesn't have that many positional parameters.
+    pub fn bind(&mut self, position: usize, value: impl Into<Value>) -> Result<(), SqlError> {
+        let idx = self
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.is_none())
+            .nth(position.checked_sub(1).ok_or(SqlError::InvalidSyntax)?)
+            .map(|(idx, _)| idx)
+            .ok_or(SqlError::InvalidSyntax)?;
+
+        self.values[idx] = Some(value.into());
+        Ok(())
+    }
+
+    /// Binds a value to every parameter of the statement written `:name`.
+    ///
+    /// Returns an `InvalidSyntax` error if the statement doesn't have a parameter with that name.
+    pub fn bind_named(&mut self, name: &str, value: impl Into<Value>) -> Result<(), SqlError> {
+        let value = value.into();
+        let mut found = false;
+
+        for (idx, parameter) in self.parameters.iter().enumerate() {
+            if parameter.as_deref() == Some(name) {
+                self.values[idx] = Some(value.clone());
+                found = true;
+            }
+        }
+
+        match found {
+            true => Ok(()),
+            false => Err(SqlError::InvalidSyntax),
+        }
+    }
+
+    /// Returns the statement with the bound values in place of its parameters.
+    ///
+    /// Returns an `InvalidSyntax` error if a parameter has no value bound, or if a value holds a comma or a line break,
+    /// since the tables don't quote their values.
+    pub fn statement(&self) -> Result<Statement, SqlError> {
+        let mut statement = self.statement.clone();
+
+        for value in statement.values_mut() {
+            if let Some(position) = parameter_position(value) {
+                let bound = self.values[position]
+                    .as_ref()
+                    .ok_or(SqlError::InvalidSyntax)?
+                    .to_string();
+                check_value(&bound)?;
+                *value = bound;
+            }
+        }
+
+        Ok(statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mark_parameters, PreparedStatement};
+    use crate::errors::SqlError;
+
+    #[test]
+    fn mark_positional_and_named() {
+        let (marked, parameters) =
+            mark_parameters("SELECT * FROM t WHERE a = ? AND b = :nombre AND c = '?:x'");
+
+        assert_eq!(
+            marked,
+            "SELECT * FROM t WHERE a = '\u{0}0' AND b = '\u{0}1' AND c = '?:x'"
+        );
+        assert_eq!(parameters, vec![None, Some(String::from("nombre"))]);
+    }
+
+    #[test]
+    fn bind_values() {
+        let mut prepared = PreparedStatement::new(
+            "UPDATE clientes SET apellido = :apellido WHERE id = ? OR nombre = :apellido",
+        )
+        .unwrap();
+        assert_eq!(prepared.statement(), Err(SqlError::InvalidSyntax));
+
+        prepared.bind(1, 7).unwrap();
+        prepared
+            .bind_named("apellido", "O'Brien AND id = 1")
+            .unwrap();
+        assert_eq!(prepared.bind(2, 8), Err(SqlError::InvalidSyntax));
+        assert_eq!(
+            prepared.bind_named("nombre", "Ana"),
+            Err(SqlError::InvalidSyntax)
+        );
+
+        let mut statement = prepared.statement().unwrap();
+        let values: Vec<&str> = statement
+            .values_mut()
+            .into_iter()
+            .map(|value| value.as_str())
+            .collect();
+        assert_eq!(
+            values,
+            vec!["O'Brien AND id = 1", "7", "O'Brien AND id = 1"]
+        );
+
+        prepared.bind(1, "1,2").unwrap();
+        assert_eq!(prepared.statement(), Err(SqlError::InvalidSyntax));
+    }
+
+    #[test]
+    fn parameters_only_in_place_of_values() {
+        assert_eq!(
+            PreparedStatement::new("SELECT * FROM :tabla"),
+            Err(SqlError::InvalidSyntax)
+        );
+        assert_eq!(
+            PreparedStatement::new("INSERT INTO clientes (?) VALUES (1)"),
+            Err(SqlError::InvalidSyntax)
+        );
+        assert!(PreparedStatement::new("INSERT INTO clientes (id, nombre) VALUES (?, :n)").is_ok());
+        assert_eq!(
+            PreparedStatement::new("BEGIN"),
+            Err(SqlError::InvalidSyntax)
+        );
+    }
+}
@@ -8,10 +8,10 @@ use crate::register::Register;
 /// * `registers` - Vector of registers
 ///
 /// # Examples
-/// ```
+/// ```ignore
 /// let table = Table::new();
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Default, PartialEq)]
 pub struct Table {
     pub columns: Vec<String>,
     pub registers: Vec<Register>,
@@ -0,0 +1,493 @@
+use crate::{
+    errors::SqlError,
+    lock::{tables_for_write, TableLock},
+    snapshot::file_id,
+    utils::is_process_running,
+    wal::{Wal, WalRecord},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+static TRANSACTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Struct that represents an open transaction over the tables of a folder.
+///
+/// When the transaction begins, the schemas and sequences of the folder are copied to a shadow folder, and the statements of the transaction
+/// are executed against it. A table is copied to the shadow folder along with its indexes the first time a statement uses it, see `prepare`.
+/// The copies changed by the transaction replace the original files on `COMMIT`, and are discarded on `ROLLBACK`.
+///
+/// Every savepoint is a copy of the shadow folder taken when the savepoint was declared.
+///
+/// # Fields
+///
+/// * `folder_path` - The path to the folder where the tables are stored.
+/// * `shadow_path` - The path to the shadow folder with the copies of the tables.
+/// * `copies` - The state of each copied file in the folder and in the shadow folder when it was copied, by its name.
+/// * `savepoints` - The savepoints declared in the transaction, in order.
+/// * `lock_timeout` - How long to wait for the lock of a table when it's copied or replaced.
+///
+#[derive(Debug)]
+pub struct Transaction {
+    pub folder_path: String,
+    pub shadow_path: String,
+    copies: BTreeMap<String, (FileState, FileState)>,
+    savepoints: Vec<Savepoint>,
+    lock_timeout: Duration,
+}
+
+/// A savepoint of a transaction, with the copies made before it and the names of the copies that weren't changed before it,
+/// which are still unchanged once the shadow folder is restored.
+#[derive(Debug)]
+struct Savepoint {
+    name: String,
+    copies: BTreeMap<String, (FileState, FileState)>,
+    unchanged: BTreeSet<String>,
+}
+
+/// The identifier, length and modification time of a file, which change whenever the file is replaced or appended to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FileState {
+    id: Option<u64>,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+fn file_state(path: &Path) -> Option<FileState> {
+    let metadata = fs::metadata(path).ok()?;
+    Some(FileState {
+        id: file_id(&metadata),
+        len: metadata.len(),
+        modified: metadata.modified().ok(),
+    })
+}
+
+fn file_names(path: &Path) -> Result<Vec<String>, SqlError> {
+    let mut names = Vec::new();
+    let entries = fs::read_dir(path).map_err(|_| SqlError::Error)?;
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type().is_ok_and(|file_type| file_type.is_file()) && !name.starts_with('.') {
+            names.push(name);
+        }
+    }
+
+    Ok(names)
+}
+
+fn copy_files(from: &Path, to: &Path) -> Result<(), SqlError> {
+    fs::create_dir_all(to).map_err(|_| SqlError::Error)?;
+    for name in file_names(from)? {
+        fs::copy(from.join(&name), to.join(&name)).map_err(|_| SqlError::Error)?;
+    }
+    Ok(())
+}
+
+fn remove_files(path: &Path) -> Result<(), SqlError> {
+    for name in file_names(path)? {
+        fs::remove_file(path.join(name)).map_err(|_| SqlError::Error)?;
+    }
+    Ok(())
+}
+
+/// Returns the name of the table of an index file, read from the first line of the file.
+fn index_table(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let header = BufReader::new(file).lines().next()?.ok()?;
+    header.split(',').next().map(String::from)
+}
+
+impl Transaction {
+    /// Begins a new transaction over the tables of the folder, copying their schemas and sequences to a new shadow folder.
+    ///
+    /// The shadow folder is named `.transaction_<pid>_<n>`, unique to the transaction, so many transactions can be open at the same time.
+    /// The tables are locked waiting up to the given timeout.
+    pub fn begin(folder_path: &str, lock_timeout: Duration) -> Result<Self, SqlError> {
+        let shadow = Path::new(folder_path).join(format!(
+            ".transaction_{}_{}",
+            process::id(),
+            TRANSACTIONS.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&shadow).map_err(|_| SqlError::Error)?;
+
+        let mut transaction = Self {
+            folder_path: folder_path.to_string(),
+            shadow_path: shadow.to_string_lossy().to_string(),
+            copies: BTreeMap::new(),
+            savepoints: Vec::new(),
+            lock_timeout,
+        };
+        for name in file_names(Path::new(folder_path))? {
+            if name.ends_with(".schema") || name.ends_with(".sequence") {
+                transaction.copy(&name)?;
+            }
+        }
+
+        Ok(transaction)
+    }
+
+    /// Copies the tables used by a statement over the given table to the shadow folder, unless they were already copied.
+    ///
+    /// If the statement changes the table, the tables it may change or check through foreign keys are copied too.
+    /// Every table is copied along with its indexes.
+    pub fn prepare(&mut self, table_name: &str, changes: bool) -> Result<(), SqlError> {
+        let tables = if changes {
+            tables_for_write(&self.shadow_path, table_name)?
+                .into_keys()
+                .collect()
+        } else {
+            vec![table_name.to_string()]
+        };
+
+        for table in tables {
+            let name = table.to_string() + ".csv";
+            if self.copies.contains_key(&name) || !Path::new(&self.folder_path).join(&name).exists()
+            {
+                continue;
+            }
+
+            let _lock = TableLock::shared(&self.folder_path, &table, self.lock_timeout)?;
+            self.copy(&name)?;
+            for index in file_names(Path::new(&self.folder_path))? {
+                if index.ends_with(".index")
+                    && index_table(&Path::new(&self.folder_path).join(&index)).as_ref()
+                        == Some(&table)
+                {
+                    self.copy(&index)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies the table of the index with the given name to the shadow folder, along with its indexes, unless it was already copied.
+    pub fn prepare_index(&mut self, name: &str) -> Result<(), SqlError> {
+        let path = Path::new(&self.folder_path).join(name.to_string() + ".index");
+        match index_table(&path) {
+            Some(table_name) => self.prepare(&table_name, false),
+            None => Ok(()),
+        }
+    }
+
+    fn copy(&mut self, name: &str) -> Result<(), SqlError> {
+        let from = Path::new(&self.folder_path).join(name);
+        let to = Path::new(&self.shadow_path).join(name);
+
+        let original = file_state(&from).ok_or(SqlError::Error)?;
+        fs::copy(&from, &to).map_err(|_| SqlError::Error)?;
+        let copy = file_state(&to).ok_or(SqlError::Error)?;
+
+        self.copies.insert(name.to_string(), (original, copy));
+        Ok(())
+    }
+
+    /// Removes the shadow folders left in the folder by processes that ended without committing or rolling back their transactions.
+    ///
+    /// Should be called after the write-ahead log was recovered, since it may still need the files of a shadow folder.
+    pub fn remove_stale(folder_path: &str) -> Result<(), SqlError> {
+        let entries = fs::read_dir(folder_path).map_err(|_| SqlError::InvalidTable)?;
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let pid = name
+                .strip_prefix(".transaction_")
+                .and_then(|name| name.split('_').next())
+                .and_then(|pid| pid.parse::<u32>().ok());
+
+            if pid.is_some_and(|pid| pid != process::id() && !is_process_running(pid)) {
+                fs::remove_dir_all(entry.path()).map_err(|_| SqlError::Error)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes the changes of the transaction visible in the folder and ends the transaction.
+    ///
+    /// Only the files changed by the transaction, the ones whose copy was replaced, appended to, created or removed,
+    /// replace the original ones. The changed tables are locked exclusively while they are replaced.
+    /// The changes are recorded in the write-ahead log first, so they are all made even if the program is interrupted.
+    ///
+    /// Returns a `Conflict` error, without changing any file, if one of the changed files was also changed in the folder
+    /// since it was copied.
+    pub fn commit(self) -> Result<(), SqlError> {
+        let folder = Path::new(&self.folder_path);
+        let shadow = Path::new(&self.shadow_path);
+
+        let mut changed = BTreeSet::new();
+        for name in file_names(shadow)? {
+            let copy = self.copies.get(&name).map(|(_, copy)| *copy);
+            if file_state(&shadow.join(&name)) != copy {
+                changed.insert(name);
+            }
+        }
+        for name in self.copies.keys() {
+            if !shadow.join(name).exists() {
+                changed.insert(name.to_string());
+            }
+        }
+
+        let mut locks = Vec::new();
+        for name in &changed {
+            if let Some(table_name) = name.strip_suffix(".csv") {
+                if folder.join(name).exists() {
+                    locks.push(TableLock::exclusive(
+                        &self.folder_path,
+                        table_name,
+                        self.lock_timeout,
+                    )?);
+                }
+            }
+        }
+
+        let mut wal = Wal::new(&self.folder_path);
+        for name in &changed {
+            let original = self.copies.get(name).map(|(original, _)| *original);
+            if file_state(&folder.join(name)) != original {
+                return Err(SqlError::Conflict {
+                    file: name.to_string(),
+                });
+            }
+
+            if shadow.join(name).exists() {
+                wal.push(WalRecord::Rename {
+                    file: name.to_string(),
+                    from: shadow.join(name).to_string_lossy().to_string(),
+                });
+            } else {
+                wal.push(WalRecord::Remove {
+                    file: name.to_string(),
+                });
+            }
+        }
+
+        wal.commit()?;
+        drop(locks);
+
+        Ok(())
+    }
+
+    /// Declares a savepoint, so the changes made after it can be discarded with `rollback_to`.
+    ///
+    /// Declaring a savepoint with the name of an existing one replaces it.
+    pub fn savepoint(&mut self, name: &str) -> Result<(), SqlError> {
+        if self.position(name).is_ok() {
+            self.release(name)?;
+        }
+
+        let shadow = Path::new(&self.shadow_path);
+        let unchanged = self
+            .copies
+            .iter()
+            .filter(|(file, (_, copy))| file_state(&shadow.join(file)) == Some(*copy))
+            .map(|(file, _)| file.to_string())
+            .collect();
+
+        copy_files(shadow, &self.savepoint_path(name))?;
+        self.savepoints.push(Savepoint {
+            name: name.to_string(),
+            copies: self.copies.clone(),
+            unchanged,
+        });
+
+        Ok(())
+    }
+
+    /// Discards the changes made after the savepoint with the given name, and the savepoints declared after it.
+    ///
+    /// The savepoint is kept, so it can be used again.
+    pub fn rollback_to(&mut self, name: &str) -> Result<(), SqlError> {
+        let position = self.position(name)?;
+        for later in self.savepoints.split_off(position + 1) {
+            fs::remove_dir_all(self.savepoint_path(&later.name)).map_err(|_| SqlError::Error)?;
+        }
+
+        let shadow = Path::new(&self.shadow_path);
+        remove_files(shadow)?;
+        copy_files(&self.savepoint_path(name), shadow)?;
+
+        let savepoint = &self.savepoints[position];
+        self.copies = savepoint.copies.clone();
+        for file in &savepoint.unchanged {
+            if let (Some(copy), Some(state)) =
+                (self.copies.get_mut(file), file_state(&shadow.join(file)))
+            {
+                copy.1 = state;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the savepoint with the given name and the savepoints declared after it, keeping the changes made after them.
+    pub fn release(&mut self, name: &str) -> Result<(), SqlError> {
+        let position = self.position(name)?;
+        for savepoint in self.savepoints.split_off(position) {
+            fs::remove_dir_all(self.savepoint_path(&savepoint.name))
+                .map_err(|_| SqlError::Error)?;
+        }
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> Result<usize, SqlError> {
+        self.savepoints
+            .iter()
+            .rposition(|savepoint| savepoint.name == name)
+            .ok_or(SqlError::InvalidSyntax)
+    }
+
+    fn savepoint_path(&self, name: &str) -> PathBuf {
+        Path::new(&self.shadow_path).join(format!(".savepoint_{}", name))
+    }
+}
+
+/// Discards the shadow folder when the transaction ends, so a transaction that is not committed is rolled back.
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.shadow_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transaction;
+    use crate::errors::SqlError;
+    use std::{fs, path::Path, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn commit_and_rollback() {
+        let folder = std::env::temp_dir().join("rustic_sql_transaction_commit");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n1\n").unwrap();
+        fs::write(folder.join("ordenes.csv"), "id\n1\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let mut transaction = Transaction::begin(folder_path, TIMEOUT).unwrap();
+        assert!(!Path::new(&transaction.shadow_path)
+            .join("clientes.csv")
+            .exists());
+        transaction.prepare("clientes", false).unwrap();
+        fs::write(
+            Path::new(&transaction.shadow_path).join("clientes.csv"),
+            "id\n1\n2\n",
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n"
+        );
+        drop(transaction);
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n"
+        );
+
+        let mut transaction = Transaction::begin(folder_path, TIMEOUT).unwrap();
+        let other = Transaction::begin(folder_path, TIMEOUT).unwrap();
+        assert_ne!(transaction.shadow_path, other.shadow_path);
+        drop(other);
+        transaction.prepare("clientes", false).unwrap();
+        transaction.prepare("ordenes", false).unwrap();
+        let shadow = Path::new(&transaction.shadow_path).to_path_buf();
+        fs::write(shadow.join("clientes.csv"), "id\n1\n2\n").unwrap();
+        fs::remove_file(shadow.join("ordenes.csv")).unwrap();
+        transaction.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n2\n"
+        );
+        assert!(!folder.join("ordenes.csv").exists());
+        assert!(!shadow.exists());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn commit_only_changed_files() {
+        let folder = std::env::temp_dir().join("rustic_sql_transaction_conflict");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n1\n").unwrap();
+        fs::write(folder.join("ordenes.csv"), "id\n1\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let mut transaction = Transaction::begin(folder_path, TIMEOUT).unwrap();
+        transaction.prepare("clientes", false).unwrap();
+        transaction.prepare("ordenes", false).unwrap();
+        let shadow = Path::new(&transaction.shadow_path).to_path_buf();
+        fs::write(shadow.join("ordenes.csv"), "id\n1\n2\n").unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n1\n3\n").unwrap();
+        transaction.commit().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n3\n"
+        );
+        assert_eq!(
+            fs::read_to_string(folder.join("ordenes.csv")).unwrap(),
+            "id\n1\n2\n"
+        );
+
+        let mut transaction = Transaction::begin(folder_path, TIMEOUT).unwrap();
+        transaction.prepare("clientes", false).unwrap();
+        let shadow = Path::new(&transaction.shadow_path).to_path_buf();
+        fs::write(shadow.join("clientes.csv"), "id\n4\n").unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n1\n3\n5\n").unwrap();
+        assert_eq!(
+            transaction.commit(),
+            Err(SqlError::Conflict {
+                file: String::from("clientes.csv")
+            })
+        );
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n3\n5\n"
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn savepoints() {
+        let folder = std::env::temp_dir().join("rustic_sql_transaction_savepoints");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let mut transaction = Transaction::begin(folder_path, TIMEOUT).unwrap();
+        transaction.prepare("clientes", false).unwrap();
+        let table = Path::new(&transaction.shadow_path).join("clientes.csv");
+
+        fs::write(&table, "id\n1\n").unwrap();
+        transaction.savepoint("a").unwrap();
+        fs::write(&table, "id\n1\n2\n").unwrap();
+        transaction.savepoint("b").unwrap();
+        fs::write(&table, "id\n1\n2\n3\n").unwrap();
+
+        transaction.rollback_to("a").unwrap();
+        assert_eq!(fs::read_to_string(&table).unwrap(), "id\n1\n");
+        assert_eq!(transaction.rollback_to("b"), Err(SqlError::InvalidSyntax));
+
+        fs::write(&table, "id\n1\n4\n").unwrap();
+        transaction.release("a").unwrap();
+        assert_eq!(transaction.rollback_to("a"), Err(SqlError::InvalidSyntax));
+
+        transaction.commit().unwrap();
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n4\n"
+        );
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
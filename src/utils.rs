@@ -1,11 +1,24 @@
-use crate::{errors::SqlError, table::Table};
-use std::{fs, path::Path};
+use crate::{
+    errors::SqlError,
+    register::{Columns, Register},
+    table::Table,
+};
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 /// Searches for the file given in the folder path, returns true if the file is found.
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// let folder_path = "tables";
 /// let file_name = "clients.csv";
 /// let result = utils::find_file_in_folder(folder_path, file_name);
@@ -26,45 +39,242 @@ pub fn find_file_in_folder(folder_path: &str, file_name: &str) -> bool {
     false
 }
 
-/// Transforms a table into a csv format where the first line is the column names and the following lines are the registers.
-/// The column order is given by the column_order parameter.
-/// Returns a vector of strings, where each string is a line in the csv.
+/// Writes a table in csv format, where the first line is the column names and the following lines are the registers.
+/// Every register is written in the order of its columns, which are the ones of the table.
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
+/// let columns = vec!["id".to_string(), "name".to_string(), "age".to_string()];
+/// let shared = Arc::new(Columns::new(columns.to_vec()));
 /// let table = Table {
-///     name: "clients".to_string(),
-///     columns: vec!["id".to_string(), "name".to_string(), "age".to_string()],
+///     columns,
 ///     registers: vec![
-///         Register {values: vec!["1".to_string(), "Alen".to_string(), "30".to_string()]},
-///         Register {values: vec!["2".to_string(), "Emily".to_string(), "25".to_string()]},
+///         Register::from_csv("1,Alen,30", &shared),
+///         Register::from_csv("2,Emily,25", &shared),
 ///     ],
 /// };
 ///
-/// let column_order = vec!["id".to_string(), "name".to_string(), "age".to_string()];
-/// let result = utils::table_to_csv(&table, &column_order);
-/// assert_eq!(result, vec!["id,name,age", "1,Alen,30", "2,Emily,25"]);
+/// let mut output = Vec::new();
+/// utils::write_csv(&table, &mut output).unwrap();
+/// assert_eq!(output, b"id,name,age\n1,Alen,30\n2,Emily,25\n");
 /// ```
 ///
-pub fn table_to_csv(table: &Table, column_order: &Vec<String>) -> Result<Vec<String>, SqlError> {
-    let mut result: Vec<String> = Vec::new();
-
-    result.push(column_order.join(","));
+pub fn write_csv<W: Write>(table: &Table, output: &mut W) -> Result<(), SqlError> {
+    writeln!(output, "{}", table.columns.join(",")).map_err(|_| SqlError::Error)?;
 
     for register in &table.registers {
-        let register_csv = register.to_csv(column_order)?;
-        result.push(register_csv);
+        writeln!(output, "{}", register.to_csv()).map_err(|_| SqlError::Error)?;
     }
 
-    Ok(result)
+    Ok(())
 }
 
-/// Returns true if the token can be converted to an i32 value.
+/// Reads every register of a table stored in the folder path.
+///
+/// # Examples
+///
+/// ```ignore
+/// let table = utils::read_table("tablas", "clientes").unwrap();
+/// assert_eq!(table.columns, vec!["id", "nombre", "apellido", "email"]);
+/// ```
+///
+pub fn read_table(folder_path: &str, table_name: &str) -> Result<Table, SqlError> {
+    let file_name = table_name.to_string() + ".csv";
+    if !find_file_in_folder(folder_path, &file_name) {
+        return Err(SqlError::InvalidTable);
+    }
+    read_table_file(&(folder_path.to_string() + "/" + &file_name))
+}
+
+/// Reads every register of the table stored in the file at the given path, which may be a temporary file.
+pub fn read_table_file(path: &str) -> Result<Table, SqlError> {
+    let file = File::open(path).map_err(|_| SqlError::InvalidTable)?;
+
+    let mut table = Table::new();
+    let mut columns = Arc::new(Columns::new(Vec::new()));
+
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|_| SqlError::Error)?;
+        if idx == 0 {
+            table.columns = line.split(',').map(|s| s.to_string()).collect();
+            columns = Arc::new(Columns::new(table.columns.to_vec()));
+            continue;
+        }
+        table.registers.push(Register::from_csv(&line, &columns));
+    }
+
+    Ok(table)
+}
+
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the path of a new hidden temporary file in the folder, with a name unique to the process like `.<name>.<pid>.<n>.tmp`.
+///
+/// The temporary files left by processes that ended abruptly are removed by `remove_stale_temp_files`.
+pub fn temp_file_path(folder_path: &str, name: &str) -> String {
+    format!(
+        "{}/.{}.{}.{}.tmp",
+        folder_path,
+        name,
+        process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Struct that represents a new version of a file of the folder, written to a hidden temporary file that replaces
+/// the file once it's complete.
+///
+/// The temporary file is removed if the struct is dropped before the file is replaced.
+///
+/// # Fields
+///
+/// * `path` - The path of the temporary file, see `temp_file_path`.
+/// * `writer` - The buffered writer of the temporary file.
+///
+#[derive(Debug)]
+pub struct TempFile {
+    pub path: String,
+    writer: BufWriter<File>,
+}
+
+impl TempFile {
+    /// Creates a new temporary file in the folder for the file with the given name.
+    pub fn create(folder_path: &str, file_name: &str) -> Result<Self, SqlError> {
+        let path = temp_file_path(folder_path, file_name);
+        let file = File::create(&path).map_err(|_| SqlError::Error)?;
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Writes the buffered content to the temporary file and syncs it to disk, so it can replace the file.
+    pub fn sync(&mut self) -> Result<(), SqlError> {
+        self.writer.flush().map_err(|_| SqlError::Error)?;
+        self.writer
+            .get_ref()
+            .sync_all()
+            .map_err(|_| SqlError::Error)
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Replaces the content of a file stored in the folder path with the given lines.
+///
+/// The lines are written to a temporary file with a unique name, which is synced to disk and then renamed to the file.
+/// The folder is synced too, so the new file survives a crash once this returns.
+///
+/// # Examples
+///
+/// ```ignore
+/// utils::write_file("tablas", "clientes.csv", &vec!["id,nombre".to_string(), "1,Juan".to_string()]).unwrap();
+/// ```
+///
+pub fn write_file(folder_path: &str, file_name: &str, lines: &[String]) -> Result<(), SqlError> {
+    let mut temp_file = TempFile::create(folder_path, file_name)?;
+    for line in lines {
+        writeln!(temp_file, "{}", line).map_err(|_| SqlError::Error)?;
+    }
+    temp_file.sync()?;
+
+    sync_folder(folder_path)?;
+    let path = folder_path.to_string() + "/" + file_name;
+    fs::rename(&temp_file.path, path).map_err(|_| SqlError::Error)?;
+    sync_folder(folder_path)
+}
+
+/// Syncs the entries of the folder to disk, so the files created, renamed or removed in it survive a crash.
+pub fn sync_folder(folder_path: &str) -> Result<(), SqlError> {
+    let folder = File::open(folder_path).map_err(|_| SqlError::Error)?;
+    folder.sync_all().map_err(|_| SqlError::Error)
+}
+
+/// Returns true if the process with the given id is running, or if it can't be known.
+pub fn is_process_running(pid: u32) -> bool {
+    let processes = Path::new("/proc");
+    !processes.is_dir() || processes.join(pid.to_string()).exists()
+}
+
+/// Removes the temporary files left in the folder by processes that ended before renaming them.
+pub fn remove_stale_temp_files(folder_path: &str) -> Result<(), SqlError> {
+    let entries = fs::read_dir(folder_path).map_err(|_| SqlError::InvalidTable)?;
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(name) = file_name.strip_suffix(".tmp") else {
+            continue;
+        };
+        let pid = name
+            .rsplit('.')
+            .nth(1)
+            .and_then(|pid| pid.parse::<u32>().ok());
+
+        if pid.is_some_and(|pid| pid != process::id() && !is_process_running(pid)) {
+            fs::remove_file(entry.path()).map_err(|_| SqlError::Error)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns an iterator over the lines of the reader, along with the byte offset where each line starts.
+///
+/// The line terminators are removed, like in `BufRead::lines`.
 ///
 /// # Examples
 ///
+/// ```ignore
+/// let reader = BufReader::new("id,name\n1,Alen\n".as_bytes());
+/// let lines: Vec<(u64, String)> = utils::lines_with_offsets(reader).map(|l| l.unwrap()).collect();
+///
+/// assert_eq!(lines, vec![(0, "id,name".to_string()), (8, "1,Alen".to_string())]);
 /// ```
+///
+pub fn lines_with_offsets<R: BufRead>(
+    mut reader: R,
+) -> impl Iterator<Item = Result<(u64, String), SqlError>> {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(read) => {
+                let start = offset;
+                offset += read as u64;
+
+                let line = line.strip_suffix('\n').unwrap_or(&line);
+                let line = line.strip_suffix('\r').unwrap_or(line);
+
+                Some(Ok((start, line.to_string())))
+            }
+            Err(_) => Some(Err(SqlError::Error)),
+        }
+    })
+}
+
+/// Returns true if the token can be converted to an i32 value.
+///
+/// # Examples
+///
+/// ```ignore
 /// let token = "123";
 /// let result = utils::is_number(token);
 /// assert_eq!(result, true);
@@ -128,6 +338,16 @@ pub fn is_into(token: &str) -> bool {
     token == "INTO"
 }
 
+/// Returns true if the token is equal to "RETURNING".
+pub fn is_returning(token: &str) -> bool {
+    token == "RETURNING"
+}
+
+/// Returns true if the token is equal to "AS".
+pub fn is_as(token: &str) -> bool {
+    token == "AS"
+}
+
 /// Returns true if the token is equal to "FROM".
 pub fn is_from(token: &str) -> bool {
     token == "FROM"
@@ -138,6 +358,11 @@ pub fn is_order(token1: &str) -> bool {
     token1 == "ORDER"
 }
 
+/// Returns true if the token is equal to "LIMIT".
+pub fn is_limit(token: &str) -> bool {
+    token == "LIMIT"
+}
+
 /// Returns true if the token is equal to "BY".
 pub fn is_by(token1: &str) -> bool {
     token1 == "BY"
@@ -157,3 +382,108 @@ pub fn is_set(token: &str) -> bool {
 pub fn is_values(token: &str) -> bool {
     token == "VALUES"
 }
+
+/// Returns true if the token is equal to "PRIMARY".
+pub fn is_primary(token: &str) -> bool {
+    token == "PRIMARY"
+}
+
+/// Returns true if the token is equal to "KEY".
+pub fn is_key(token: &str) -> bool {
+    token == "KEY"
+}
+
+/// Returns true if the token is equal to "UNIQUE".
+pub fn is_unique(token: &str) -> bool {
+    token == "UNIQUE"
+}
+
+/// Returns true if the token is equal to "NULL".
+pub fn is_null(token: &str) -> bool {
+    token == "NULL"
+}
+
+/// Returns true if the token is equal to "DEFAULT".
+pub fn is_default(token: &str) -> bool {
+    token == "DEFAULT"
+}
+
+/// Returns true if the token is equal to "CHECK".
+pub fn is_check(token: &str) -> bool {
+    token == "CHECK"
+}
+
+/// Returns true if the token is equal to "FOREIGN".
+pub fn is_foreign(token: &str) -> bool {
+    token == "FOREIGN"
+}
+
+/// Returns true if the token is equal to "REFERENCES".
+pub fn is_references(token: &str) -> bool {
+    token == "REFERENCES"
+}
+
+/// Returns true if the token is equal to "ON".
+pub fn is_on(token: &str) -> bool {
+    token == "ON"
+}
+
+/// Returns true if the token is equal to "CREATE".
+pub fn is_create(token: &str) -> bool {
+    token == "CREATE"
+}
+
+/// Returns true if the token is equal to "INDEX".
+pub fn is_index(token: &str) -> bool {
+    token == "INDEX"
+}
+
+/// Returns true if the token is equal to "SEQUENCE".
+pub fn is_sequence(token: &str) -> bool {
+    token == "SEQUENCE"
+}
+
+/// Returns true if the token is equal to "AUTOINCREMENT".
+pub fn is_autoincrement(token: &str) -> bool {
+    token == "AUTOINCREMENT"
+}
+
+/// Returns true if the token is equal to "GENERATED".
+pub fn is_generated(token: &str) -> bool {
+    token == "GENERATED"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remove_stale_temp_files, write_file};
+    use std::{fs, process};
+
+    #[test]
+    fn write_file_and_remove_stale_temp_files() {
+        let folder = std::env::temp_dir().join("rustic_sql_utils_temp_files");
+        fs::create_dir_all(&folder).unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let stale = folder.join(format!(".temp.csv.{}.0.tmp", u32::MAX));
+        let running = folder.join(format!(".temp.csv.{}.0.tmp", process::id()));
+        fs::write(&stale, "id\n").unwrap();
+        fs::write(&running, "id\n").unwrap();
+
+        write_file(
+            folder_path,
+            "temp.csv",
+            &[String::from("id"), String::from("1")],
+        )
+        .unwrap();
+        remove_stale_temp_files(folder_path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(folder.join("temp.csv")).unwrap(),
+            "id\n1\n"
+        );
+        assert!(!stale.exists());
+        assert!(running.exists());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
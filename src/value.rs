@@ -0,0 +1,125 @@
+use std::{borrow::Cow, fmt};
+
+/// Enum for the value of a column in a register.
+///
+/// The tables store every value as text, so the type of a value is given by its content:
+///
+/// - `Null`: an empty value.
+/// - `Integer`: a whole number, written without a plus sign or leading zeros.
+/// - `Text`: any other value.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Text(String),
+}
+
+impl Value {
+    /// Returns the value stored as text in a table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_sql::Value;
+    ///
+    /// assert_eq!(Value::from_csv(""), Value::Null);
+    /// assert_eq!(Value::from_csv("-12"), Value::Integer(-12));
+    /// assert_eq!(Value::from_csv("007"), Value::Text(String::from("007")));
+    /// ```
+    ///
+    pub fn from_csv(value: &str) -> Self {
+        if value.is_empty() {
+            return Value::Null;
+        }
+        let digits = value.strip_prefix('-').unwrap_or(value);
+        let canonical = value == "0" || !digits.starts_with(['0', '+']);
+        match value.parse::<i64>() {
+            Ok(integer) if canonical => Value::Integer(integer),
+            _ => Value::Text(value.to_string()),
+        }
+    }
+
+    /// Returns the value as it is stored in a table, borrowing it unless it is an integer.
+    ///
+    /// The `WHERE` and `ORDER BY` clauses compare the values by this text.
+    pub fn to_csv(&self) -> Cow<'_, str> {
+        match self {
+            Value::Null => Cow::Borrowed(""),
+            Value::Integer(integer) => Cow::Owned(integer.to_string()),
+            Value::Text(text) => Cow::Borrowed(text),
+        }
+    }
+
+    /// Returns whether the value is empty.
+    pub fn is_null(&self) -> bool {
+        *self == Value::Null
+    }
+
+    /// Returns the number if the value is an integer.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    /// Returns the text if the value is not empty nor an integer.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(integer: i64) -> Self {
+        Value::Integer(integer)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+/// Shows the value as it is stored in the tables.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Integer(integer) => write!(f, "{}", integer),
+            Value::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+
+    #[test]
+    fn from_csv() {
+        assert_eq!(Value::from_csv(""), Value::Null);
+        assert_eq!(Value::from_csv("42"), Value::Integer(42));
+        assert_eq!(Value::from_csv("-42"), Value::Integer(-42));
+        assert_eq!(Value::from_csv("+42"), Value::Text(String::from("+42")));
+        assert_eq!(Value::from_csv("042"), Value::Text(String::from("042")));
+        assert_eq!(Value::from_csv("0"), Value::Integer(0));
+        assert_eq!(Value::from_csv("-0"), Value::Text(String::from("-0")));
+        assert_eq!(Value::from_csv("Juan"), Value::Text(String::from("Juan")));
+
+        for value in ["", "42", "042", "-0", "Juan"] {
+            assert_eq!(Value::from_csv(value).to_string(), value);
+            assert_eq!(Value::from_csv(value).to_csv(), value);
+        }
+    }
+}
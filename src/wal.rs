@@ -0,0 +1,391 @@
+use crate::{
+    errors::SqlError,
+    index::Index,
+    snapshot::publish,
+    utils::{sync_folder, temp_file_path},
+};
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static LOGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Enum for the changes recorded in the write-ahead log.
+///
+/// - `Rename`: the file is replaced with the one in the `from` path. Replayed on recovery if the `from` file still exists.
+/// - `Remove`: the file is removed. Replayed on recovery.
+/// - `Truncate`: the file had the given length before a register was appended to it. On recovery the append is rolled back.
+///
+#[derive(Debug, PartialEq)]
+pub enum WalRecord {
+    Rename { file: String, from: String },
+    Remove { file: String },
+    Truncate { file: String, len: u64 },
+}
+
+/// Struct that represents the write-ahead log of a folder.
+///
+/// Before a statement changes the files of the folder, its changes are recorded in a hidden `.wal_<pid>_<n>.log` file of the statement,
+/// ended by a `COMMIT` line, which is synced to disk. The statement holds an exclusive lock over its log until the log is removed,
+/// once every change was made. So a log found unlocked belongs to a statement that was interrupted: its changes are replayed,
+/// or rolled back in the case of appends. A log without the `COMMIT` line was interrupted before any change was made, and is discarded.
+///
+/// # Fields
+///
+/// * `folder_path` - The path to the folder where the tables are stored.
+/// * `records` - The changes of the statement.
+/// * `path` - The path of the log of the statement, unique to it.
+/// * `file` - The log file, locked by the statement, once it was stored.
+///
+#[derive(Debug)]
+pub struct Wal {
+    pub folder_path: String,
+    pub records: Vec<WalRecord>,
+    path: PathBuf,
+    file: Option<File>,
+}
+
+fn parse_records(content: &str) -> Option<Vec<WalRecord>> {
+    let mut records = Vec::new();
+    let mut lines = content.lines();
+
+    while let Some(line) = lines.next() {
+        let mut fields = line.splitn(3, ' ');
+        let record = match (fields.next()?, fields.next(), fields.next()) {
+            ("COMMIT", None, None) => return lines.next().is_none().then_some(records),
+            ("RENAME", Some(file), Some(from)) => WalRecord::Rename {
+                file: file.to_string(),
+                from: from.to_string(),
+            },
+            ("REMOVE", Some(file), None) => WalRecord::Remove {
+                file: file.to_string(),
+            },
+            ("TRUNCATE", Some(file), Some(len)) => WalRecord::Truncate {
+                file: file.to_string(),
+                len: len.parse::<u64>().ok()?,
+            },
+            _ => return None,
+        };
+        records.push(record);
+    }
+
+    None
+}
+
+fn recover_log(folder_path: &str, path: &Path) -> Result<(), SqlError> {
+    let Ok(mut file) = File::open(path) else {
+        return Ok(());
+    };
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Ok(()),
+        Err(TryLockError::Error(_)) => return Err(SqlError::Error),
+    }
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|_| SqlError::Error)?;
+
+    if let Some(records) = parse_records(&content) {
+        let wal = Wal {
+            folder_path: folder_path.to_string(),
+            records,
+            path: path.to_path_buf(),
+            file: None,
+        };
+        wal.apply()?;
+        wal.rollback()?;
+        wal.rebuild_indexes()?;
+    }
+
+    fs::remove_file(path).map_err(|_| SqlError::Error)?;
+    sync_folder(folder_path)
+}
+
+impl Wal {
+    /// Creates a new log without records for the given folder.
+    pub fn new(folder_path: &str) -> Self {
+        let name = format!(
+            ".wal_{}_{}.log",
+            process::id(),
+            LOGS.fetch_add(1, Ordering::Relaxed)
+        );
+
+        Self {
+            folder_path: folder_path.to_string(),
+            records: Vec::new(),
+            path: Path::new(folder_path).join(name),
+            file: None,
+        }
+    }
+
+    /// Adds a change to the log.
+    pub fn push(&mut self, record: WalRecord) {
+        self.records.push(record);
+    }
+
+    /// Stores the log in the folder, followed by the `COMMIT` line, and syncs it to disk.
+    ///
+    /// The log is written to a temporary file, which is locked before it's renamed to the log, so other statements
+    /// never recover a log that is still in use. Once this returns, the changes of the log survive a crash.
+    pub fn log(&mut self) -> Result<(), SqlError> {
+        let mut content = String::new();
+
+        for record in &self.records {
+            match record {
+                WalRecord::Rename { file, from } => {
+                    content.push_str(&format!("RENAME {} {}\n", file, from))
+                }
+                WalRecord::Remove { file } => content.push_str(&format!("REMOVE {}\n", file)),
+                WalRecord::Truncate { file, len } => {
+                    content.push_str(&format!("TRUNCATE {} {}\n", file, len))
+                }
+            }
+        }
+        content.push_str("COMMIT\n");
+
+        let temp_path = temp_file_path(&self.folder_path, "wal.log");
+        let mut file = File::create(&temp_path).map_err(|_| SqlError::Error)?;
+        file.lock().map_err(|_| SqlError::Error)?;
+
+        let result = (|| {
+            file.write_all(content.as_bytes())
+                .map_err(|_| SqlError::Error)?;
+            file.sync_all().map_err(|_| SqlError::Error)?;
+            fs::rename(&temp_path, &self.path).map_err(|_| SqlError::Error)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+            return result;
+        }
+        self.file = Some(file);
+
+        sync_folder(&self.folder_path)
+    }
+
+    /// Records the changes in the log, makes them and removes the log.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut wal = Wal::new("tablas");
+    /// wal.push(WalRecord::Rename { file: "clientes.csv".to_string(), from: "tablas/.clientes.csv.1.0.tmp".to_string() });
+    /// wal.commit().unwrap();
+    /// ```
+    ///
+    pub fn commit(mut self) -> Result<(), SqlError> {
+        self.log()?;
+        self.apply()?;
+        self.clear()
+    }
+
+    /// Removes the log of the statement from the folder, once the changes it records were made or are no longer needed,
+    /// and releases its lock.
+    pub fn clear(self) -> Result<(), SqlError> {
+        if self.path.exists() {
+            fs::remove_file(&self.path).map_err(|_| SqlError::Error)?;
+            sync_folder(&self.folder_path)?;
+        }
+        Ok(())
+    }
+
+    /// Replays or rolls back the changes of the logs left in the folder by interrupted statements, and removes them.
+    ///
+    /// Only the logs that are not locked are recovered, since the statement that stored a log holds its lock until it
+    /// removes the log, or until its process ends. The lock is taken while the log is recovered, so two statements don't
+    /// recover it at the same time. The indexes of the tables changed by the logs are rebuilt.
+    pub fn recover(folder_path: &str) -> Result<(), SqlError> {
+        let entries = fs::read_dir(folder_path).map_err(|_| SqlError::InvalidTable)?;
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.starts_with(".wal_") && file_name.ends_with(".log") {
+                recover_log(folder_path, &entry.path())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rolls back the appends recorded in the log of a statement that failed, and removes the log.
+    pub fn abort(self) -> Result<(), SqlError> {
+        self.rollback()?;
+        self.rebuild_indexes()?;
+        self.clear()
+    }
+
+    fn apply(&self) -> Result<(), SqlError> {
+        let folder = Path::new(&self.folder_path);
+
+        for record in &self.records {
+            match record {
+                WalRecord::Rename { file, from } => {
+                    if Path::new(from).exists() {
+                        fs::rename(from, folder.join(file)).map_err(|_| SqlError::Error)?;
+                    }
+                    if let Some(table) = file.strip_suffix(".csv") {
+                        publish(&self.folder_path, table)?;
+                    }
+                }
+                WalRecord::Remove { file } => {
+                    if folder.join(file).exists() {
+                        fs::remove_file(folder.join(file)).map_err(|_| SqlError::Error)?;
+                    }
+                }
+                WalRecord::Truncate { .. } => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn rollback(&self) -> Result<(), SqlError> {
+        for record in &self.records {
+            if let WalRecord::Truncate { file: name, len } = record {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .open(Path::new(&self.folder_path).join(name))
+                    .map_err(|_| SqlError::Error)?;
+                file.set_len(*len).map_err(|_| SqlError::Error)?;
+                file.sync_all().map_err(|_| SqlError::Error)?;
+
+                if let Some(table) = name.strip_suffix(".csv") {
+                    publish(&self.folder_path, table)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn rebuild_indexes(&self) -> Result<(), SqlError> {
+        for table in self.tables() {
+            for mut index in Index::for_table(&self.folder_path, &table)? {
+                index.rebuild(&self.folder_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn tables(&self) -> Vec<String> {
+        self.records
+            .iter()
+            .filter_map(|record| match record {
+                WalRecord::Rename { file, .. } | WalRecord::Truncate { file, .. } => {
+                    file.strip_suffix(".csv").map(String::from)
+                }
+                WalRecord::Remove { .. } => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_records, Wal, WalRecord};
+    use std::fs;
+
+    #[test]
+    fn parse_complete_and_truncated_logs() {
+        let content = "REMOVE clientes.index\nTRUNCATE ordenes.csv 42\nRENAME clientes.schema /tmp/a b/clientes.schema\nCOMMIT\n";
+        assert_eq!(
+            parse_records(content),
+            Some(vec![
+                WalRecord::Remove {
+                    file: String::from("clientes.index"),
+                },
+                WalRecord::Truncate {
+                    file: String::from("ordenes.csv"),
+                    len: 42,
+                },
+                WalRecord::Rename {
+                    file: String::from("clientes.schema"),
+                    from: String::from("/tmp/a b/clientes.schema"),
+                },
+            ])
+        );
+
+        assert_eq!(parse_records("REMOVE clientes.index\n"), None);
+        assert_eq!(
+            parse_records("REMOVE clientes.index\nCOMMIT\nREMOVE\n"),
+            None
+        );
+        assert_eq!(parse_records("TRUNCATE ordenes.csv 4"), None);
+    }
+
+    #[test]
+    fn recover_replays_and_rolls_back() {
+        let folder = std::env::temp_dir().join("rustic_sql_wal_recover");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n1\n").unwrap();
+        fs::write(folder.join("ordenes.csv"), "id\n101\n10").unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        let temp = folder.join(".clientes.csv.tmp");
+        fs::write(&temp, "id\n2\n").unwrap();
+
+        let mut wal = Wal::new(folder_path);
+        wal.push(WalRecord::Rename {
+            file: String::from("clientes.csv"),
+            from: temp.to_string_lossy().to_string(),
+        });
+        wal.push(WalRecord::Truncate {
+            file: String::from("ordenes.csv"),
+            len: 7,
+        });
+        wal.log().unwrap();
+
+        Wal::recover(folder_path).unwrap();
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n"
+        );
+
+        drop(wal);
+        Wal::recover(folder_path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n2\n"
+        );
+        assert_eq!(
+            fs::read_to_string(folder.join("ordenes.csv")).unwrap(),
+            "id\n101\n"
+        );
+        assert!(fs::read_dir(&folder)
+            .unwrap()
+            .flatten()
+            .all(|entry| !entry.file_name().to_string_lossy().starts_with(".wal_")));
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn recover_discards_incomplete_log() {
+        let folder = std::env::temp_dir().join("rustic_sql_wal_incomplete");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("clientes.csv"), "id\n1\n").unwrap();
+        fs::write(
+            folder.join(format!(".wal_{}.log", u32::MAX)),
+            "REMOVE clientes.csv\n",
+        )
+        .unwrap();
+        let folder_path = folder.to_str().unwrap();
+
+        Wal::recover(folder_path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+            "id\n1\n"
+        );
+        assert!(!folder.join(format!(".wal_{}.log", u32::MAX)).exists());
+
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}